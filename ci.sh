@@ -16,4 +16,10 @@ then
 else
     group cargo test
     group cargo test --features=rt
+    group cargo build --features=rt-signals
+    group cargo test --features=backtrace
+    group cargo test --features=c-abi
+    # i686 binaries run on the same kernel through its ia32 emulation
+    group rustup target add i686-unknown-linux-gnu
+    group cargo test --target=i686-unknown-linux-gnu --features=rt
 fi
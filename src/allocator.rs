@@ -1,5 +1,5 @@
 #![allow(clippy::missing_inline_in_public_items)]
-use crate::{spinlock::SpinLock, syscalls};
+use crate::{spinlock::SpinLock, syscalls, uapi};
 use core::{
     alloc::{GlobalAlloc, Layout},
     ptr,
@@ -94,11 +94,12 @@ impl Allocator {
 }
 
 fn round_to_page(layout: Layout) -> Layout {
-    let remainder = layout.size() % 4096;
+    let page_size = crate::env::page_size();
+    let remainder = layout.size() % page_size;
     let size = if remainder == 0 {
         layout.size()
     } else {
-        layout.size() + 4096 - remainder
+        layout.size() + page_size - remainder
     };
     match Layout::from_size_align(size, layout.align()) {
         Ok(l) => l,
@@ -134,7 +135,7 @@ unsafe impl GlobalAlloc for Allocator {
             .max_by_key(|(_, _, len)| *len)
         {
             *is_used = true;
-            *ptr = syscalls::mremap(*ptr, *len, layout.size(), libc::MREMAP_MAYMOVE)
+            *ptr = syscalls::mremap(*ptr, *len, layout.size(), uapi::MREMAP_MAYMOVE)
                 .unwrap_or(core::ptr::null_mut());
             return *ptr;
         }
@@ -142,9 +143,9 @@ unsafe impl GlobalAlloc for Allocator {
         syscalls::mmap(
             core::ptr::null_mut(),
             layout.size(),
-            libc::PROT_READ | libc::PROT_WRITE,
-            libc::MAP_ANON | libc::MAP_PRIVATE,
-            -1,
+            uapi::PROT_READ | uapi::PROT_WRITE,
+            uapi::MAP_ANON | uapi::MAP_PRIVATE,
+            None,
             0,
         )
         .unwrap_or(core::ptr::null_mut())
@@ -193,11 +194,12 @@ unsafe impl GlobalAlloc for Allocator {
         }
 
         layout = round_to_page(layout);
-        let remainder = new_size % 4096;
+        let page_size = crate::env::page_size();
+        let remainder = new_size % page_size;
         new_size = if remainder == 0 {
             new_size
         } else {
-            new_size + 4096 - remainder
+            new_size + page_size - remainder
         };
 
         if layout.size() >= new_size {
@@ -210,13 +212,13 @@ unsafe impl GlobalAlloc for Allocator {
             if *cache_ptr == ptr {
                 *len = new_size;
                 assert!(*is_used);
-                *cache_ptr = syscalls::mremap(ptr, layout.size(), new_size, libc::MREMAP_MAYMOVE)
+                *cache_ptr = syscalls::mremap(ptr, layout.size(), new_size, uapi::MREMAP_MAYMOVE)
                     .unwrap_or(core::ptr::null_mut());
                 return *cache_ptr;
             }
         }
 
-        syscalls::mremap(ptr, layout.size(), new_size, libc::MREMAP_MAYMOVE)
+        syscalls::mremap(ptr, layout.size(), new_size, uapi::MREMAP_MAYMOVE)
             .unwrap_or(core::ptr::null_mut())
     }
 }
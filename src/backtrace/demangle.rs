@@ -0,0 +1,847 @@
+//! Demangling of Rust symbol names, in both the legacy scheme and the v0 scheme
+//!
+//! Names are printed without hashes or crate disambiguators, the way `rustc-demangle` prints them
+//! in its alternate form.
+
+use alloc::{string::String, vec::Vec};
+use core::{convert::TryFrom, mem};
+
+/// Demangle a Rust symbol, or return it unchanged if it is not one
+#[inline]
+pub fn demangle(symbol: &str) -> String {
+    // LLVM appends a suffix to the names of some local symbols
+    let symbol_without_suffix = match symbol.find(".llvm.") {
+        Some(i) => &symbol[..i],
+        None => symbol,
+    };
+    legacy(symbol_without_suffix)
+        .or_else(|| v0(symbol_without_suffix))
+        .unwrap_or_else(|| String::from(symbol))
+}
+
+fn legacy(symbol: &str) -> Option<String> {
+    let inner = symbol
+        .strip_prefix("_ZN")
+        .or_else(|| symbol.strip_prefix("ZN"))
+        .or_else(|| symbol.strip_prefix("__ZN"))?;
+    let mut rest = inner.as_bytes();
+
+    let mut elements = Vec::new();
+    while rest.first() != Some(&b'E') {
+        let digits = rest.iter().take_while(|b| b.is_ascii_digit()).count();
+        let mut len = 0usize;
+        for digit in &rest[..digits] {
+            len = len
+                .checked_mul(10)?
+                .checked_add(usize::from(digit - b'0'))?;
+        }
+        rest = &rest[digits..];
+        if len == 0 || len > rest.len() {
+            return None;
+        }
+        elements.push(core::str::from_utf8(&rest[..len]).ok()?);
+        rest = &rest[len..];
+    }
+    if rest.len() != 1 || elements.is_empty() {
+        return None;
+    }
+
+    // The last element is a hash of the crate and the function's signature
+    if elements.len() > 1 && is_legacy_hash(elements[elements.len() - 1]) {
+        elements.pop();
+    }
+
+    let mut out = String::new();
+    for (i, element) in elements.iter().enumerate() {
+        if i > 0 {
+            out.push_str("::");
+        }
+        unescape_legacy(element, &mut out)?;
+    }
+    Some(out)
+}
+
+fn is_legacy_hash(element: &str) -> bool {
+    element.len() == 17
+        && element.starts_with('h')
+        && element[1..].bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn unescape_legacy(element: &str, out: &mut String) -> Option<()> {
+    // An element which would start with `$` is prefixed with `_`, so that it is a valid identifier
+    let mut rest = if element.starts_with("_$") {
+        &element[1..]
+    } else {
+        element
+    };
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("..") {
+            out.push_str("::");
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('$') {
+            let end = after.find('$')?;
+            let escape = &after[..end];
+            let c = match escape {
+                "SP" => '@',
+                "BP" => '*',
+                "RF" => '&',
+                "LT" => '<',
+                "GT" => '>',
+                "LP" => '(',
+                "RP" => ')',
+                "C" => ',',
+                _ => {
+                    let code = escape.strip_prefix('u')?;
+                    core::char::from_u32(u32::from_str_radix(code, 16).ok()?)?
+                }
+            };
+            out.push(c);
+            rest = &after[end + 1..];
+        } else {
+            let end = rest
+                .bytes()
+                .skip(1)
+                .position(|b| b == b'$' || b == b'.')
+                .map_or(rest.len(), |i| i + 1);
+            out.push_str(&rest[..end]);
+            rest = &rest[end..];
+        }
+    }
+    Some(())
+}
+
+fn v0(symbol: &str) -> Option<String> {
+    let inner = symbol
+        .strip_prefix("_R")
+        .or_else(|| symbol.strip_prefix("R"))
+        .or_else(|| symbol.strip_prefix("__R"))?;
+    // Anything after a `.` is a suffix added by some tool, not part of the mangled name
+    let inner = inner.split('.').next()?;
+    // Only the first version of the scheme, which has no version number, exists
+    if !inner.bytes().next()?.is_ascii_uppercase() {
+        return None;
+    }
+
+    let mut parser = V0 {
+        sym: inner.as_bytes(),
+        pos: 0,
+        out: String::new(),
+        skipping: false,
+        bound_lifetimes: 0,
+        depth: 0,
+    };
+    parser.path(true).ok()?;
+    // Then the crate which instantiated a generic function, which is not printed
+    if parser.pos < parser.sym.len() {
+        parser.skipping = true;
+        parser.path(false).ok()?;
+    }
+    if parser.pos != parser.sym.len() {
+        return None;
+    }
+    Some(parser.out)
+}
+
+struct Invalid;
+
+// Mangled names are untrusted input, so recursion through nested paths and types is bounded
+const MAX_DEPTH: u32 = 256;
+
+// Back references can cite each other to double the output at every level, so its length is
+// bounded too, at the same size as `rustc-demangle`'s
+const MAX_LEN: usize = 1_000_000;
+
+struct V0<'s> {
+    sym: &'s [u8],
+    pos: usize,
+    out: String,
+    // Some parts of a name, like the path an impl block is in, are parsed but not printed
+    skipping: bool,
+    bound_lifetimes: u64,
+    depth: u32,
+}
+
+struct Ident<'s> {
+    bytes: &'s [u8],
+    punycode: bool,
+}
+
+impl<'s> V0<'s> {
+    fn peek(&self) -> Option<u8> {
+        self.sym.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, b: u8) -> bool {
+        if self.peek() == Some(b) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn next(&mut self) -> Result<u8, Invalid> {
+        let b = self.peek().ok_or(Invalid)?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    fn print(&mut self, s: &str) {
+        if !self.skipping {
+            self.out.push_str(s);
+        }
+    }
+
+    fn print_char(&mut self, c: char) {
+        if !self.skipping {
+            self.out.push(c);
+        }
+    }
+
+    fn print_decimal(&mut self, mut n: u128) {
+        let mut digits = [0u8; 39];
+        let mut start = digits.len();
+        loop {
+            start -= 1;
+            digits[start] = b'0' + (n % 10) as u8;
+            n /= 10;
+            if n == 0 {
+                break;
+            }
+        }
+        // Only ASCII digits were written
+        self.print(core::str::from_utf8(&digits[start..]).unwrap_or_default());
+    }
+
+    fn base62(&mut self) -> Result<u64, Invalid> {
+        if self.eat(b'_') {
+            return Ok(0);
+        }
+        let mut x = 0u64;
+        loop {
+            let digit = match self.next()? {
+                b'_' => return x.checked_add(1).ok_or(Invalid),
+                c @ b'0'..=b'9' => c - b'0',
+                c @ b'a'..=b'z' => c - b'a' + 10,
+                c @ b'A'..=b'Z' => c - b'A' + 36,
+                _ => return Err(Invalid),
+            };
+            x = x
+                .checked_mul(62)
+                .and_then(|x| x.checked_add(u64::from(digit)))
+                .ok_or(Invalid)?;
+        }
+    }
+
+    /// A base-62 number after `tag`, which is offset by one so that 0 means the tag is absent
+    fn opt_base62(&mut self, tag: u8) -> Result<u64, Invalid> {
+        if !self.eat(tag) {
+            return Ok(0);
+        }
+        self.base62()?.checked_add(1).ok_or(Invalid)
+    }
+
+    fn decimal(&mut self) -> Result<usize, Invalid> {
+        let first = self.next()?;
+        if !first.is_ascii_digit() {
+            return Err(Invalid);
+        }
+        let mut x = usize::from(first - b'0');
+        if x == 0 {
+            return Ok(0);
+        }
+        while let Some(digit @ b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+            x = x
+                .checked_mul(10)
+                .and_then(|x| x.checked_add(usize::from(digit - b'0')))
+                .ok_or(Invalid)?;
+        }
+        Ok(x)
+    }
+
+    fn hex(&mut self) -> Result<u128, Invalid> {
+        let mut x = 0u128;
+        loop {
+            let digit = match self.next()? {
+                b'_' => return Ok(x),
+                c @ b'0'..=b'9' => c - b'0',
+                c @ b'a'..=b'f' => c - b'a' + 10,
+                _ => return Err(Invalid),
+            };
+            x = x
+                .checked_mul(16)
+                .and_then(|x| x.checked_add(u128::from(digit)))
+                .ok_or(Invalid)?;
+        }
+    }
+
+    fn ident(&mut self) -> Result<Ident<'s>, Invalid> {
+        let punycode = self.eat(b'u');
+        let len = self.decimal()?;
+        // Separates the length from identifiers which start with a digit or `_`
+        self.eat(b'_');
+        let start = self.pos;
+        let end = start.checked_add(len).ok_or(Invalid)?;
+        let bytes = self.sym.get(start..end).ok_or(Invalid)?;
+        self.pos = end;
+        Ok(Ident { bytes, punycode })
+    }
+
+    fn print_ident(&mut self, ident: &Ident<'_>) -> Result<(), Invalid> {
+        if self.skipping {
+            return Ok(());
+        }
+        if !ident.punycode {
+            let ident = core::str::from_utf8(ident.bytes).map_err(|_| Invalid)?;
+            self.out.push_str(ident);
+            return Ok(());
+        }
+        // The ASCII characters come first, then the rest encoded with `_` in place of `-`
+        let (ascii, encoded) = match ident.bytes.iter().rposition(|b| *b == b'_') {
+            Some(i) => (&ident.bytes[..i], &ident.bytes[i + 1..]),
+            None => (&[][..], ident.bytes),
+        };
+        if encoded.is_empty() {
+            return Err(Invalid);
+        }
+        let decoded = punycode_decode(ascii, encoded).ok_or(Invalid)?;
+        self.out.extend(decoded);
+        Ok(())
+    }
+
+    fn print_lifetime(&mut self, lifetime: u64) -> Result<(), Invalid> {
+        if lifetime == 0 {
+            self.print("'_");
+            return Ok(());
+        }
+        let depth = self.bound_lifetimes.checked_sub(lifetime).ok_or(Invalid)?;
+        self.print_char('\'');
+        if depth < 26 {
+            self.print_char((b'a' + depth as u8) as char);
+        } else {
+            self.print_char('_');
+            self.print_decimal(u128::from(depth));
+        }
+        Ok(())
+    }
+
+    fn nested<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T, Invalid>) -> Result<T, Invalid> {
+        if self.out.len() > MAX_LEN {
+            return Err(Invalid);
+        }
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(Invalid);
+        }
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn skip<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T, Invalid>) -> Result<T, Invalid> {
+        let skipping = mem::replace(&mut self.skipping, true);
+        let result = f(self);
+        self.skipping = skipping;
+        result
+    }
+
+    /// Print the part of the name at an earlier position, which must come after a `B` tag
+    fn backref<T: Default>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<T, Invalid>,
+    ) -> Result<T, Invalid> {
+        let start = self.pos - 1;
+        let target = self.base62()? as usize;
+        if target >= start {
+            return Err(Invalid);
+        }
+        // Nothing that is skipped needs to be parsed, and following the reference could be slow
+        if self.skipping {
+            return Ok(T::default());
+        }
+        let resume = mem::replace(&mut self.pos, target);
+        let result = self.nested(f);
+        self.pos = resume;
+        result
+    }
+
+    fn path(&mut self, in_value: bool) -> Result<(), Invalid> {
+        self.nested(|p| match p.next()? {
+            // The root of a crate
+            b'C' => {
+                p.opt_base62(b's')?;
+                let name = p.ident()?;
+                p.print_ident(&name)
+            }
+            b'N' => {
+                let namespace = p.next()?;
+                p.path(in_value)?;
+                let disambiguator = p.opt_base62(b's')?;
+                let name = p.ident()?;
+                match namespace {
+                    // Special namespaces, like closures, are shown as `{closure#0}`
+                    b'A'..=b'Z' => {
+                        p.print("::{");
+                        match namespace {
+                            b'C' => p.print("closure"),
+                            b'S' => p.print("shim"),
+                            _ => p.print_char(namespace as char),
+                        }
+                        if !name.bytes.is_empty() {
+                            p.print(":");
+                            p.print_ident(&name)?;
+                        }
+                        p.print("#");
+                        p.print_decimal(u128::from(disambiguator));
+                        p.print("}");
+                        Ok(())
+                    }
+                    b'a'..=b'z' => {
+                        if !name.bytes.is_empty() {
+                            p.print("::");
+                            p.print_ident(&name)?;
+                        }
+                        Ok(())
+                    }
+                    _ => Err(Invalid),
+                }
+            }
+            // Inherent impls, trait impls, and trait definitions
+            tag @ (b'M' | b'X' | b'Y') => {
+                if tag != b'Y' {
+                    p.opt_base62(b's')?;
+                    p.skip(|p| p.path(false))?;
+                }
+                p.print("<");
+                p.ty()?;
+                if tag != b'M' {
+                    p.print(" as ");
+                    p.path(false)?;
+                }
+                p.print(">");
+                Ok(())
+            }
+            b'I' => {
+                p.path(in_value)?;
+                if in_value {
+                    p.print("::");
+                }
+                p.print("<");
+                p.generic_args()?;
+                p.print(">");
+                Ok(())
+            }
+            b'B' => p.backref(|p| p.path(in_value)),
+            _ => Err(Invalid),
+        })
+    }
+
+    /// Print a path, leaving its list of generic arguments open if it has one so that associated
+    /// type bindings can be added to it. Returns whether the list was left open.
+    fn path_maybe_open_generics(&mut self) -> Result<bool, Invalid> {
+        if self.eat(b'B') {
+            self.backref(|p| p.path_maybe_open_generics())
+        } else if self.eat(b'I') {
+            self.path(false)?;
+            self.print("<");
+            self.generic_args()?;
+            Ok(true)
+        } else {
+            self.path(false)?;
+            Ok(false)
+        }
+    }
+
+    /// Print arguments up to the terminating `E`, without the surrounding brackets
+    fn generic_args(&mut self) -> Result<(), Invalid> {
+        let mut i = 0;
+        while !self.eat(b'E') {
+            if i > 0 {
+                self.print(", ");
+            }
+            if self.eat(b'L') {
+                let lifetime = self.base62()?;
+                self.print_lifetime(lifetime)?;
+            } else if self.eat(b'K') {
+                self.constant()?;
+            } else {
+                self.ty()?;
+            }
+            i += 1;
+        }
+        Ok(())
+    }
+
+    fn in_binder(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<(), Invalid>,
+    ) -> Result<(), Invalid> {
+        let bound = self.opt_base62(b'G')?;
+        if bound > 0 {
+            self.print("for<");
+            for i in 0..bound {
+                if i > 0 {
+                    self.print(", ");
+                }
+                self.bound_lifetimes += 1;
+                self.print_lifetime(1)?;
+            }
+            self.print("> ");
+        }
+        let result = f(self);
+        self.bound_lifetimes -= bound;
+        result
+    }
+
+    fn ty(&mut self) -> Result<(), Invalid> {
+        self.nested(|p| {
+            let tag = p.next()?;
+            if let Some(name) = basic_type(tag) {
+                p.print(name);
+                return Ok(());
+            }
+            match tag {
+                b'R' | b'Q' => {
+                    p.print("&");
+                    if p.eat(b'L') {
+                        let lifetime = p.base62()?;
+                        if lifetime != 0 {
+                            p.print_lifetime(lifetime)?;
+                            p.print(" ");
+                        }
+                    }
+                    if tag == b'Q' {
+                        p.print("mut ");
+                    }
+                    p.ty()
+                }
+                b'P' => {
+                    p.print("*const ");
+                    p.ty()
+                }
+                b'O' => {
+                    p.print("*mut ");
+                    p.ty()
+                }
+                b'A' | b'S' => {
+                    p.print("[");
+                    p.ty()?;
+                    if tag == b'A' {
+                        p.print("; ");
+                        p.constant()?;
+                    }
+                    p.print("]");
+                    Ok(())
+                }
+                b'T' => {
+                    p.print("(");
+                    let mut count = 0;
+                    while !p.eat(b'E') {
+                        if count > 0 {
+                            p.print(", ");
+                        }
+                        p.ty()?;
+                        count += 1;
+                    }
+                    if count == 1 {
+                        p.print(",");
+                    }
+                    p.print(")");
+                    Ok(())
+                }
+                b'F' => p.in_binder(|p| p.fn_sig()),
+                b'D' => {
+                    p.print("dyn ");
+                    p.in_binder(|p| {
+                        let mut count = 0;
+                        while !p.eat(b'E') {
+                            if count > 0 {
+                                p.print(" + ");
+                            }
+                            p.dyn_trait()?;
+                            count += 1;
+                        }
+                        Ok(())
+                    })?;
+                    if !p.eat(b'L') {
+                        return Err(Invalid);
+                    }
+                    let lifetime = p.base62()?;
+                    if lifetime != 0 {
+                        p.print(" + ");
+                        p.print_lifetime(lifetime)?;
+                    }
+                    Ok(())
+                }
+                b'B' => p.backref(|p| p.ty()),
+                // Anything else is the path of a named type
+                _ => {
+                    p.pos -= 1;
+                    p.path(false)
+                }
+            }
+        })
+    }
+
+    fn fn_sig(&mut self) -> Result<(), Invalid> {
+        if self.eat(b'U') {
+            self.print("unsafe ");
+        }
+        if self.eat(b'K') {
+            self.print("extern \"");
+            if self.eat(b'C') {
+                self.print("C");
+            } else {
+                // `-` is not allowed in identifiers, so ABIs like `system-unwind` use `_`
+                let abi = self.ident()?;
+                if abi.punycode {
+                    return Err(Invalid);
+                }
+                for b in abi.bytes {
+                    self.print_char(if *b == b'_' { '-' } else { *b as char });
+                }
+            }
+            self.print("\" ");
+        }
+        self.print("fn(");
+        let mut count = 0;
+        while !self.eat(b'E') {
+            if count > 0 {
+                self.print(", ");
+            }
+            self.ty()?;
+            count += 1;
+        }
+        self.print(")");
+        if !self.eat(b'u') {
+            self.print(" -> ");
+            self.ty()?;
+        }
+        Ok(())
+    }
+
+    fn dyn_trait(&mut self) -> Result<(), Invalid> {
+        let mut open = self.path_maybe_open_generics()?;
+        while self.eat(b'p') {
+            self.print(if open { ", " } else { "<" });
+            open = true;
+            let name = self.ident()?;
+            self.print_ident(&name)?;
+            self.print(" = ");
+            self.ty()?;
+        }
+        if open {
+            self.print(">");
+        }
+        Ok(())
+    }
+
+    fn constant(&mut self) -> Result<(), Invalid> {
+        if self.eat(b'B') {
+            return self.backref(|p| p.constant());
+        }
+        match self.next()? {
+            // A placeholder for a value that is not known
+            b'p' => self.print("_"),
+            b'h' | b't' | b'm' | b'y' | b'o' | b'j' => {
+                let value = self.hex()?;
+                self.print_decimal(value);
+            }
+            b'a' | b's' | b'l' | b'x' | b'n' | b'i' => {
+                if self.eat(b'n') {
+                    self.print("-");
+                }
+                let value = self.hex()?;
+                self.print_decimal(value);
+            }
+            b'b' => match self.hex()? {
+                0 => self.print("false"),
+                1 => self.print("true"),
+                _ => return Err(Invalid),
+            },
+            b'c' => {
+                let value = u32::try_from(self.hex()?).map_err(|_| Invalid)?;
+                let c = core::char::from_u32(value).ok_or(Invalid)?;
+                self.print_char('\'');
+                self.print_char(c);
+                self.print_char('\'');
+            }
+            _ => return Err(Invalid),
+        }
+        Ok(())
+    }
+}
+
+fn basic_type(tag: u8) -> Option<&'static str> {
+    Some(match tag {
+        b'a' => "i8",
+        b'b' => "bool",
+        b'c' => "char",
+        b'd' => "f64",
+        b'e' => "str",
+        b'f' => "f32",
+        b'h' => "u8",
+        b'i' => "isize",
+        b'j' => "usize",
+        b'l' => "i32",
+        b'm' => "u32",
+        b'n' => "i128",
+        b'o' => "u128",
+        b's' => "i16",
+        b't' => "u16",
+        b'u' => "()",
+        b'v' => "...",
+        b'x' => "i64",
+        b'y' => "u64",
+        b'z' => "!",
+        b'p' => "_",
+        _ => return None,
+    })
+}
+
+/// Decode a punycode string (RFC 3492) whose basic code points are `ascii`
+fn punycode_decode(ascii: &[u8], encoded: &[u8]) -> Option<Vec<char>> {
+    const BASE: u32 = 36;
+    const T_MIN: u32 = 1;
+    const T_MAX: u32 = 26;
+
+    let mut output: Vec<char> = ascii.iter().map(|b| *b as char).collect();
+    let mut n = 0x80u32;
+    let mut i = 0u32;
+    let mut bias = 72u32;
+    let mut encoded = encoded.iter();
+
+    while encoded.len() > 0 {
+        let old_i = i;
+        let mut w = 1u32;
+        let mut k = BASE;
+        loop {
+            let digit = match *encoded.next()? {
+                c @ b'a'..=b'z' => u32::from(c - b'a'),
+                c @ b'0'..=b'9' => u32::from(c - b'0') + 26,
+                _ => return None,
+            };
+            i = i.checked_add(digit.checked_mul(w)?)?;
+            let t = k.saturating_sub(bias).clamp(T_MIN, T_MAX);
+            if digit < t {
+                break;
+            }
+            w = w.checked_mul(BASE - t)?;
+            k += BASE;
+        }
+
+        let len = output.len() as u32 + 1;
+        bias = adapt(i - old_i, len, old_i == 0);
+        n = n.checked_add(i / len)?;
+        i %= len;
+        output.insert(i as usize, core::char::from_u32(n)?);
+        i += 1;
+    }
+    Some(output)
+}
+
+fn adapt(delta: u32, points: u32, first: bool) -> u32 {
+    let mut delta = if first { delta / 700 } else { delta / 2 };
+    delta += delta / points;
+    let mut k = 0;
+    while delta > ((36 - 1) * 26) / 2 {
+        delta /= 36 - 1;
+        k += 36;
+    }
+    k + (36 * delta) / (delta + 38)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy() {
+        assert_eq!(
+            demangle("_ZN4core9panicking9panic_fmt17h0123456789abcdefE"),
+            "core::panicking::panic_fmt"
+        );
+        assert_eq!(
+            demangle("_ZN71_$LT$Test$u20$$u2b$$u20$$u27$static$u20$as$u20$foo..Bar$LT$Test$GT$$GT$3bar17h930b740aa94f1d3aE"),
+            "<Test + 'static as foo::Bar<Test>>::bar"
+        );
+        assert_eq!(
+            demangle("_ZN3foo3bar17h05af221e174051e9E.llvm.8412740553384442545"),
+            "foo::bar"
+        );
+        assert_eq!(demangle("main"), "main");
+        assert_eq!(demangle("_ZN3fooE_"), "_ZN3fooE_");
+    }
+
+    #[test]
+    fn v0() {
+        assert_eq!(demangle("_RNvC6_123foo3bar"), "123foo::bar");
+        assert_eq!(
+            demangle("_RNvNtCs1234_7mycrate3foo3bar"),
+            "mycrate::foo::bar"
+        );
+        assert_eq!(
+            demangle("_RNCNCNgCs6DXkGYLi8lr_2cc5spawn00B5_"),
+            "cc::spawn::{closure#0}::{closure#0}"
+        );
+        assert_eq!(
+            demangle("_RMC0INtC8arrayvec8ArrayVechKj7b_E"),
+            "<arrayvec::ArrayVec<u8, 123>>"
+        );
+        assert_eq!(
+            demangle("_RNvNvMCs4fqI2P2rA04_13const_genericINtB4_3FooKpE3foo3FOO"),
+            "<const_generic::Foo<_>>::foo::FOO"
+        );
+        assert_eq!(
+            demangle("_RINbNbCskIICzLVDPPb_5alloc5alloc8box_freeDINbNiB4_5boxed5FnBoxuEp6OutputuEL_ECs1L9rbBT4NA_7example"),
+            "alloc::alloc::box_free::<dyn alloc::boxed::FnBox<(), Output = ()>>"
+        );
+        assert_eq!(
+            demangle("_RNqCs4fqI2P2rA04_11utf8_identsu30____7hkackfecea1cbdathfdh9hlq6y"),
+            "utf8_idents::საჭმელად_გემრიელი_სადილი"
+        );
+        // Recursion through back references must not run away
+        assert_eq!(demangle("_RNvB_1a"), "_RNvB_1a");
+    }
+
+    #[test]
+    fn v0_output_is_bounded() {
+        // Each tuple holds two back references to the one before, doubling the output each time
+        fn chain(levels: usize) -> String {
+            let mut symbol = String::from("_RINvC1a1fu");
+            let mut previous = symbol.len() - 3;
+            for _ in 0..levels {
+                let backref = format!("B{}_", base62(previous - 1));
+                let start = symbol.len() - 2;
+                symbol.push('T');
+                symbol.push_str(&backref);
+                symbol.push_str(&backref);
+                symbol.push('E');
+                previous = start;
+            }
+            symbol.push('E');
+            symbol
+        }
+        fn base62(mut n: usize) -> String {
+            const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+            let mut digits = Vec::new();
+            loop {
+                digits.push(DIGITS[n % 62]);
+                n /= 62;
+                if n == 0 {
+                    break;
+                }
+            }
+            digits.reverse();
+            String::from_utf8(digits).unwrap()
+        }
+
+        assert_eq!(
+            demangle(&chain(2)),
+            "a::f::<(), ((), ()), (((), ()), ((), ()))>"
+        );
+        let symbol = chain(64);
+        assert_eq!(demangle(&symbol), symbol);
+    }
+}
@@ -0,0 +1,316 @@
+//! Capturing and printing the call stack of the current thread
+//!
+//! Frames are found by following the chain of saved frame pointers, so this only produces useful
+//! results in programs built with `-C force-frame-pointers=yes`. Return addresses are resolved to
+//! function names using the symbol table of the running executable, which must not be stripped.
+
+use crate::{
+    elf::{self, Ehdr, Shdr, Sym},
+    fd::{self, AsFd},
+    io::Write,
+    syscalls::{self, OpenFlags, OpenMode},
+    uapi, CStr, Error,
+};
+use alloc::vec::Vec;
+use core::mem;
+
+mod demangle;
+pub use demangle::demangle;
+
+// Stop following a chain of frame pointers that is probably corrupt
+const MAX_FRAMES: usize = 256;
+
+/// Call `f` with the return address of each frame on the stack, starting with the caller of
+/// `trace`, until `f` returns `false` or the outermost frame is reached
+#[inline(never)]
+pub fn trace<F: FnMut(usize) -> bool>(mut f: F) {
+    let mut fp = frame_pointer();
+    for _ in 0..MAX_FRAMES {
+        if fp == 0 || !fp.is_multiple_of(mem::align_of::<usize>()) {
+            return;
+        }
+        // Every architecture saves the caller's frame pointer with the return address above it
+        let (next, return_address) = unsafe {
+            let frame = fp as *const usize;
+            (*frame, *frame.add(1))
+        };
+        if return_address == 0 || !f(return_address) {
+            return;
+        }
+        // The stack grows down, so every caller's frame is above its callee's
+        if next <= fp {
+            return;
+        }
+        fp = next;
+    }
+}
+
+#[inline(always)]
+fn frame_pointer() -> usize {
+    let fp;
+    #[cfg(target_arch = "x86_64")]
+    unsafe {
+        core::arch::asm!("mov {}, rbp", out(reg) fp, options(nomem, nostack, preserves_flags));
+    }
+    #[cfg(target_arch = "x86")]
+    unsafe {
+        core::arch::asm!("mov {}, ebp", out(reg) fp, options(nomem, nostack, preserves_flags));
+    }
+    #[cfg(target_arch = "aarch64")]
+    unsafe {
+        core::arch::asm!("mov {}, x29", out(reg) fp, options(nomem, nostack, preserves_flags));
+    }
+    fp
+}
+
+/// The return addresses of the frames on the stack at the time it was captured
+pub struct Backtrace {
+    frames: Vec<usize>,
+}
+
+impl Backtrace {
+    /// Record the stack of the calling thread, starting with the caller of `capture`
+    #[inline(never)]
+    pub fn capture() -> Self {
+        let mut frames = Vec::new();
+        // The first frame is `capture` itself
+        let mut skip = 1;
+        trace(|address| {
+            if skip > 0 {
+                skip -= 1;
+            } else {
+                frames.push(address);
+            }
+            true
+        });
+        Self { frames }
+    }
+
+    #[inline]
+    pub fn frames(&self) -> &[usize] {
+        &self.frames
+    }
+
+    /// Write each frame, with the name of its function if the symbol table has one, to `out`
+    #[inline]
+    pub fn print<W: Write>(&self, out: &mut W) -> Result<(), Error> {
+        let symbols = Symbols::load().ok();
+        let mut number = crate::fmt::Buffer::new();
+        out.write_all(b"stack backtrace:\n")?;
+        for (i, address) in self.frames.iter().enumerate() {
+            let index = number.format(i as u64);
+            out.write_all(&b"    "[index.len().min(4)..])?;
+            out.write_all(index)?;
+            out.write_all(b": ")?;
+            write_hex(out, *address)?;
+            // A return address is just past the call, which may be the last instruction of its
+            // function, so look up the address before it
+            match symbols
+                .as_ref()
+                .and_then(|s| s.resolve(address.wrapping_sub(1)))
+            {
+                Some(symbol) => {
+                    out.write_all(b" - ")?;
+                    out.write_all(demangle(symbol.name).as_bytes())?;
+                }
+                None => out.write_all(b" - <unknown>")?,
+            }
+            out.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+}
+
+fn write_hex<W: Write>(out: &mut W, value: usize) -> Result<(), Error> {
+    let mut digits = [0u8; 2 + 2 * mem::size_of::<usize>()];
+    digits[..2].copy_from_slice(b"0x");
+    for (i, digit) in digits[2..].iter_mut().rev().enumerate() {
+        *digit = b"0123456789abcdef"[(value >> (4 * i)) & 0xf];
+    }
+    out.write_all(&digits)
+}
+
+/// Whether panics should print a backtrace, because `RUST_BACKTRACE` is set to something other
+/// than `0`
+#[inline]
+pub fn enabled() -> bool {
+    match crate::env::var(b"RUST_BACKTRACE") {
+        Some(value) => value.as_bytes() != b"0",
+        None => false,
+    }
+}
+
+/// A function from the symbol table which contains an address
+pub struct Symbol<'a> {
+    /// The mangled name of the function
+    pub name: &'a str,
+    /// The address of the start of the function in this process
+    pub address: usize,
+    /// How far into the function the looked-up address is
+    pub offset: usize,
+}
+
+/// The function symbols of the running executable
+pub struct Symbols {
+    image: *mut u8,
+    len: usize,
+    base: usize,
+    functions: Vec<Function>,
+    strtab: (usize, usize),
+}
+
+struct Function {
+    start: usize,
+    size: usize,
+    name: usize,
+}
+
+impl Symbols {
+    /// Map `/proc/self/exe` and read its `.symtab`
+    ///
+    /// A stripped executable has no symbols, so nothing will resolve.
+    #[inline]
+    pub fn load() -> Result<Self, Error> {
+        let fd = syscalls::openat(
+            fd::CWD,
+            CStr::from_bytes(b"/proc/self/exe\0"),
+            OpenFlags::RDONLY | OpenFlags::CLOEXEC,
+            OpenMode::empty(),
+        )?;
+        let image = syscalls::fstat(fd.as_fd()).and_then(|stat| {
+            let len = stat.st_size as usize;
+            syscalls::mmap(
+                core::ptr::null_mut(),
+                len,
+                uapi::PROT_READ,
+                uapi::MAP_PRIVATE,
+                Some(fd.as_fd()),
+                0,
+            )
+            .map(|image| (image, len))
+        });
+        // The mapping stays valid after the file is closed
+        drop(fd);
+        let (image, len) = image?;
+
+        let mut symbols = Self {
+            image,
+            len,
+            base: elf::load_base(),
+            functions: Vec::new(),
+            strtab: (0, 0),
+        };
+        symbols.read_symtab();
+        Ok(symbols)
+    }
+
+    fn bytes(&self) -> &[u8] {
+        unsafe { core::slice::from_raw_parts(self.image, self.len) }
+    }
+
+    /// Read a `T` from the executable, if it is entirely in bounds
+    fn read<T: Copy>(&self, offset: usize) -> Option<T> {
+        let end = offset.checked_add(mem::size_of::<T>())?;
+        let bytes = self.bytes().get(offset..end)?;
+        Some(unsafe { bytes.as_ptr().cast::<T>().read_unaligned() })
+    }
+
+    fn read_symtab(&mut self) {
+        let header = match self.read::<Ehdr>(0) {
+            Some(header) if header.e_ident[..4] == *b"\x7fELF" => header,
+            _ => return,
+        };
+        let section =
+            |i: usize| self.read::<Shdr>(header.e_shoff as usize + i * header.e_shentsize as usize);
+        let symtab = match (0..header.e_shnum as usize)
+            .filter_map(section)
+            .find(|s| s.sh_type == elf::SHT_SYMTAB)
+        {
+            Some(symtab) => symtab,
+            None => return,
+        };
+        let strtab = match section(symtab.sh_link as usize) {
+            Some(strtab) => strtab,
+            None => return,
+        };
+
+        let mut functions = Vec::new();
+        let count = symtab.sh_size as usize / mem::size_of::<Sym>();
+        for i in 0..count {
+            let sym = match self.read::<Sym>(symtab.sh_offset as usize + i * mem::size_of::<Sym>())
+            {
+                Some(sym) => sym,
+                None => break,
+            };
+            if sym.kind() == elf::STT_FUNC && sym.st_shndx != elf::SHN_UNDEF && sym.st_value != 0 {
+                functions.push(Function {
+                    start: sym.st_value as usize,
+                    size: sym.st_size as usize,
+                    name: sym.st_name as usize,
+                });
+            }
+        }
+        functions.sort_unstable_by_key(|f| f.start);
+
+        self.functions = functions;
+        self.strtab = (strtab.sh_offset as usize, strtab.sh_size as usize);
+    }
+
+    /// Find the function which contains `address`
+    #[inline]
+    pub fn resolve(&self, address: usize) -> Option<Symbol<'_>> {
+        let address = address.checked_sub(self.base)?;
+        let i = match self.functions.binary_search_by_key(&address, |f| f.start) {
+            Ok(i) => i,
+            Err(0) => return None,
+            Err(i) => i - 1,
+        };
+        let function = &self.functions[i];
+        let offset = address - function.start;
+        // Symbols for hand-written assembly often have no size
+        if function.size != 0 && offset >= function.size {
+            return None;
+        }
+
+        let (strtab, strtab_len) = self.strtab;
+        let names = self.bytes().get(strtab..strtab.checked_add(strtab_len)?)?;
+        let name = names.get(function.name..)?;
+        let name = &name[..name.iter().position(|b| *b == 0)?];
+        Some(Symbol {
+            name: core::str::from_utf8(name).ok()?,
+            address: function.start + self.base,
+            offset,
+        })
+    }
+}
+
+impl Drop for Symbols {
+    #[inline]
+    fn drop(&mut self) {
+        let _ = unsafe { syscalls::munmap(self.image, self.len) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_own_functions() {
+        crate::env::capture_for_test();
+        let symbols = Symbols::load().unwrap();
+
+        let address = resolves_own_functions as *const () as usize;
+        let symbol = symbols.resolve(address).unwrap();
+        assert_eq!(symbol.address, address);
+        assert_eq!(symbol.offset, 0);
+        assert_eq!(
+            demangle(symbol.name),
+            "veneer::backtrace::tests::resolves_own_functions"
+        );
+
+        let symbol = symbols.resolve(address + 1).unwrap();
+        assert_eq!(symbol.address, address);
+        assert_eq!(symbol.offset, 1);
+    }
+}
@@ -0,0 +1,33 @@
+use crate::{uapi::c_int, Error};
+
+#[thread_local]
+static mut ERRNO: c_int = 0;
+
+/// The address of the calling thread's `errno`, which is what the `errno` macro of both glibc's
+/// and musl's headers reads
+#[cfg_attr(not(test), no_mangle)]
+pub extern "C" fn __errno_location() -> *mut c_int {
+    core::ptr::addr_of_mut!(ERRNO)
+}
+
+pub(super) fn set(error: Error) {
+    unsafe { *__errno_location() = error.0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uapi;
+
+    #[test]
+    fn per_thread() {
+        set(Error(uapi::ENOENT));
+        std::thread::spawn(|| {
+            assert_eq!(unsafe { *__errno_location() }, 0);
+            set(Error(uapi::EBADF));
+        })
+        .join()
+        .unwrap();
+        assert_eq!(unsafe { *__errno_location() }, uapi::ENOENT);
+    }
+}
@@ -0,0 +1,34 @@
+//! The parts of libc that small C objects linked into a veneer program usually need, exported
+//! with C linkage when the `c-abi` feature is enabled
+//!
+//! This is for programs which do not link a libc at all. The allocation functions have a heap of
+//! their own, separate from Rust's global allocator, and failures set the thread's `errno` the
+//! way C code expects. Only a small subset of each header is here: see the functions for what
+//! they support.
+//!
+//! The symbols are left mangled in this crate's own unit tests, which are linked with glibc.
+
+// Exported functions cannot be inlined into C callers anyway
+#![allow(clippy::missing_inline_in_public_items)]
+
+use crate::Error;
+
+mod errno;
+mod stdio;
+mod stdlib;
+mod string;
+mod unistd;
+
+pub use errno::__errno_location;
+pub use stdio::snprintf;
+pub use stdlib::{calloc, free, getenv, malloc, posix_memalign, realloc};
+pub use string::{memchr, strchr, strcmp, strncmp};
+pub use unistd::{close, open, read, write};
+
+/// The value of a C function which succeeded, or `failed` after setting `errno`
+fn or_errno<T>(result: Result<T, Error>, failed: T) -> T {
+    result.unwrap_or_else(|error| {
+        errno::set(error);
+        failed
+    })
+}
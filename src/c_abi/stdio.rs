@@ -0,0 +1,351 @@
+use super::errno;
+use crate::{
+    uapi::{self, c_int},
+    Error,
+};
+use core::{
+    convert::TryFrom,
+    ffi::{c_char, c_long, c_longlong, c_uint, c_ulong, c_ulonglong, VaList},
+};
+
+/// Where formatted output goes, which keeps counting past the end of the buffer because
+/// `snprintf` returns the length the whole output would have had
+struct Output {
+    buf: *mut u8,
+    capacity: usize,
+    len: usize,
+}
+
+impl Output {
+    fn push(&mut self, byte: u8) {
+        // The last byte of the buffer is kept for the terminator
+        if self.len + 1 < self.capacity {
+            unsafe { *self.buf.add(self.len) = byte };
+        }
+        self.len += 1;
+    }
+
+    fn extend(&mut self, bytes: &[u8]) {
+        bytes.iter().for_each(|&b| self.push(b));
+    }
+
+    fn repeat(&mut self, byte: u8, n: usize) {
+        (0..n).for_each(|_| self.push(byte));
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Length {
+    Char,
+    Short,
+    Int,
+    Long,
+    LongLong,
+    Size,
+}
+
+/// How one conversion is written, from the flags, width, and precision before it
+#[derive(Default)]
+struct Spec {
+    left: bool,
+    zero: bool,
+    width: usize,
+    precision: Option<usize>,
+}
+
+impl Spec {
+    /// Write `digits` with `prefix`, such as a sign or `0x`, padded out to the width
+    fn number(&self, out: &mut Output, prefix: &[u8], digits: &[u8]) {
+        let padding = self.width.saturating_sub(prefix.len() + digits.len());
+        if self.left {
+            out.extend(prefix);
+            out.extend(digits);
+            out.repeat(b' ', padding);
+        } else if self.zero {
+            out.extend(prefix);
+            out.repeat(b'0', padding);
+            out.extend(digits);
+        } else {
+            out.repeat(b' ', padding);
+            out.extend(prefix);
+            out.extend(digits);
+        }
+    }
+
+    fn text(&self, out: &mut Output, text: &[u8]) {
+        let text = &text[..self.precision.map_or(text.len(), |p| p.min(text.len()))];
+        let padding = self.width.saturating_sub(text.len());
+        if !self.left {
+            out.repeat(b' ', padding);
+        }
+        out.extend(text);
+        if self.left {
+            out.repeat(b' ', padding);
+        }
+    }
+}
+
+/// Read a number from the digits at `format`, moving past them
+unsafe fn digits(format: &mut *const u8) -> usize {
+    let mut n = 0usize;
+    while (**format).is_ascii_digit() {
+        n = n
+            .saturating_mul(10)
+            .saturating_add(usize::from(**format - b'0'));
+        *format = format.add(1);
+    }
+    n
+}
+
+unsafe fn signed(args: &mut VaList<'_>, length: Length) -> i64 {
+    match length {
+        Length::Char => i64::from(args.next_arg::<c_int>() as i8),
+        Length::Short => i64::from(args.next_arg::<c_int>() as i16),
+        Length::Int => i64::from(args.next_arg::<c_int>()),
+        Length::Long => args.next_arg::<c_long>() as _,
+        Length::LongLong => args.next_arg::<c_longlong>(),
+        Length::Size => args.next_arg::<isize>() as i64,
+    }
+}
+
+unsafe fn unsigned(args: &mut VaList<'_>, length: Length) -> u64 {
+    match length {
+        Length::Char => u64::from(args.next_arg::<c_uint>() as u8),
+        Length::Short => u64::from(args.next_arg::<c_uint>() as u16),
+        Length::Int => u64::from(args.next_arg::<c_uint>()),
+        Length::Long => args.next_arg::<c_ulong>() as _,
+        Length::LongLong => args.next_arg::<c_ulonglong>(),
+        Length::Size => args.next_arg::<usize>() as u64,
+    }
+}
+
+fn hex(mut n: u64, digits: &[u8; 16], buf: &mut [u8; 16]) -> usize {
+    let mut start = buf.len();
+    loop {
+        start -= 1;
+        buf[start] = digits[(n & 0xf) as usize];
+        n >>= 4;
+        if n == 0 {
+            return start;
+        }
+    }
+}
+
+/// Format into `buf`, writing at most `size` bytes including the null terminator, and return the
+/// length the whole output would have had
+///
+/// Only the `-` and `0` flags, widths and precisions given as digits or `*`, the `hh`, `h`, `l`,
+/// `ll`, and `z` lengths, and the `d`, `i`, `u`, `x`, `X`, `p`, `s`, `c`, and `%` conversions are
+/// supported. A precision only applies to `s`. Anything else is copied to the output as it is.
+///
+/// # Safety
+///
+/// `buf` must be valid for writes of `size` bytes, `format` must be null-terminated, and the
+/// arguments must match it
+#[cfg_attr(not(test), no_mangle)]
+pub unsafe extern "C" fn snprintf(
+    buf: *mut c_char,
+    size: usize,
+    format: *const c_char,
+    mut args: ...
+) -> c_int {
+    let mut out = Output {
+        buf: buf.cast(),
+        capacity: size,
+        len: 0,
+    };
+    let mut format = format.cast::<u8>();
+    while *format != 0 {
+        let start = format;
+        let c = *format;
+        format = format.add(1);
+        if c != b'%' {
+            out.push(c);
+            continue;
+        }
+
+        let mut spec = Spec::default();
+        loop {
+            match *format {
+                b'-' => spec.left = true,
+                b'0' => spec.zero = true,
+                _ => break,
+            }
+            format = format.add(1);
+        }
+        if *format == b'*' {
+            format = format.add(1);
+            let width = args.next_arg::<c_int>();
+            // A negative width is a positive one with the - flag
+            spec.left |= width < 0;
+            spec.width = width.unsigned_abs() as usize;
+        } else {
+            spec.width = digits(&mut format);
+        }
+        if *format == b'.' {
+            format = format.add(1);
+            spec.precision = if *format == b'*' {
+                format = format.add(1);
+                usize::try_from(args.next_arg::<c_int>()).ok()
+            } else {
+                Some(digits(&mut format))
+            };
+        }
+        let length = match *format {
+            b'h' if *format.add(1) == b'h' => Length::Char,
+            b'h' => Length::Short,
+            b'l' if *format.add(1) == b'l' => Length::LongLong,
+            b'l' => Length::Long,
+            b'z' => Length::Size,
+            _ => Length::Int,
+        };
+        format = format.add(match length {
+            Length::Char | Length::LongLong => 2,
+            Length::Short | Length::Long | Length::Size => 1,
+            Length::Int => 0,
+        });
+
+        let mut decimal = crate::fmt::Buffer::new();
+        let mut hex_digits = [0u8; 16];
+        match *format {
+            b'd' | b'i' => {
+                let n = signed(&mut args, length);
+                let sign: &[u8] = if n < 0 { b"-" } else { b"" };
+                spec.number(&mut out, sign, decimal.format(n.unsigned_abs()));
+            }
+            b'u' => spec.number(&mut out, b"", decimal.format(unsigned(&mut args, length))),
+            b'x' | b'X' => {
+                let table = if *format == b'x' {
+                    b"0123456789abcdef"
+                } else {
+                    b"0123456789ABCDEF"
+                };
+                let start = hex(unsigned(&mut args, length), table, &mut hex_digits);
+                spec.number(&mut out, b"", &hex_digits[start..]);
+            }
+            b'p' => match args.next_arg::<*const u8>() as usize {
+                // As glibc prints it
+                0 => spec.text(&mut out, b"(nil)"),
+                address => {
+                    let start = hex(address as u64, b"0123456789abcdef", &mut hex_digits);
+                    spec.number(&mut out, b"0x", &hex_digits[start..]);
+                }
+            },
+            b's' => {
+                let s = args.next_arg::<*const u8>();
+                // The precision limits how far the string is read, so it need not be terminated
+                let mut len = 0;
+                while spec.precision.is_none_or(|p| len < p) && *s.add(len) != 0 {
+                    len += 1;
+                }
+                spec.text(&mut out, core::slice::from_raw_parts(s, len));
+            }
+            b'c' => spec.text(&mut out, &[args.next_arg::<c_int>() as u8]),
+            b'%' => out.push(b'%'),
+            // An unknown conversion, or the end of the string
+            _ => {
+                out.extend(core::slice::from_raw_parts(
+                    start,
+                    format.offset_from(start) as usize,
+                ));
+                continue;
+            }
+        }
+        format = format.add(1);
+    }
+
+    if size > 0 {
+        *out.buf.add(out.len.min(size - 1)) = 0;
+    }
+    match c_int::try_from(out.len) {
+        Ok(len) => len,
+        Err(_) => {
+            errno::set(Error(uapi::EOVERFLOW));
+            -1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn f(format: &[u8]) -> *const c_char {
+        format.as_ptr().cast()
+    }
+
+    #[test]
+    fn conversions() {
+        let mut buf = [0xffu8; 64];
+        let p = buf.as_mut_ptr().cast::<c_char>();
+        unsafe {
+            let len = snprintf(
+                p,
+                64,
+                f(b"%d %i %u%%\0"),
+                -42 as c_int,
+                7 as c_int,
+                3 as c_uint,
+            );
+            assert_eq!(&buf[..len as usize], b"-42 7 3%");
+            assert_eq!(buf[len as usize], 0);
+
+            let len = snprintf(
+                p,
+                64,
+                f(b"[%5d|%-5d|%05d|%x|%X]\0"),
+                12 as c_int,
+                12 as c_int,
+                -12 as c_int,
+                255 as c_uint,
+                0xabc as c_uint,
+            );
+            assert_eq!(&buf[..len as usize], b"[   12|12   |-0012|ff|ABC]");
+
+            let len = snprintf(
+                p,
+                64,
+                f(b"%ld %lld %zu %hhd %hu\0"),
+                -1 as c_long,
+                i64::MIN as c_longlong,
+                usize::MAX,
+                300 as c_int,
+                70000 as c_uint,
+            );
+            let expected = std::format!("-1 {} {} 44 4464", i64::MIN, usize::MAX);
+            assert_eq!(&buf[..len as usize], expected.as_bytes());
+
+            let len = snprintf(
+                p,
+                64,
+                f(b"%s|%.2s|%-4s|%*s|%c|%p|%p\0"),
+                b"str\0".as_ptr(),
+                b"unterminated".as_ptr(),
+                b"ab\0".as_ptr(),
+                3 as c_int,
+                b"x\0".as_ptr(),
+                b'z' as c_int,
+                0x1234usize as *const u8,
+                core::ptr::null::<u8>(),
+            );
+            assert_eq!(&buf[..len as usize], b"str|un|ab  |  x|z|0x1234|(nil)");
+
+            let len = snprintf(p, 64, f(b"100%q and %\0"));
+            assert_eq!(&buf[..len as usize], b"100%q and %");
+        }
+    }
+
+    #[test]
+    fn truncation() {
+        let mut buf = [0xffu8; 8];
+        let p = buf.as_mut_ptr().cast::<c_char>();
+        unsafe {
+            assert_eq!(snprintf(p, 4, f(b"%d\0"), 123456 as c_int), 6);
+            assert_eq!(&buf[..5], b"123\0\xff");
+            assert_eq!(
+                snprintf(core::ptr::null_mut(), 0, f(b"%s\0"), b"abc\0".as_ptr()),
+                3
+            );
+        }
+    }
+}
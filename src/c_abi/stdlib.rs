@@ -0,0 +1,236 @@
+use super::errno;
+use crate::{
+    uapi::{self, c_int},
+    Allocator, CStr, Error,
+};
+use core::{
+    alloc::{GlobalAlloc, Layout},
+    ffi::{c_char, c_void},
+    mem, ptr,
+};
+
+static HEAP: Allocator = Allocator::new();
+
+/// The alignment `malloc` guarantees, which is that of `max_align_t`
+const MIN_ALIGN: usize = 16;
+
+/// Where a block starts and how big it is, stored just below the address handed out, because
+/// `free` is not told the size
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Header {
+    /// From the start of the block to the address handed out
+    offset: usize,
+    /// The size of the whole block
+    size: usize,
+}
+
+unsafe fn header(ptr: *mut u8) -> *mut Header {
+    ptr.sub(mem::size_of::<Header>()).cast()
+}
+
+/// Allocate `size` bytes aligned to `align`, which must be a power of two
+///
+/// The block is aligned to `MIN_ALIGN`, with `align` bytes more than asked for, so there is always
+/// an aligned address with room for the header below it.
+unsafe fn allocate(size: usize, align: usize, zeroed: bool) -> *mut u8 {
+    let align = align.max(MIN_ALIGN);
+    let layout = match size
+        .checked_add(align)
+        .and_then(|total| Layout::from_size_align(total, MIN_ALIGN).ok())
+    {
+        Some(layout) => layout,
+        None => return ptr::null_mut(),
+    };
+    let block = if zeroed {
+        HEAP.alloc_zeroed(layout)
+    } else {
+        HEAP.alloc(layout)
+    };
+    if block.is_null() {
+        return block;
+    }
+    let offset = (block as usize + MIN_ALIGN).next_multiple_of(align) - block as usize;
+    let ptr = block.add(offset);
+    header(ptr).write(Header {
+        offset,
+        size: layout.size(),
+    });
+    ptr
+}
+
+/// # Safety
+///
+/// The memory must be freed with [`free`], not by another allocator
+#[cfg_attr(not(test), no_mangle)]
+pub unsafe extern "C" fn malloc(size: usize) -> *mut c_void {
+    let ptr = allocate(size, MIN_ALIGN, false);
+    if ptr.is_null() {
+        errno::set(Error(uapi::ENOMEM));
+    }
+    ptr.cast()
+}
+
+/// # Safety
+///
+/// As for [`malloc`]
+#[cfg_attr(not(test), no_mangle)]
+pub unsafe extern "C" fn calloc(count: usize, size: usize) -> *mut c_void {
+    let ptr = match count.checked_mul(size) {
+        Some(total) => allocate(total, MIN_ALIGN, true),
+        None => ptr::null_mut(),
+    };
+    if ptr.is_null() {
+        errno::set(Error(uapi::ENOMEM));
+    }
+    ptr.cast()
+}
+
+/// Allocate `size` bytes aligned to `alignment`, which must be a power of two and a multiple of
+/// the size of a pointer, returning an error number rather than setting `errno`
+///
+/// # Safety
+///
+/// `memptr` must be valid for a write, and the memory must be freed with [`free`]
+#[cfg_attr(not(test), no_mangle)]
+pub unsafe extern "C" fn posix_memalign(
+    memptr: *mut *mut c_void,
+    alignment: usize,
+    size: usize,
+) -> c_int {
+    if !alignment.is_power_of_two() || !alignment.is_multiple_of(mem::size_of::<*mut c_void>()) {
+        return uapi::EINVAL;
+    }
+    let ptr = allocate(size, alignment, false);
+    if ptr.is_null() {
+        return uapi::ENOMEM;
+    }
+    *memptr = ptr.cast();
+    0
+}
+
+/// Resize an allocation, which may move it
+///
+/// A null `ptr` allocates, and a `size` of 0 frees `ptr` and returns null. If this fails the old
+/// allocation is left as it was.
+///
+/// # Safety
+///
+/// `ptr` must be null or returned by one of these allocation functions and not yet freed
+#[cfg_attr(not(test), no_mangle)]
+pub unsafe extern "C" fn realloc(ptr: *mut c_void, size: usize) -> *mut c_void {
+    if ptr.is_null() {
+        return malloc(size);
+    }
+    if size == 0 {
+        free(ptr);
+        return ptr::null_mut();
+    }
+    let ptr = ptr.cast::<u8>();
+    let old = header(ptr).read();
+    // Only blocks from posix_memalign have a larger offset, and realloc need not keep their
+    // alignment, so they are copied into an ordinary one
+    if old.offset != MIN_ALIGN {
+        let new = malloc(size);
+        if !new.is_null() {
+            ptr::copy_nonoverlapping(ptr, new.cast(), size.min(old.size - old.offset));
+            free(ptr.cast());
+        }
+        return new;
+    }
+    let block = match size.checked_add(MIN_ALIGN) {
+        Some(new_size) => HEAP.realloc(
+            ptr.sub(old.offset),
+            Layout::from_size_align_unchecked(old.size, MIN_ALIGN),
+            new_size,
+        ),
+        None => ptr::null_mut(),
+    };
+    if block.is_null() {
+        errno::set(Error(uapi::ENOMEM));
+        return ptr::null_mut();
+    }
+    let ptr = block.add(MIN_ALIGN);
+    header(ptr).write(Header {
+        offset: MIN_ALIGN,
+        size: size + MIN_ALIGN,
+    });
+    ptr.cast()
+}
+
+/// # Safety
+///
+/// `ptr` must be null or returned by one of these allocation functions and not yet freed
+#[cfg_attr(not(test), no_mangle)]
+pub unsafe extern "C" fn free(ptr: *mut c_void) {
+    if ptr.is_null() {
+        return;
+    }
+    let ptr = ptr.cast::<u8>();
+    let Header { offset, size } = header(ptr).read();
+    HEAP.dealloc(
+        ptr.sub(offset),
+        Layout::from_size_align_unchecked(size, MIN_ALIGN),
+    );
+}
+
+/// Look up an environment variable, returning a pointer into the environment the process was
+/// started with, or null when it is not set
+///
+/// # Safety
+///
+/// `name` must be null-terminated
+#[cfg_attr(not(test), no_mangle)]
+pub unsafe extern "C" fn getenv(name: *const c_char) -> *mut c_char {
+    // Nothing has captured the environment in a program without veneer's _start
+    if crate::env::ENVP
+        .load(core::sync::atomic::Ordering::SeqCst)
+        .is_null()
+    {
+        return ptr::null_mut();
+    }
+    let name = CStr::from_ptr(name.cast());
+    match crate::env::var(name.as_bytes()) {
+        Some(value) => value.as_ptr() as *mut c_char,
+        None => ptr::null_mut(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocations() {
+        crate::env::capture_for_test();
+        unsafe {
+            let small = malloc(10).cast::<u8>();
+            assert_eq!(small as usize % MIN_ALIGN, 0);
+            small.write_bytes(7, 10);
+
+            let zeroed = calloc(1000, 8).cast::<u8>();
+            assert!(core::slice::from_raw_parts(zeroed, 8000)
+                .iter()
+                .all(|&b| b == 0));
+            assert!(calloc(usize::MAX, 2).is_null());
+
+            // Growing past the small slab and past a page keeps the contents
+            let grown = realloc(small.cast(), 100_000).cast::<u8>();
+            assert_eq!(core::slice::from_raw_parts(grown, 10), &[7; 10]);
+            grown.add(99_999).write(1);
+
+            let mut aligned = ptr::null_mut();
+            assert_eq!(posix_memalign(&mut aligned, 8192, 100), 0);
+            assert_eq!(aligned as usize % 8192, 0);
+            aligned.cast::<u8>().write_bytes(3, 100);
+            let moved = realloc(aligned, 50).cast::<u8>();
+            assert_eq!(core::slice::from_raw_parts(moved, 50), &[3; 50]);
+            assert_eq!(posix_memalign(&mut aligned, 12, 100), uapi::EINVAL);
+
+            assert!(realloc(zeroed.cast(), 0).is_null());
+            free(grown.cast());
+            free(moved.cast());
+            free(ptr::null_mut());
+        }
+    }
+}
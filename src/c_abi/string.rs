@@ -0,0 +1,99 @@
+use crate::uapi::c_int;
+use core::ffi::{c_char, c_void};
+
+/// # Safety
+///
+/// Both strings must be null-terminated
+#[cfg_attr(not(test), no_mangle)]
+pub unsafe extern "C" fn strcmp(s1: *const c_char, s2: *const c_char) -> c_int {
+    strncmp(s1, s2, usize::MAX)
+}
+
+/// Compare at most `n` bytes of two strings, as unsigned bytes
+///
+/// # Safety
+///
+/// Both strings must be null-terminated or at least `n` bytes long
+#[cfg_attr(not(test), no_mangle)]
+pub unsafe extern "C" fn strncmp(s1: *const c_char, s2: *const c_char, n: usize) -> c_int {
+    for i in 0..n {
+        let (a, b) = (*s1.add(i) as u8, *s2.add(i) as u8);
+        if a != b || a == 0 {
+            return c_int::from(a) - c_int::from(b);
+        }
+    }
+    0
+}
+
+/// Find the first `c` in `s`, which may be its null terminator
+///
+/// # Safety
+///
+/// `s` must be null-terminated
+#[cfg_attr(not(test), no_mangle)]
+pub unsafe extern "C" fn strchr(s: *const c_char, c: c_int) -> *mut c_char {
+    let mut s = s;
+    loop {
+        if *s as u8 == c as u8 {
+            return s as *mut c_char;
+        }
+        if *s == 0 {
+            return core::ptr::null_mut();
+        }
+        s = s.add(1);
+    }
+}
+
+/// # Safety
+///
+/// `s` must be valid for reads of `n` bytes
+#[cfg_attr(not(test), no_mangle)]
+pub unsafe extern "C" fn memchr(s: *const c_void, c: c_int, n: usize) -> *mut c_void {
+    let s = s as *const u8;
+    match core::slice::from_raw_parts(s, n)
+        .iter()
+        .position(|&b| b == c as u8)
+    {
+        Some(i) => s.add(i) as *mut c_void,
+        None => core::ptr::null_mut(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn s(bytes: &[u8]) -> *const c_char {
+        bytes.as_ptr().cast()
+    }
+
+    #[test]
+    fn comparisons() {
+        unsafe {
+            assert_eq!(strcmp(s(b"abc\0"), s(b"abc\0")), 0);
+            assert!(strcmp(s(b"abc\0"), s(b"abd\0")) < 0);
+            assert!(strcmp(s(b"ab\0"), s(b"abc\0")) < 0);
+            // Bytes compare as unsigned
+            assert!(strcmp(s(b"\xff\0"), s(b"a\0")) > 0);
+            assert_eq!(strncmp(s(b"abcx\0"), s(b"abcy\0"), 3), 0);
+            assert!(strncmp(s(b"abcx\0"), s(b"abcy\0"), 4) < 0);
+            assert_eq!(strncmp(s(b"a\0"), s(b"b\0"), 0), 0);
+        }
+    }
+
+    #[test]
+    fn searches() {
+        let text = b"key=value\0";
+        unsafe {
+            assert_eq!(strchr(s(text), b'=' as c_int), s(&text[3..]) as *mut c_char);
+            assert_eq!(strchr(s(text), 0), s(&text[9..]) as *mut c_char);
+            assert!(strchr(s(text), b'x' as c_int).is_null());
+            let start = text.as_ptr().cast::<c_void>();
+            assert_eq!(
+                memchr(start, b'v' as c_int, text.len()),
+                text[4..].as_ptr() as *mut c_void
+            );
+            assert!(memchr(start, b'v' as c_int, 4).is_null());
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use super::or_errno;
+use crate::{
+    fd::{self, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd},
+    syscalls::{self, OpenFlags, OpenMode},
+    uapi::{self, c_int},
+    CStr,
+};
+use core::ffi::{c_char, c_void};
+
+/// # Safety
+///
+/// `buf` must be valid for writes of `count` bytes, and may be null if `count` is 0
+#[cfg_attr(not(test), no_mangle)]
+pub unsafe extern "C" fn read(fd: c_int, buf: *mut c_void, count: usize) -> isize {
+    // A slice cannot have a null pointer even when it is empty
+    let buf = if count == 0 {
+        &mut []
+    } else {
+        core::slice::from_raw_parts_mut(buf.cast::<u8>(), count)
+    };
+    or_errno(
+        syscalls::read(BorrowedFd::borrow_raw(fd), buf).map(|n| n as isize),
+        -1,
+    )
+}
+
+/// # Safety
+///
+/// `buf` must be valid for reads of `count` bytes, and may be null if `count` is 0
+#[cfg_attr(not(test), no_mangle)]
+pub unsafe extern "C" fn write(fd: c_int, buf: *const c_void, count: usize) -> isize {
+    let buf = if count == 0 {
+        &[]
+    } else {
+        core::slice::from_raw_parts(buf.cast::<u8>(), count)
+    };
+    or_errno(
+        syscalls::write(BorrowedFd::borrow_raw(fd), buf).map(|n| n as isize),
+        -1,
+    )
+}
+
+/// Open the file at `path`, reading a mode argument only when `flags` can create a file
+///
+/// # Safety
+///
+/// `path` must be null-terminated, and a mode must be passed with `O_CREAT` or `O_TMPFILE`
+#[cfg_attr(not(test), no_mangle)]
+pub unsafe extern "C" fn open(path: *const c_char, flags: c_int, mut args: ...) -> c_int {
+    let mode = if flags & uapi::O_CREAT != 0 || flags & uapi::O_TMPFILE == uapi::O_TMPFILE {
+        args.next_arg::<uapi::c_uint>()
+    } else {
+        0
+    };
+    or_errno(
+        syscalls::openat(
+            fd::CWD,
+            CStr::from_ptr(path.cast()),
+            OpenFlags::from_bits_retain(flags),
+            OpenMode::from_bits_retain(mode),
+        )
+        .map(IntoRawFd::into_raw_fd),
+        -1,
+    )
+}
+
+/// # Safety
+///
+/// `fd` must not be used again, by this caller or by anything else which owned it
+#[cfg_attr(not(test), no_mangle)]
+pub unsafe extern "C" fn close(fd: c_int) -> c_int {
+    or_errno(syscalls::close(OwnedFd::from_raw_fd(fd)).map(|()| 0), -1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_and_errno() {
+        let path = b"/tmp/veneer-c-abi-open\0".as_ptr().cast::<c_char>();
+        unsafe {
+            let fd = open(
+                path,
+                uapi::O_WRONLY | uapi::O_CREAT | uapi::O_TRUNC | uapi::O_CLOEXEC,
+                0o600 as uapi::c_uint,
+            );
+            assert!(fd >= 0);
+            assert_eq!(write(fd, b"hello".as_ptr().cast(), 5), 5);
+            assert_eq!(write(fd, core::ptr::null(), 0), 0);
+            assert_eq!(close(fd), 0);
+
+            let fd = open(path, uapi::O_RDONLY | uapi::O_CLOEXEC);
+            let mut buf = [0u8; 8];
+            assert_eq!(read(fd, buf.as_mut_ptr().cast(), buf.len()), 5);
+            assert_eq!(&buf[..5], b"hello");
+            assert_eq!(read(fd, core::ptr::null_mut(), 0), 0);
+            assert_eq!(close(fd), 0);
+
+            let missing = b"/nonexistent/file\0".as_ptr().cast::<c_char>();
+            assert_eq!(open(missing, uapi::O_RDONLY), -1);
+            assert_eq!(*super::super::__errno_location(), uapi::ENOENT);
+        }
+    }
+}
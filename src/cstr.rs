@@ -41,6 +41,11 @@ impl<'a> CStr<'a> {
         unsafe { self.bytes.get_unchecked(..self.bytes.len() - 1) }
     }
 
+    #[inline]
+    pub fn as_bytes_with_nul(&self) -> &'a [u8] {
+        self.bytes
+    }
+
     #[inline]
     pub fn get(&self, i: usize) -> Option<u8> {
         self.bytes.get(i).copied()
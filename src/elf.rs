@@ -0,0 +1,193 @@
+//! Just enough of the ELF format to read the vDSO and our own executable
+
+#![allow(dead_code)]
+
+// The 32-bit format has the same structures with narrower fields, except that a few fields of
+// `Phdr` and `Sym` are in a different order
+#[cfg(target_pointer_width = "64")]
+mod width {
+    pub type Addr = u64;
+    pub type Off = u64;
+    pub type Xword = u64;
+    pub type Sxword = i64;
+}
+#[cfg(target_pointer_width = "32")]
+mod width {
+    pub type Addr = u32;
+    pub type Off = u32;
+    pub type Xword = u32;
+    pub type Sxword = i32;
+}
+pub use width::*;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Ehdr {
+    pub e_ident: [u8; 16],
+    pub e_type: u16,
+    pub e_machine: u16,
+    pub e_version: u32,
+    pub e_entry: Addr,
+    pub e_phoff: Off,
+    pub e_shoff: Off,
+    pub e_flags: u32,
+    pub e_ehsize: u16,
+    pub e_phentsize: u16,
+    pub e_phnum: u16,
+    pub e_shentsize: u16,
+    pub e_shnum: u16,
+    pub e_shstrndx: u16,
+}
+
+#[cfg(target_pointer_width = "64")]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Phdr {
+    pub p_type: u32,
+    pub p_flags: u32,
+    pub p_offset: u64,
+    pub p_vaddr: u64,
+    pub p_paddr: u64,
+    pub p_filesz: u64,
+    pub p_memsz: u64,
+    pub p_align: u64,
+}
+
+#[cfg(target_pointer_width = "32")]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Phdr {
+    pub p_type: u32,
+    pub p_offset: u32,
+    pub p_vaddr: u32,
+    pub p_paddr: u32,
+    pub p_filesz: u32,
+    pub p_memsz: u32,
+    pub p_flags: u32,
+    pub p_align: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Shdr {
+    pub sh_name: u32,
+    pub sh_type: u32,
+    pub sh_flags: Xword,
+    pub sh_addr: Addr,
+    pub sh_offset: Off,
+    pub sh_size: Xword,
+    pub sh_link: u32,
+    pub sh_info: u32,
+    pub sh_addralign: Xword,
+    pub sh_entsize: Xword,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Dyn {
+    pub d_tag: Sxword,
+    pub d_val: Xword,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Rela {
+    pub r_offset: Addr,
+    pub r_info: Xword,
+    pub r_addend: Sxword,
+}
+
+/// A relocation whose addend is stored at the address it applies to, which is the only kind on
+/// i386
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Rel {
+    pub r_offset: Addr,
+    pub r_info: Xword,
+}
+
+#[cfg(target_pointer_width = "64")]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Sym {
+    pub st_name: u32,
+    pub st_info: u8,
+    pub st_other: u8,
+    pub st_shndx: u16,
+    pub st_value: u64,
+    pub st_size: u64,
+}
+
+#[cfg(target_pointer_width = "32")]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Sym {
+    pub st_name: u32,
+    pub st_value: u32,
+    pub st_size: u32,
+    pub st_info: u8,
+    pub st_other: u8,
+    pub st_shndx: u16,
+}
+
+impl Sym {
+    #[inline]
+    pub fn kind(&self) -> u8 {
+        self.st_info & 0xf
+    }
+
+    #[inline]
+    pub fn binding(&self) -> u8 {
+        self.st_info >> 4
+    }
+}
+
+pub const PT_LOAD: u32 = 1;
+pub const PT_DYNAMIC: u32 = 2;
+pub const PT_PHDR: u32 = 6;
+pub const PT_TLS: u32 = 7;
+
+pub const SHT_SYMTAB: u32 = 2;
+
+pub const DT_NULL: Sxword = 0;
+pub const DT_HASH: Sxword = 4;
+pub const DT_STRTAB: Sxword = 5;
+pub const DT_SYMTAB: Sxword = 6;
+pub const DT_RELA: Sxword = 7;
+pub const DT_RELASZ: Sxword = 8;
+pub const DT_RELAENT: Sxword = 9;
+pub const DT_REL: Sxword = 17;
+pub const DT_RELSZ: Sxword = 18;
+pub const DT_RELENT: Sxword = 19;
+pub const DT_RELRSZ: Sxword = 35;
+pub const DT_RELR: Sxword = 36;
+pub const DT_GNU_HASH: Sxword = 0x6fff_fef5;
+
+pub const R_X86_64_RELATIVE: u32 = 8;
+pub const R_AARCH64_RELATIVE: u32 = 1027;
+pub const R_386_RELATIVE: u32 = 8;
+
+pub const STT_FUNC: u8 = 2;
+pub const STB_GLOBAL: u8 = 1;
+pub const STB_WEAK: u8 = 2;
+pub const SHN_UNDEF: u16 = 0;
+
+/// The program headers of the running executable, as found through the auxiliary vector
+pub fn program_headers() -> impl Iterator<Item = &'static Phdr> {
+    let (phdrs, phent, phnum) = crate::env::program_headers().unwrap_or((core::ptr::null(), 0, 0));
+    (0..phnum).map(move |i| unsafe { &*phdrs.add(i * phent).cast::<Phdr>() })
+}
+
+/// How far the executable was moved from the addresses it was linked at when it was loaded
+///
+/// `AT_PHDR` is where the program headers are in memory, and the `PT_PHDR` entry says where they
+/// were linked to be. An executable without `PT_PHDR` cannot be position-independent.
+pub fn load_base() -> usize {
+    let phdrs = match crate::env::program_headers() {
+        Some((phdrs, _, _)) => phdrs as usize,
+        None => return 0,
+    };
+    program_headers()
+        .find(|phdr| phdr.p_type == PT_PHDR)
+        .map_or(0, |phdr| phdrs.wrapping_sub(phdr.p_vaddr as usize))
+}
@@ -0,0 +1,250 @@
+use crate::CStr;
+use core::sync::atomic::{AtomicPtr, AtomicUsize, Ordering::SeqCst};
+
+pub(crate) static AUXV: AtomicPtr<usize> = AtomicPtr::new(core::ptr::null_mut());
+
+// Keys from the kernel's include/uapi/linux/auxvec.h
+pub(crate) const AT_NULL: usize = 0;
+pub(crate) const AT_PHDR: usize = 3;
+pub(crate) const AT_PHENT: usize = 4;
+pub(crate) const AT_PHNUM: usize = 5;
+const AT_PAGESZ: usize = 6;
+const AT_BASE: usize = 7;
+const AT_ENTRY: usize = 9;
+const AT_UID: usize = 11;
+const AT_EUID: usize = 12;
+const AT_GID: usize = 13;
+const AT_EGID: usize = 14;
+const AT_HWCAP: usize = 16;
+const AT_CLKTCK: usize = 17;
+const AT_SECURE: usize = 23;
+const AT_RANDOM: usize = 25;
+const AT_HWCAP2: usize = 26;
+const AT_EXECFN: usize = 31;
+const AT_SYSINFO_EHDR: usize = 33;
+
+/// One entry of the ELF auxiliary vector the kernel passes to a new process
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AuxEntry {
+    /// Address of the program headers of the executable
+    Phdr(*const u8),
+    /// Size of one program header entry
+    Phent(usize),
+    /// Number of program headers
+    Phnum(usize),
+    PageSize(usize),
+    /// Base address of the program interpreter, 0 if there is none
+    Base(usize),
+    /// Entry point of the executable
+    Entry(usize),
+    Uid(usize),
+    Euid(usize),
+    Gid(usize),
+    Egid(usize),
+    HwCap(usize),
+    /// Frequency of `times()`
+    ClockTick(usize),
+    /// Whether the program is running in secure mode, such as after a setuid exec
+    Secure(bool),
+    /// 16 random bytes provided by the kernel
+    Random(&'static [u8; 16]),
+    HwCap2(usize),
+    /// The path that was passed to `execve`
+    ExecFn(CStr<'static>),
+    /// Address of the ELF header of the vDSO
+    SysinfoEhdr(*const u8),
+    Other {
+        key: usize,
+        value: usize,
+    },
+}
+
+/// Iterate over the auxiliary vector this process was started with
+///
+/// The auxiliary vector is only available when the `rt-start` feature provides the entry point,
+/// so this iterator is empty otherwise.
+#[inline]
+pub fn auxv() -> Auxv {
+    Auxv {
+        ptr: AUXV.load(SeqCst),
+    }
+}
+
+pub struct Auxv {
+    ptr: *const usize,
+}
+
+impl Iterator for Auxv {
+    type Item = AuxEntry;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.ptr.is_null() {
+            return None;
+        }
+        let (key, value) = unsafe { (*self.ptr, *self.ptr.add(1)) };
+        if key == AT_NULL {
+            self.ptr = core::ptr::null();
+            return None;
+        }
+        self.ptr = unsafe { self.ptr.add(2) };
+        Some(match key {
+            AT_PHDR => AuxEntry::Phdr(value as *const u8),
+            AT_PHENT => AuxEntry::Phent(value),
+            AT_PHNUM => AuxEntry::Phnum(value),
+            AT_PAGESZ => AuxEntry::PageSize(value),
+            AT_BASE => AuxEntry::Base(value),
+            AT_ENTRY => AuxEntry::Entry(value),
+            AT_UID => AuxEntry::Uid(value),
+            AT_EUID => AuxEntry::Euid(value),
+            AT_GID => AuxEntry::Gid(value),
+            AT_EGID => AuxEntry::Egid(value),
+            AT_HWCAP => AuxEntry::HwCap(value),
+            AT_CLKTCK => AuxEntry::ClockTick(value),
+            AT_SECURE => AuxEntry::Secure(value != 0),
+            AT_RANDOM => AuxEntry::Random(unsafe { &*(value as *const [u8; 16]) }),
+            AT_HWCAP2 => AuxEntry::HwCap2(value),
+            AT_EXECFN => AuxEntry::ExecFn(unsafe { CStr::from_ptr(value as *const u8) }),
+            AT_SYSINFO_EHDR => AuxEntry::SysinfoEhdr(value as *const u8),
+            _ => AuxEntry::Other { key, value },
+        })
+    }
+}
+
+static PAGE_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+/// The page size reported by the kernel, or 4096 if the auxiliary vector is unavailable
+#[inline]
+pub fn page_size() -> usize {
+    let cached = PAGE_SIZE.load(SeqCst);
+    if cached != 0 {
+        return cached;
+    }
+    let size = auxv()
+        .find_map(|entry| match entry {
+            AuxEntry::PageSize(size) => Some(size),
+            _ => None,
+        })
+        .unwrap_or(4096);
+    PAGE_SIZE.store(size, SeqCst);
+    size
+}
+
+#[inline]
+pub fn hwcap() -> usize {
+    auxv()
+        .find_map(|entry| match entry {
+            AuxEntry::HwCap(caps) => Some(caps),
+            _ => None,
+        })
+        .unwrap_or(0)
+}
+
+#[inline]
+pub fn hwcap2() -> usize {
+    auxv()
+        .find_map(|entry| match entry {
+            AuxEntry::HwCap2(caps) => Some(caps),
+            _ => None,
+        })
+        .unwrap_or(0)
+}
+
+#[inline]
+pub fn random_bytes() -> Option<&'static [u8; 16]> {
+    auxv().find_map(|entry| match entry {
+        AuxEntry::Random(bytes) => Some(bytes),
+        _ => None,
+    })
+}
+
+#[inline]
+pub fn is_secure() -> bool {
+    auxv().any(|entry| entry == AuxEntry::Secure(true))
+}
+
+#[inline]
+pub fn clock_ticks() -> Option<usize> {
+    auxv().find_map(|entry| match entry {
+        AuxEntry::ClockTick(ticks) => Some(ticks),
+        _ => None,
+    })
+}
+
+#[inline]
+pub fn execfn() -> Option<CStr<'static>> {
+    auxv().find_map(|entry| match entry {
+        AuxEntry::ExecFn(path) => Some(path),
+        _ => None,
+    })
+}
+
+#[inline]
+pub fn sysinfo_ehdr() -> Option<*const u8> {
+    auxv().find_map(|entry| match entry {
+        AuxEntry::SysinfoEhdr(ehdr) if !ehdr.is_null() => Some(ehdr),
+        _ => None,
+    })
+}
+
+/// The address, entry size, and number of the executable's program headers
+#[inline]
+pub fn program_headers() -> Option<(*const u8, usize, usize)> {
+    let mut phdr = None;
+    let mut phent = None;
+    let mut phnum = None;
+    for entry in auxv() {
+        match entry {
+            AuxEntry::Phdr(p) => phdr = Some(p),
+            AuxEntry::Phent(n) => phent = Some(n),
+            AuxEntry::Phnum(n) => phnum = Some(n),
+            _ => {}
+        }
+    }
+    Some((phdr?, phent?, phnum?))
+}
+
+#[cfg(test)]
+pub(crate) fn capture_for_test() {
+    if !AUXV.load(SeqCst).is_null() {
+        return;
+    }
+    use core::convert::TryInto;
+    let bytes = std::fs::read("/proc/self/auxv").unwrap();
+    let words: std::vec::Vec<usize> = bytes
+        .chunks_exact(core::mem::size_of::<usize>())
+        .map(|c| usize::from_ne_bytes(c.try_into().unwrap()))
+        .collect();
+    AUXV.store(words.leak().as_mut_ptr(), SeqCst);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_getauxval() {
+        capture_for_test();
+        unsafe {
+            assert_eq!(page_size(), libc::getauxval(libc::AT_PAGESZ) as usize);
+            // glibc replaces AT_HWCAP with its own bits on x86, so only HWCAP2 is comparable
+            assert_eq!(hwcap2(), libc::getauxval(libc::AT_HWCAP2) as usize);
+            assert_eq!(is_secure(), libc::getauxval(libc::AT_SECURE) != 0);
+            assert_eq!(
+                random_bytes().unwrap().as_ptr() as usize,
+                libc::getauxval(libc::AT_RANDOM) as usize
+            );
+            assert_eq!(
+                sysinfo_ehdr().unwrap() as usize,
+                libc::getauxval(libc::AT_SYSINFO_EHDR) as usize
+            );
+            assert_eq!(
+                execfn().unwrap().as_ptr() as usize,
+                libc::getauxval(libc::AT_EXECFN) as usize
+            );
+            let (phdr, _, phnum) = program_headers().unwrap();
+            assert_eq!(phdr as usize, libc::getauxval(libc::AT_PHDR) as usize);
+            assert_eq!(phnum, libc::getauxval(libc::AT_PHNUM) as usize);
+        }
+    }
+}
@@ -0,0 +1,337 @@
+use crate::CStr;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicIsize, AtomicPtr, Ordering::SeqCst};
+
+mod auxv;
+pub use auxv::*;
+
+pub(crate) static ARGC: AtomicIsize = AtomicIsize::new(-1);
+pub(crate) static ARGV: AtomicPtr<*const u8> = AtomicPtr::new(core::ptr::null_mut());
+pub(crate) static ENVP: AtomicPtr<*const u8> = AtomicPtr::new(core::ptr::null_mut());
+
+#[inline]
+pub fn args() -> Args {
+    unsafe {
+        let argc = ARGC.load(SeqCst);
+        let argv = ARGV.load(SeqCst);
+        assert!(!argv.is_null() && argc != -1);
+        Args::from_raw(argc as usize, argv)
+    }
+}
+
+/// The arguments this process was started with
+#[derive(Clone)]
+pub struct Args {
+    argv: *const *const u8,
+    remaining: usize,
+}
+
+impl Args {
+    /// # Safety
+    ///
+    /// `argv` must point to `argc` pointers to null-terminated strings which live forever
+    #[inline]
+    pub unsafe fn from_raw(argc: usize, argv: *const *const u8) -> Self {
+        Self {
+            argv,
+            remaining: argc,
+        }
+    }
+}
+
+impl Iterator for Args {
+    type Item = CStr<'static>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        unsafe {
+            let arg = CStr::from_ptr(*self.argv);
+            self.argv = self.argv.add(1);
+            self.remaining -= 1;
+            Some(arg)
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for Args {}
+
+/// Look up an environment variable of this process by name
+#[inline]
+pub fn var(name: &[u8]) -> Option<CStr<'static>> {
+    Env::current().var(name)
+}
+
+/// Iterate over the `(name, value)` pairs in the environment this process was started with
+///
+/// Entries which do not contain a `=` are skipped.
+#[inline]
+pub fn vars() -> Vars {
+    Env::current().vars()
+}
+
+/// The environment this process was started with
+#[derive(Clone, Copy)]
+pub struct Env {
+    envp: *const *const u8,
+}
+
+impl Env {
+    #[inline]
+    pub fn current() -> Self {
+        let envp = ENVP.load(SeqCst);
+        assert!(!envp.is_null());
+        Self { envp }
+    }
+
+    /// # Safety
+    ///
+    /// `envp` must point to a null-terminated array of pointers to null-terminated strings which
+    /// live forever
+    #[inline]
+    pub unsafe fn from_raw(envp: *const *const u8) -> Self {
+        Self { envp }
+    }
+
+    #[inline]
+    pub fn var(&self, name: &[u8]) -> Option<CStr<'static>> {
+        self.vars()
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| value)
+    }
+
+    #[inline]
+    pub fn vars(&self) -> Vars {
+        Vars { envp: self.envp }
+    }
+}
+
+pub struct Vars {
+    envp: *const *const u8,
+}
+
+impl Iterator for Vars {
+    type Item = (&'static [u8], CStr<'static>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            unsafe {
+                let entry = *self.envp;
+                if entry.is_null() {
+                    return None;
+                }
+                self.envp = self.envp.add(1);
+                if let Some(pair) = split_entry(CStr::from_ptr(entry)) {
+                    return Some(pair);
+                }
+            }
+        }
+    }
+}
+
+fn split_entry(entry: CStr<'_>) -> Option<(&[u8], CStr<'_>)> {
+    let bytes = entry.as_bytes_with_nul();
+    let eq = bytes.iter().position(|b| *b == b'=')?;
+    Some((&bytes[..eq], CStr::from_bytes(&bytes[eq + 1..])))
+}
+
+/// An owned, modifiable set of environment variables
+///
+/// The environment of this process is only ever read, so to pass a different environment to a
+/// child process, copy it with [`Environment::current`], edit it, then hand the pointers from
+/// [`Environment::envp`] to `execve`.
+#[derive(Clone, Default)]
+pub struct Environment {
+    // Each entry is stored as name=value\0 so that it can be passed to the kernel directly
+    entries: Vec<Vec<u8>>,
+}
+
+impl Environment {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Copy the environment this process was started with
+    #[inline]
+    pub fn current() -> Self {
+        Self::from(Env::current())
+    }
+
+    #[inline]
+    pub fn get(&self, name: &[u8]) -> Option<CStr<'_>> {
+        self.iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Add a variable, replacing any existing variable with the same name
+    ///
+    /// # Panics
+    ///
+    /// If `name` is empty or contains `=` or a null byte, or if `value` contains a null byte
+    #[inline]
+    pub fn set(&mut self, name: &[u8], value: &[u8]) {
+        assert!(
+            !name.is_empty() && !name.contains(&b'=') && !name.contains(&0),
+            "invalid environment variable name"
+        );
+        assert!(
+            !value.contains(&0),
+            "environment variable value contains a null byte"
+        );
+        let mut entry = Vec::with_capacity(name.len() + value.len() + 2);
+        entry.extend_from_slice(name);
+        entry.push(b'=');
+        entry.extend_from_slice(value);
+        entry.push(0);
+        match self.position(name) {
+            Some(i) => self.entries[i] = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    #[inline]
+    pub fn remove(&mut self, name: &[u8]) {
+        if let Some(i) = self.position(name) {
+            self.entries.remove(i);
+        }
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8], CStr<'_>)> {
+        self.entries
+            .iter()
+            .filter_map(|entry| split_entry(CStr::from_bytes(entry)))
+    }
+
+    /// A null-terminated array of pointers to `name=value` strings, in the form `execve` expects
+    ///
+    /// The pointers are only valid as long as this `Environment` is not modified or dropped.
+    #[inline]
+    pub fn envp(&self) -> Vec<*const u8> {
+        self.entries
+            .iter()
+            .map(|entry| entry.as_ptr())
+            .chain(core::iter::once(core::ptr::null()))
+            .collect()
+    }
+
+    fn position(&self, name: &[u8]) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| entry.starts_with(name) && entry.get(name.len()) == Some(&b'='))
+    }
+}
+
+impl From<Env> for Environment {
+    #[inline]
+    fn from(env: Env) -> Self {
+        let mut environment = Self::new();
+        // execve allows entries like "=value", whose empty name set would reject
+        for (name, value) in env.vars().filter(|(name, _)| !name.is_empty()) {
+            environment.set(name, value.as_bytes());
+        }
+        environment
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern "C" {
+        static environ: *const *const u8;
+    }
+
+    fn capture_environ() {
+        unsafe {
+            ENVP.store(environ as *mut _, SeqCst);
+        }
+    }
+
+    #[test]
+    fn var_matches_std() {
+        capture_environ();
+        for (name, value) in std::env::vars_os() {
+            use std::os::unix::ffi::OsStrExt;
+            let ours = var(name.as_bytes()).unwrap();
+            assert_eq!(ours, value.as_bytes());
+        }
+        assert!(var(b"VENEER_SURELY_NOT_SET").is_none());
+    }
+
+    #[test]
+    fn vars_matches_std() {
+        capture_environ();
+        assert_eq!(vars().count(), std::env::vars_os().count());
+    }
+
+    #[test]
+    fn environment() {
+        let mut env = Environment::new();
+        env.set(b"HOME", b"/root");
+        env.set(b"TZ", b"UTC");
+        env.set(b"HOME", b"/home/veneer");
+        assert_eq!(env.len(), 2);
+        assert_eq!(env.get(b"HOME").unwrap(), "/home/veneer");
+        assert!(env.get(b"HOM").is_none());
+
+        env.remove(b"TZ");
+        assert!(env.get(b"TZ").is_none());
+
+        let envp = env.envp();
+        assert_eq!(envp.len(), 2);
+        assert!(envp[1].is_null());
+        assert_eq!(unsafe { CStr::from_ptr(envp[0]) }, "HOME=/home/veneer");
+
+        let borrowed = unsafe { Env::from_raw(envp.as_ptr()) };
+        assert_eq!(borrowed.var(b"HOME").unwrap(), "/home/veneer");
+    }
+
+    #[test]
+    fn environment_skips_empty_names() {
+        let envp = [
+            b"=value\0".as_ptr(),
+            b"HOME=/root\0".as_ptr(),
+            core::ptr::null(),
+        ];
+        let env = Environment::from(unsafe { Env::from_raw(envp.as_ptr()) });
+        assert_eq!(env.len(), 1);
+        assert_eq!(env.get(b"HOME").unwrap(), "/root");
+    }
+
+    #[test]
+    fn args_from_raw() {
+        let argv = [b"ls\0".as_ptr(), b"-l\0".as_ptr()];
+        let args = unsafe { Args::from_raw(argv.len(), argv.as_ptr()) };
+        assert_eq!(args.len(), 2);
+        assert_eq!(
+            args.map(|a| a.as_bytes()).collect::<Vec<_>>(),
+            [&b"ls"[..], &b"-l"[..]]
+        );
+    }
+}
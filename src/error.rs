@@ -1,5 +1,129 @@
+use crate::uapi::{self, Errno};
+
+/// An `errno` value from a failed system call
+///
+/// This holds the number itself, so it can carry values newer than [`Errno`] knows about, and
+/// compares equal to the plain constants in [`uapi`].
 #[derive(Clone, Copy)]
-pub struct Error(pub libc::c_int);
+pub struct Error(pub uapi::c_int);
+
+impl Error {
+    /// The [`Errno`] variant for this value, if it is one Linux defines
+    #[inline]
+    pub const fn errno(self) -> Option<Errno> {
+        Errno::from_raw(self.0)
+    }
+
+    /// The name of the constant, like `"ENOENT"`
+    #[inline]
+    pub fn name(self) -> Option<&'static str> {
+        self.errno().map(Errno::name)
+    }
+
+    /// The message `strerror` gives, like `"No such file or directory"`
+    #[inline]
+    pub fn description(self) -> Option<&'static str> {
+        self.errno().map(Errno::description)
+    }
+
+    /// What sort of failure this is, for callers which handle some errors but not others
+    #[inline]
+    pub fn kind(self) -> ErrorKind {
+        match self.0 {
+            uapi::ENOENT => ErrorKind::NotFound,
+            uapi::EPERM | uapi::EACCES => ErrorKind::PermissionDenied,
+            uapi::EEXIST => ErrorKind::AlreadyExists,
+            uapi::EAGAIN => ErrorKind::WouldBlock,
+            uapi::EINTR => ErrorKind::Interrupted,
+            uapi::EINVAL => ErrorKind::InvalidInput,
+            uapi::ETIMEDOUT => ErrorKind::TimedOut,
+            uapi::EPIPE => ErrorKind::BrokenPipe,
+            uapi::ENOMEM => ErrorKind::OutOfMemory,
+            uapi::ENOSYS | uapi::EOPNOTSUPP => ErrorKind::Unsupported,
+            uapi::EINPROGRESS => ErrorKind::InProgress,
+            uapi::ENOTDIR => ErrorKind::NotADirectory,
+            uapi::EISDIR => ErrorKind::IsADirectory,
+            uapi::ENOTEMPTY => ErrorKind::DirectoryNotEmpty,
+            uapi::EROFS => ErrorKind::ReadOnlyFilesystem,
+            uapi::ELOOP => ErrorKind::FilesystemLoop,
+            uapi::ESTALE => ErrorKind::StaleNetworkFileHandle,
+            uapi::ENOSPC => ErrorKind::StorageFull,
+            uapi::EDQUOT => ErrorKind::QuotaExceeded,
+            uapi::EFBIG => ErrorKind::FileTooLarge,
+            uapi::ESPIPE => ErrorKind::NotSeekable,
+            uapi::EBUSY => ErrorKind::ResourceBusy,
+            uapi::ETXTBSY => ErrorKind::ExecutableFileBusy,
+            uapi::EDEADLK => ErrorKind::Deadlock,
+            uapi::EXDEV => ErrorKind::CrossesDevices,
+            uapi::EMLINK => ErrorKind::TooManyLinks,
+            uapi::ENAMETOOLONG => ErrorKind::InvalidFilename,
+            uapi::E2BIG => ErrorKind::ArgumentListTooLong,
+            uapi::ECONNREFUSED => ErrorKind::ConnectionRefused,
+            uapi::ECONNRESET => ErrorKind::ConnectionReset,
+            uapi::ECONNABORTED => ErrorKind::ConnectionAborted,
+            uapi::ENOTCONN => ErrorKind::NotConnected,
+            uapi::EADDRINUSE => ErrorKind::AddrInUse,
+            uapi::EADDRNOTAVAIL => ErrorKind::AddrNotAvailable,
+            uapi::EHOSTUNREACH => ErrorKind::HostUnreachable,
+            uapi::ENETUNREACH => ErrorKind::NetworkUnreachable,
+            uapi::ENETDOWN => ErrorKind::NetworkDown,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+/// A classification of [`Error`]s, with the same variants and mapping as
+/// [`std::io::ErrorKind`](https://doc.rust-lang.org/std/io/enum.ErrorKind.html)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    NotFound,
+    PermissionDenied,
+    AlreadyExists,
+    WouldBlock,
+    Interrupted,
+    InvalidInput,
+    TimedOut,
+    BrokenPipe,
+    OutOfMemory,
+    Unsupported,
+    InProgress,
+    NotADirectory,
+    IsADirectory,
+    DirectoryNotEmpty,
+    ReadOnlyFilesystem,
+    FilesystemLoop,
+    StaleNetworkFileHandle,
+    StorageFull,
+    QuotaExceeded,
+    FileTooLarge,
+    NotSeekable,
+    ResourceBusy,
+    ExecutableFileBusy,
+    Deadlock,
+    CrossesDevices,
+    TooManyLinks,
+    InvalidFilename,
+    ArgumentListTooLong,
+    ConnectionRefused,
+    ConnectionReset,
+    ConnectionAborted,
+    NotConnected,
+    AddrInUse,
+    AddrNotAvailable,
+    HostUnreachable,
+    NetworkUnreachable,
+    NetworkDown,
+    /// Any error without a more specific kind
+    Other,
+}
+
+impl From<Errno> for Error {
+    #[inline]
+    fn from(errno: Errno) -> Self {
+        Self(errno.raw())
+    }
+}
 
 impl PartialEq<i32> for Error {
     #[inline]
@@ -8,6 +132,13 @@ impl PartialEq<i32> for Error {
     }
 }
 
+impl PartialEq<Errno> for Error {
+    #[inline]
+    fn eq(&self, other: &Errno) -> bool {
+        self.0 == other.raw()
+    }
+}
+
 impl core::fmt::Debug for Error {
     #[inline]
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
@@ -15,9 +146,51 @@ impl core::fmt::Debug for Error {
     }
 }
 
+/// Writes the `strerror` message and the constant's name, like
+/// `"No such file or directory (ENOENT)"`, or `"Unknown error 4000"` as glibc does
 impl core::fmt::Display for Error {
     #[inline]
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-        write!(f, "OS Error {}", self.0)
+        match self.errno() {
+            Some(errno) => write!(f, "{} ({})", errno.description(), errno.name()),
+            None => write!(f, "Unknown error {}", self.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_and_messages() {
+        let error = Error(uapi::ENOENT);
+        assert_eq!(error.name(), Some("ENOENT"));
+        assert_eq!(error.description(), Some("No such file or directory"));
+        assert_eq!(error.to_string(), "No such file or directory (ENOENT)");
+        assert_eq!(error, uapi::ENOENT);
+        assert_eq!(error, Errno::ENOENT);
+        assert_eq!(Error::from(Errno::EWOULDBLOCK), uapi::EAGAIN);
+
+        let unknown = Error(4000);
+        assert_eq!(unknown.name(), None);
+        assert_eq!(unknown.to_string(), "Unknown error 4000");
+        assert_eq!(unknown.kind(), ErrorKind::Other);
+    }
+
+    #[test]
+    fn kinds_match_std() {
+        for errno in 1..4096 {
+            let ours = format!("{:?}", Error(errno).kind());
+            let std = format!("{:?}", std::io::Error::from_raw_os_error(errno).kind());
+            // std calls the rest Uncategorized, which it does not let anyone else name
+            assert!(
+                ours == std || (ours == "Other" && std == "Uncategorized"),
+                "{} is {} here but {} in std",
+                errno,
+                ours,
+                std
+            );
+        }
     }
 }
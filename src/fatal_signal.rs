@@ -0,0 +1,334 @@
+//! Handlers for the signals the kernel sends when the program faults, which report what went wrong
+//! before letting the signal kill the process.
+//!
+//! Everything that runs in the handler must be async-signal-safe, so the report is assembled in a
+//! buffer on the signal stack and written to stderr with raw `write` calls.
+
+use crate::{
+    syscalls::{self, SigAction, SigStack},
+    uapi::{self, c_int},
+};
+use core::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+
+const SIGNALS: [(c_int, &[u8]); 4] = [
+    (uapi::SIGSEGV, b"SIGSEGV"),
+    (uapi::SIGBUS, b"SIGBUS"),
+    (uapi::SIGILL, b"SIGILL"),
+    (uapi::SIGFPE, b"SIGFPE"),
+];
+
+// Enough for the handler itself, even on machines with very large vector register files
+const SIGNAL_STACK_SIZE: usize = 64 * 1024;
+
+// The kernel will not grow a stack to within this many pages of another mapping
+const STACK_GUARD_GAP: usize = 256;
+
+// The addresses which a fault just past the end of the main thread's stack can touch
+static GUARD_START: AtomicUsize = AtomicUsize::new(0);
+static GUARD_END: AtomicUsize = AtomicUsize::new(0);
+
+/// Set up an alternate signal stack and install handlers for the fatal signals
+///
+/// Must be called after the auxiliary vector has been captured. If the signal stack cannot be
+/// allocated, no handlers are installed and faults kill the process silently as before.
+pub(crate) fn install() {
+    let page_size = crate::env::page_size();
+    let mapping = match syscalls::mmap(
+        core::ptr::null_mut(),
+        page_size + SIGNAL_STACK_SIZE,
+        uapi::PROT_READ | uapi::PROT_WRITE,
+        uapi::MAP_PRIVATE | uapi::MAP_ANONYMOUS,
+        None,
+        0,
+    ) {
+        Ok(mapping) => mapping,
+        Err(_) => return,
+    };
+
+    // A guard page below the signal stack, so that an overflow in the handler cannot silently
+    // corrupt whatever is mapped underneath it
+    let guard = unsafe { core::slice::from_raw_parts(mapping, page_size) };
+    let _ = syscalls::mprotect(guard, uapi::PROT_NONE);
+
+    let stack = SigStack {
+        sp: unsafe { mapping.add(page_size) },
+        flags: 0,
+        size: SIGNAL_STACK_SIZE,
+    };
+    if syscalls::sigaltstack(&stack).is_err() {
+        let _ = unsafe { syscalls::munmap(mapping, page_size + SIGNAL_STACK_SIZE) };
+        return;
+    }
+
+    if let Some((start, end)) = main_stack_guard(page_size) {
+        GUARD_START.store(start, Relaxed);
+        GUARD_END.store(end, Relaxed);
+    }
+
+    let restorer = restorer();
+    let action = SigAction {
+        handler: handle as *const () as usize,
+        flags: uapi::SA_SIGINFO
+            | uapi::SA_ONSTACK
+            | if restorer != 0 { uapi::SA_RESTORER } else { 0 },
+        restorer,
+        mask: 0,
+    };
+    for (signal, _) in SIGNALS.iter() {
+        let _ = syscalls::sigaction(*signal, &action, &mut SigAction::default());
+    }
+}
+
+/// The range of addresses just below the lowest address the main thread's stack may grow to
+///
+/// The kernel refuses to grow the stack past `RLIMIT_STACK`, so a fault in this range is an
+/// overflow. Returns `None` if the stack has no limit.
+fn main_stack_guard(page_size: usize) -> Option<(usize, usize)> {
+    let limit = syscalls::getrlimit(uapi::RLIMIT_STACK).ok()?.rlim_cur;
+    // RLIM64_INFINITY
+    if limit == u64::MAX {
+        return None;
+    }
+    // The kernel copies the path of the executable to the very top of the stack
+    let execfn = crate::env::execfn()?.as_bytes_with_nul().as_ptr() as usize;
+    let top = (execfn | (page_size - 1)) + 1;
+    let bottom = top.checked_sub(limit as usize)?;
+    Some((
+        bottom.saturating_sub(STACK_GUARD_GAP * page_size),
+        bottom + page_size,
+    ))
+}
+
+fn is_stack_overflow(address: usize, sp: usize) -> bool {
+    let page_size = crate::env::page_size();
+    let guard = GUARD_START.load(Relaxed)..GUARD_END.load(Relaxed);
+    // Memory right next to the stack pointer is only ever unmapped when the stack ran into it,
+    // which also catches overflows when the stack limit is unknown
+    guard.contains(&address)
+        || (sp.saturating_sub(page_size)..sp.saturating_add(page_size)).contains(&address)
+}
+
+// aarch64 has a signal trampoline in the vDSO, x86_64 and i386 need one from us
+#[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
+fn restorer() -> usize {
+    restore_rt as *const () as usize
+}
+
+#[cfg(target_arch = "aarch64")]
+fn restorer() -> usize {
+    0
+}
+
+#[cfg(target_arch = "x86_64")]
+#[unsafe(naked)]
+unsafe extern "C" fn restore_rt() {
+    core::arch::naked_asm!("mov rax, 15", "syscall")
+}
+
+#[cfg(target_arch = "x86")]
+#[unsafe(naked)]
+unsafe extern "C" fn restore_rt() {
+    core::arch::naked_asm!("mov eax, 173", "int 0x80")
+}
+
+// Offsets into the kernel's siginfo_t, where the address is after three ints and aligned like a
+// pointer
+const SI_CODE: usize = 8;
+#[cfg(target_pointer_width = "64")]
+const SI_ADDR: usize = 16;
+#[cfg(target_pointer_width = "32")]
+const SI_ADDR: usize = 12;
+
+// Names of the general purpose registers in the kernel's sigcontext, which starts at offset 40 in
+// the ucontext_t, and their indices in it
+#[cfg(target_arch = "x86_64")]
+const MCONTEXT: usize = 40;
+#[cfg(target_arch = "x86_64")]
+const REGISTERS: [(&[u8], usize); 18] = [
+    (b"rax", 13),
+    (b"rbx", 11),
+    (b"rcx", 14),
+    (b"rdx", 12),
+    (b"rsi", 9),
+    (b"rdi", 8),
+    (b"rbp", 10),
+    (b"rsp", 15),
+    (b"r8", 0),
+    (b"r9", 1),
+    (b"r10", 2),
+    (b"r11", 3),
+    (b"r12", 4),
+    (b"r13", 5),
+    (b"r14", 6),
+    (b"r15", 7),
+    (b"rip", 16),
+    (b"eflags", 17),
+];
+#[cfg(target_arch = "x86_64")]
+const SP: usize = 15;
+
+// On i386 the sigcontext is at offset 20, and starts with the four segment registers
+#[cfg(target_arch = "x86")]
+const MCONTEXT: usize = 20;
+#[cfg(target_arch = "x86")]
+const REGISTERS: [(&[u8], usize); 10] = [
+    (b"eax", 11),
+    (b"ebx", 8),
+    (b"ecx", 10),
+    (b"edx", 9),
+    (b"esi", 5),
+    (b"edi", 4),
+    (b"ebp", 6),
+    (b"esp", 7),
+    (b"eip", 14),
+    (b"eflags", 16),
+];
+#[cfg(target_arch = "x86")]
+const SP: usize = 7;
+
+// On aarch64 the sigcontext is at offset 176 and begins with the fault address, followed by
+// x0-x30, sp, pc and pstate
+#[cfg(target_arch = "aarch64")]
+const MCONTEXT: usize = 176 + 8;
+#[cfg(target_arch = "aarch64")]
+const REGISTERS: [(&[u8], usize); 34] = [
+    (b"x0", 0),
+    (b"x1", 1),
+    (b"x2", 2),
+    (b"x3", 3),
+    (b"x4", 4),
+    (b"x5", 5),
+    (b"x6", 6),
+    (b"x7", 7),
+    (b"x8", 8),
+    (b"x9", 9),
+    (b"x10", 10),
+    (b"x11", 11),
+    (b"x12", 12),
+    (b"x13", 13),
+    (b"x14", 14),
+    (b"x15", 15),
+    (b"x16", 16),
+    (b"x17", 17),
+    (b"x18", 18),
+    (b"x19", 19),
+    (b"x20", 20),
+    (b"x21", 21),
+    (b"x22", 22),
+    (b"x23", 23),
+    (b"x24", 24),
+    (b"x25", 25),
+    (b"x26", 26),
+    (b"x27", 27),
+    (b"x28", 28),
+    (b"fp", 29),
+    (b"lr", 30),
+    (b"sp", 31),
+    (b"pc", 32),
+    (b"pstate", 33),
+];
+#[cfg(target_arch = "aarch64")]
+const SP: usize = 31;
+
+extern "C" fn handle(signal: c_int, info: *const u8, context: *const u8) {
+    let (code, address) = unsafe {
+        (
+            info.add(SI_CODE).cast::<c_int>().read(),
+            info.add(SI_ADDR).cast::<usize>().read(),
+        )
+    };
+    let registers = unsafe { context.add(MCONTEXT).cast::<usize>() };
+    let register = |index: usize| unsafe { registers.add(index).read() };
+
+    let name = SIGNALS
+        .iter()
+        .find(|(s, _)| *s == signal)
+        .map_or(&b"signal"[..], |(_, name)| name);
+
+    let mut report = Report::new();
+    // A positive code means the kernel sent the signal because of a fault
+    if code > 0 {
+        if (signal == uapi::SIGSEGV || signal == uapi::SIGBUS)
+            && is_stack_overflow(address, register(SP))
+        {
+            report.push(b"\nthread main has overflowed its stack");
+        }
+        report
+            .push(b"\nfatal signal ")
+            .push(name)
+            .push(b" at address ")
+            .push_hex(address);
+    } else {
+        report.push(b"\nfatal signal ").push(name);
+    }
+    report.push(b"\n");
+    for (i, (name, index)) in REGISTERS.iter().enumerate() {
+        report
+            .push(&b"      "[name.len()..])
+            .push(name)
+            .push(b" ")
+            .push_hex(register(*index))
+            .push(if i % 4 == 3 || i + 1 == REGISTERS.len() {
+                b"\n"
+            } else {
+                b" "
+            });
+    }
+    report.flush();
+
+    // Let the default action kill the process. When we return a faulting instruction runs again
+    // and faults again, but a signal that was sent to us must be sent again; it stays pending
+    // until this handler returns.
+    let _ = syscalls::sigaction(signal, &SigAction::default(), &mut SigAction::default());
+    if code <= 0 {
+        let _ = syscalls::tgkill(syscalls::getpid(), syscalls::gettid(), signal);
+    }
+}
+
+/// A fixed-size buffer for building a message without allocating or using `core::fmt`
+struct Report {
+    buf: [u8; 512],
+    len: usize,
+}
+
+impl Report {
+    fn new() -> Self {
+        Self {
+            buf: [0; 512],
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, bytes: &[u8]) -> &mut Self {
+        for byte in bytes {
+            if self.len == self.buf.len() {
+                self.flush();
+            }
+            self.buf[self.len] = *byte;
+            self.len += 1;
+        }
+        self
+    }
+
+    fn push_hex(&mut self, value: usize) -> &mut Self {
+        let mut digits = [0u8; 2 + 2 * core::mem::size_of::<usize>()];
+        digits[..2].copy_from_slice(b"0x");
+        for (i, digit) in digits[2..].iter_mut().rev().enumerate() {
+            *digit = b"0123456789abcdef"[(value >> (4 * i)) & 0xf];
+        }
+        self.push(&digits)
+    }
+
+    fn flush(&mut self) {
+        let mut written = 0;
+        while written < self.len {
+            match syscalls::write(crate::fd::STDERR, &self.buf[written..self.len]) {
+                Ok(0) => break,
+                Ok(n) => written += n,
+                Err(e) if e == uapi::EINTR => {}
+                Err(_) => break,
+            }
+        }
+        self.len = 0;
+    }
+}
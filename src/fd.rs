@@ -0,0 +1,245 @@
+//! File descriptors which close themselves, and borrows of them which cannot outlive the owner
+//!
+//! These mirror `std::os::fd`. An [`OwnedFd`] closes its descriptor when it is dropped, a
+//! [`BorrowedFd`] is a copyable handle to a descriptor which stays open for `'fd`, and the traits
+//! convert between them and the bare integers the kernel uses. The wrappers in [`syscalls`] take a
+//! `BorrowedFd` and return an `OwnedFd`, so a descriptor can neither be leaked by forgetting to
+//! close it nor used after it has been closed.
+
+use crate::{
+    syscalls::{self, FdFlags, OpenFlags},
+    uapi::{self, c_int},
+    Error,
+};
+use core::{fmt, marker::PhantomData, mem};
+
+/// The kernel's representation of a file descriptor
+pub type RawFd = c_int;
+
+/// The current working directory, for the system calls which resolve paths relative to a
+/// directory
+pub const CWD: BorrowedFd<'static> = unsafe { BorrowedFd::borrow_raw(uapi::AT_FDCWD) };
+pub const STDIN: BorrowedFd<'static> = unsafe { BorrowedFd::borrow_raw(uapi::STDIN_FILENO) };
+pub const STDOUT: BorrowedFd<'static> = unsafe { BorrowedFd::borrow_raw(uapi::STDOUT_FILENO) };
+pub const STDERR: BorrowedFd<'static> = unsafe { BorrowedFd::borrow_raw(uapi::STDERR_FILENO) };
+
+/// An open file descriptor, which is closed when this is dropped
+///
+/// Errors from closing it on drop are ignored. Pass it to [`syscalls::close`] to see them.
+#[repr(transparent)]
+pub struct OwnedFd {
+    fd: RawFd,
+}
+
+impl OwnedFd {
+    /// Make another descriptor for the same open file, with `CLOEXEC` set
+    #[inline]
+    pub fn try_clone(&self) -> Result<Self, Error> {
+        self.as_fd().try_clone_to_owned()
+    }
+}
+
+impl Drop for OwnedFd {
+    #[inline]
+    fn drop(&mut self) {
+        let _ = syscalls::close_raw(self.fd);
+    }
+}
+
+impl fmt::Debug for OwnedFd {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fd.fmt(f)
+    }
+}
+
+/// A file descriptor which is open for at least `'fd`
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct BorrowedFd<'fd> {
+    fd: RawFd,
+    _owner: PhantomData<&'fd OwnedFd>,
+}
+
+impl BorrowedFd<'_> {
+    /// Borrow a descriptor which is owned by something else
+    ///
+    /// # Safety
+    ///
+    /// `fd` must stay open for as long as the result is in use
+    #[inline]
+    pub const unsafe fn borrow_raw(fd: RawFd) -> Self {
+        Self {
+            fd,
+            _owner: PhantomData,
+        }
+    }
+
+    /// Make a new descriptor for the same open file, with `CLOEXEC` set
+    #[inline]
+    pub fn try_clone_to_owned(&self) -> Result<OwnedFd, Error> {
+        syscalls::fcntl_dupfd_cloexec(*self, 0)
+    }
+}
+
+impl fmt::Debug for BorrowedFd<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fd.fmt(f)
+    }
+}
+
+/// Borrow the descriptor of something which owns one
+pub trait AsFd {
+    fn as_fd(&self) -> BorrowedFd<'_>;
+}
+
+/// Get the number of a descriptor, without giving up ownership of it
+pub trait AsRawFd {
+    fn as_raw_fd(&self) -> RawFd;
+}
+
+/// Give up ownership of a descriptor, which the caller is then responsible for closing
+pub trait IntoRawFd {
+    fn into_raw_fd(self) -> RawFd;
+}
+
+/// Take ownership of a descriptor
+pub trait FromRawFd {
+    /// # Safety
+    ///
+    /// `fd` must be open, and must not be owned by anything else, which would close it too
+    unsafe fn from_raw_fd(fd: RawFd) -> Self;
+}
+
+impl AsFd for OwnedFd {
+    #[inline]
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        unsafe { BorrowedFd::borrow_raw(self.fd) }
+    }
+}
+
+impl AsFd for BorrowedFd<'_> {
+    #[inline]
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        *self
+    }
+}
+
+impl<T: AsFd + ?Sized> AsFd for &T {
+    #[inline]
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        T::as_fd(self)
+    }
+}
+
+impl AsRawFd for OwnedFd {
+    #[inline]
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl AsRawFd for BorrowedFd<'_> {
+    #[inline]
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl IntoRawFd for OwnedFd {
+    #[inline]
+    fn into_raw_fd(self) -> RawFd {
+        let fd = self.fd;
+        mem::forget(self);
+        fd
+    }
+}
+
+impl FromRawFd for OwnedFd {
+    #[inline]
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        Self { fd }
+    }
+}
+
+/// Set or clear `FD_CLOEXEC`, which closes `fd` in the new program when this process calls
+/// `execve`
+#[inline]
+pub fn set_cloexec(fd: BorrowedFd<'_>, cloexec: bool) -> Result<(), Error> {
+    let mut flags = syscalls::fcntl_getfd(fd)?;
+    flags.set(FdFlags::CLOEXEC, cloexec);
+    syscalls::fcntl_setfd(fd, flags)
+}
+
+/// Set or clear `O_NONBLOCK` on the open file `fd` refers to, which is shared with every
+/// descriptor duplicated from it
+#[inline]
+pub fn set_nonblocking(fd: BorrowedFd<'_>, nonblocking: bool) -> Result<(), Error> {
+    let mut flags = syscalls::fcntl_getfl(fd)?;
+    flags.set(OpenFlags::NONBLOCK, nonblocking);
+    syscalls::fcntl_setfl(fd, flags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syscalls::Pipe2Flags;
+
+    #[test]
+    fn flags_and_duplicates() {
+        let (read, write) = syscalls::pipe2(Pipe2Flags::CLOEXEC).unwrap();
+        assert_eq!(
+            syscalls::fcntl_getfd(read.as_fd()).unwrap(),
+            FdFlags::CLOEXEC
+        );
+        set_cloexec(read.as_fd(), false).unwrap();
+        assert_eq!(
+            syscalls::fcntl_getfd(read.as_fd()).unwrap(),
+            FdFlags::empty()
+        );
+
+        set_nonblocking(read.as_fd(), true).unwrap();
+        assert!(syscalls::fcntl_getfl(read.as_fd())
+            .unwrap()
+            .contains(OpenFlags::NONBLOCK));
+        let mut buf = [0u8; 4];
+        assert_eq!(
+            syscalls::read(read.as_fd(), &mut buf).unwrap_err(),
+            uapi::EAGAIN
+        );
+
+        // A duplicate shares the file status flags but not the descriptor flags
+        let copy = read.try_clone().unwrap();
+        assert_ne!(copy.as_raw_fd(), read.as_raw_fd());
+        assert_eq!(
+            syscalls::fcntl_getfd(copy.as_fd()).unwrap(),
+            FdFlags::CLOEXEC
+        );
+        assert!(syscalls::fcntl_getfl(copy.as_fd())
+            .unwrap()
+            .contains(OpenFlags::NONBLOCK));
+
+        syscalls::write(write.as_fd(), b"ping").unwrap();
+        assert_eq!(syscalls::read(copy.as_fd(), &mut buf).unwrap(), 4);
+        assert_eq!(&buf, b"ping");
+
+        let size = syscalls::fcntl_setpipe_sz(write.as_fd(), 1 << 16).unwrap();
+        assert!(size >= 1 << 16);
+        assert_eq!(syscalls::fcntl_getpipe_sz(read.as_fd()).unwrap(), size);
+    }
+
+    #[test]
+    fn ownership_roundtrip() {
+        let (read, write) = syscalls::pipe2(Pipe2Flags::CLOEXEC).unwrap();
+        let raw = write.into_raw_fd();
+        // Still open, because giving up ownership does not close it
+        syscalls::write(unsafe { BorrowedFd::borrow_raw(raw) }, b"!").unwrap();
+        let write = unsafe { OwnedFd::from_raw_fd(raw) };
+        assert!(syscalls::close(write).is_ok());
+        // With the only write end closed, reading finds the byte and then the end of the pipe
+        let mut buf = [0u8; 2];
+        assert_eq!(syscalls::read(read.as_fd(), &mut buf).unwrap(), 1);
+        assert_eq!(syscalls::read(read.as_fd(), &mut buf).unwrap(), 0);
+    }
+}
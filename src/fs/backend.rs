@@ -0,0 +1,190 @@
+//! The system calls [`File`](super::File), [`Directory`](super::Directory), and
+//! [`read`](super::read) are made through, so that programs built on them can be tested against
+//! something other than the real disk
+//!
+//! The default is [`Kernel`], which makes the system calls in [`syscalls`]. Tests can select a
+//! [`MemFs`](super::MemFs) or their own implementation with [`set_backend`]. A file or directory
+//! keeps using the backend that opened it, even if another one is selected later. The standard
+//! streams in [`io`](crate::io) always write to the kernel.
+
+use crate::{
+    fd::{BorrowedFd, FromRawFd, IntoRawFd, OwnedFd},
+    spinlock::SpinLock,
+    syscalls::{
+        self,
+        probe::{self, Capability},
+        OpenFlags, OpenMode,
+    },
+    uapi::{self, c_int},
+    CStr, Error,
+};
+
+/// The operations on files and directories that [`fs`](super) needs, with the same arguments and
+/// errors as the system calls of the same names
+///
+/// Descriptors are bare numbers here rather than [`OwnedFd`]s, because only the kernel's refer to
+/// anything the kernel can close. Each backend owns the ones it hands out until they are passed to
+/// [`close`](Backend::close).
+pub trait Backend: Sync {
+    fn openat(
+        &self,
+        at_fd: c_int,
+        path: CStr,
+        flags: OpenFlags,
+        mode: OpenMode,
+    ) -> Result<c_int, Error>;
+
+    fn close(&self, fd: c_int) -> Result<(), Error>;
+
+    fn read(&self, fd: c_int, bytes: &mut [u8]) -> Result<usize, Error>;
+
+    fn write(&self, fd: c_int, bytes: &[u8]) -> Result<usize, Error>;
+
+    fn fstatat(&self, fd: c_int, path: CStr) -> Result<uapi::stat, Error>;
+
+    fn lstatat(&self, fd: c_int, path: CStr) -> Result<uapi::stat, Error>;
+
+    /// Fill `buf` with `linux_dirent64` records for the next entries of the directory open at `fd`
+    fn getdents64(&self, fd: c_int, buf: &mut [u8]) -> Result<usize, Error>;
+
+    fn readlinkat<'a>(&self, fd: c_int, path: CStr, buf: &'a mut [u8]) -> Result<&'a [u8], Error>;
+
+    fn faccessat(&self, fd: c_int, path: CStr, mode: c_int) -> Result<(), Error>;
+
+    /// Like `statx`, or `ENOSYS` to make callers fall back to [`fstatat`](Backend::fstatat)
+    #[inline]
+    fn statx(
+        &self,
+        _fd: c_int,
+        _path: CStr,
+        _flags: c_int,
+        _mask: uapi::c_uint,
+    ) -> Result<uapi::statx, Error> {
+        Err(Error(uapi::ENOSYS))
+    }
+
+    /// Like `openat2`, or `ENOSYS` to make callers fall back to [`openat`](Backend::openat)
+    #[inline]
+    fn openat2(&self, _at_fd: c_int, _path: CStr, _how: &uapi::open_how) -> Result<c_int, Error> {
+        Err(Error(uapi::ENOSYS))
+    }
+
+    /// Like `copy_file_range` on the files' offsets, or `ENOSYS` to make callers fall back to
+    /// [`read`](Backend::read) and [`write`](Backend::write)
+    #[inline]
+    fn copy_file_range(&self, _fd_in: c_int, _fd_out: c_int, _len: usize) -> Result<usize, Error> {
+        Err(Error(uapi::ENOSYS))
+    }
+}
+
+/// The real filesystem, through the system calls in [`syscalls`]
+///
+/// The methods for system calls which older kernels lack return `ENOSYS` once [`probe`] has found
+/// them missing.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Kernel;
+
+/// Borrow a descriptor that the caller got from [`Kernel::openat`]
+///
+/// A number which is not open only makes the system call fail with `EBADF`. One which has been
+/// reused for another file is the caller's mistake, as it would be with the system call itself.
+fn borrow(fd: c_int) -> BorrowedFd<'static> {
+    unsafe { BorrowedFd::borrow_raw(fd) }
+}
+
+impl Backend for Kernel {
+    #[inline]
+    fn openat(
+        &self,
+        at_fd: c_int,
+        path: CStr,
+        flags: OpenFlags,
+        mode: OpenMode,
+    ) -> Result<c_int, Error> {
+        syscalls::openat(borrow(at_fd), path, flags, mode).map(IntoRawFd::into_raw_fd)
+    }
+
+    #[inline]
+    fn close(&self, fd: c_int) -> Result<(), Error> {
+        syscalls::close(unsafe { OwnedFd::from_raw_fd(fd) })
+    }
+
+    #[inline]
+    fn read(&self, fd: c_int, bytes: &mut [u8]) -> Result<usize, Error> {
+        syscalls::read(borrow(fd), bytes)
+    }
+
+    #[inline]
+    fn write(&self, fd: c_int, bytes: &[u8]) -> Result<usize, Error> {
+        syscalls::write(borrow(fd), bytes)
+    }
+
+    #[inline]
+    fn fstatat(&self, fd: c_int, path: CStr) -> Result<uapi::stat, Error> {
+        syscalls::fstatat(borrow(fd), path)
+    }
+
+    #[inline]
+    fn lstatat(&self, fd: c_int, path: CStr) -> Result<uapi::stat, Error> {
+        syscalls::lstatat(borrow(fd), path)
+    }
+
+    #[inline]
+    fn getdents64(&self, fd: c_int, buf: &mut [u8]) -> Result<usize, Error> {
+        syscalls::getdents64(borrow(fd), buf)
+    }
+
+    #[inline]
+    fn readlinkat<'a>(&self, fd: c_int, path: CStr, buf: &'a mut [u8]) -> Result<&'a [u8], Error> {
+        syscalls::readlinkat(borrow(fd), path, buf)
+    }
+
+    #[inline]
+    fn faccessat(&self, fd: c_int, path: CStr, mode: c_int) -> Result<(), Error> {
+        syscalls::faccessat(borrow(fd), path, mode)
+    }
+
+    #[inline]
+    fn statx(
+        &self,
+        fd: c_int,
+        path: CStr,
+        flags: c_int,
+        mask: uapi::c_uint,
+    ) -> Result<uapi::statx, Error> {
+        probe::attempt(Capability::Statx, || {
+            syscalls::statx(borrow(fd), path, flags, mask)
+        })
+    }
+
+    #[inline]
+    fn openat2(&self, at_fd: c_int, path: CStr, how: &uapi::open_how) -> Result<c_int, Error> {
+        probe::attempt(Capability::Openat2, || {
+            syscalls::openat2(borrow(at_fd), path, how).map(IntoRawFd::into_raw_fd)
+        })
+    }
+
+    #[inline]
+    fn copy_file_range(&self, fd_in: c_int, fd_out: c_int, len: usize) -> Result<usize, Error> {
+        probe::attempt(Capability::CopyFileRange, || {
+            syscalls::copy_file_range(borrow(fd_in), borrow(fd_out), len)
+        })
+    }
+}
+
+static BACKEND: SpinLock<Option<&'static dyn Backend>> = SpinLock::new(None);
+
+/// Make files and directories opened from now on use `backend`
+///
+/// A `MemFs` can be made `'static` with `Box::leak`.
+#[inline]
+pub fn set_backend(backend: &'static dyn Backend) {
+    *BACKEND.lock() = Some(backend);
+}
+
+/// The backend that files and directories are opened with, which is [`Kernel`] unless
+/// [`set_backend`] has been called
+#[inline]
+pub fn backend() -> &'static dyn Backend {
+    BACKEND.lock().unwrap_or(&Kernel)
+}
@@ -1,31 +1,67 @@
+use super::{
+    backend::{self, Backend},
+    File,
+};
 use crate::{
-    syscalls,
+    fd::{AsRawFd, FromRawFd, IntoRawFd, RawFd},
     syscalls::{OpenFlags, OpenMode},
-    CStr, Error,
+    uapi, CStr, Error,
 };
 use alloc::{vec, vec::Vec};
-use core::convert::TryInto;
-use libc::c_int;
+use core::{convert::TryInto, mem};
 
+/// A directory open through a [`Backend`], whose descriptor is a kernel one in the same cases as a
+/// [`File`]'s
 pub struct Directory {
-    fd: c_int,
+    fd: RawFd,
+    backend: &'static dyn Backend,
 }
 
 impl Directory {
     #[inline]
     pub fn open(path: CStr) -> Result<Self, Error> {
-        Ok(Self {
-            fd: syscalls::openat(
-                libc::AT_FDCWD,
-                path,
-                OpenFlags::RDONLY | OpenFlags::DIRECTORY | OpenFlags::CLOEXEC,
-                OpenMode::empty(),
-            )?,
+        let backend = backend::backend();
+        let fd = backend.openat(
+            uapi::AT_FDCWD,
+            path,
+            OpenFlags::RDONLY | OpenFlags::DIRECTORY | OpenFlags::CLOEXEC,
+            OpenMode::empty(),
+        )?;
+        Ok(Self { fd, backend })
+    }
+
+    /// Open the file at `path` in this directory for reading, refusing to leave the directory
+    /// while resolving it
+    ///
+    /// With `openat2` the kernel checks every step, including symbolic links. Without it, absolute
+    /// paths and paths with a `..` component are refused, but a symbolic link can still lead out of
+    /// the directory. Paths which would leave it fail with `EXDEV`.
+    #[inline]
+    pub fn open_file(&self, path: CStr) -> Result<File, Error> {
+        let flags = OpenFlags::RDONLY | OpenFlags::CLOEXEC;
+        let how = uapi::open_how {
+            flags: flags.bits() as u64,
+            mode: 0,
+            resolve: uapi::RESOLVE_BENEATH | uapi::RESOLVE_NO_MAGICLINKS,
+        };
+        let fd = match self.backend.openat2(self.fd, path, &how) {
+            Err(Error(uapi::ENOSYS)) => {
+                if path.first() == Some(&b'/') || path.split(|&b| b == b'/').any(|c| c == b"..") {
+                    return Err(Error(uapi::EXDEV));
+                }
+                self.backend
+                    .openat(self.fd, path, flags, OpenMode::empty())?
+            }
+            result => result?,
+        };
+        Ok(File {
+            fd,
+            backend: self.backend,
         })
     }
 
     #[inline]
-    pub fn raw_fd(&self) -> c_int {
+    pub fn raw_fd(&self) -> RawFd {
         self.fd
     }
 
@@ -34,12 +70,14 @@ impl Directory {
         let mut contents = vec![0u8; 4096];
 
         // First, read using the first half of the allocation
-        let mut previous_bytes_used = syscalls::getdents64(self.fd, &mut contents[..2048])?;
+        let mut previous_bytes_used = self.backend.getdents64(self.fd, &mut contents[..2048])?;
         let mut bytes_used = previous_bytes_used;
 
         // If we read something, try using the rest of the allocation
         if previous_bytes_used > 0 {
-            bytes_used += syscalls::getdents64(self.fd, &mut contents[previous_bytes_used..])?;
+            bytes_used += self
+                .backend
+                .getdents64(self.fd, &mut contents[previous_bytes_used..])?;
         }
         // Then, if we read something on the second time, start reallocating.
 
@@ -48,7 +86,9 @@ impl Directory {
         while bytes_used != previous_bytes_used {
             previous_bytes_used = bytes_used;
             contents.extend(core::iter::repeat(0).take(contents.capacity()));
-            bytes_used += syscalls::getdents64(self.fd, &mut contents[previous_bytes_used..])?;
+            bytes_used += self
+                .backend
+                .getdents64(self.fd, &mut contents[previous_bytes_used..])?;
         }
 
         contents.truncate(bytes_used);
@@ -60,7 +100,33 @@ impl Directory {
 impl Drop for Directory {
     #[inline]
     fn drop(&mut self) {
-        let _ = syscalls::close(self.fd);
+        let _ = self.backend.close(self.fd);
+    }
+}
+
+impl AsRawFd for Directory {
+    #[inline]
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl IntoRawFd for Directory {
+    #[inline]
+    fn into_raw_fd(self) -> RawFd {
+        let fd = self.fd;
+        mem::forget(self);
+        fd
+    }
+}
+
+impl FromRawFd for Directory {
+    #[inline]
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        Self {
+            fd,
+            backend: &backend::Kernel,
+        }
     }
 }
 
@@ -107,14 +173,14 @@ impl<'a> Iterator for IterDir<'a> {
             inode,
             name,
             d_type: match d_type {
-                0 => DType::UNKNOWN,
-                1 => DType::FIFO,
-                2 => DType::CHR,
-                4 => DType::DIR,
-                6 => DType::BLK,
-                8 => DType::REG,
-                10 => DType::LNK,
-                12 => DType::SOCK,
+                uapi::DT_UNKNOWN => DType::UNKNOWN,
+                uapi::DT_FIFO => DType::FIFO,
+                uapi::DT_CHR => DType::CHR,
+                uapi::DT_DIR => DType::DIR,
+                uapi::DT_BLK => DType::BLK,
+                uapi::DT_REG => DType::REG,
+                uapi::DT_LNK => DType::LNK,
+                uapi::DT_SOCK => DType::SOCK,
                 _ => DType::UNKNOWN,
             },
         })
@@ -123,15 +189,15 @@ impl<'a> Iterator for IterDir<'a> {
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
         (
-            self.remaining.len() / core::mem::size_of::<libc::dirent64>(),
-            Some(self.remaining.len() / (core::mem::size_of::<libc::dirent64>() - 256)),
+            self.remaining.len() / (core::mem::size_of::<uapi::linux_dirent64>() + 256),
+            Some(self.remaining.len() / core::mem::size_of::<uapi::linux_dirent64>()),
         )
     }
 }
 
 #[derive(Clone)]
 pub struct DirEntry<'a> {
-    inode: libc::c_ulong,
+    inode: u64,
     name: CStr<'a>,
     d_type: DType,
 }
@@ -143,7 +209,7 @@ impl<'a> DirEntry<'a> {
     }
 
     #[inline]
-    pub fn inode(&self) -> libc::c_ulong {
+    pub fn inode(&self) -> u64 {
         self.inode
     }
 
@@ -197,4 +263,20 @@ mod tests {
             assert_eq!(libc.d_type, ven.d_type as u8);
         }
     }
+
+    #[test]
+    fn open_file_stays_inside() {
+        std::fs::create_dir_all("/tmp/veneer-open-file/inner").unwrap();
+        std::fs::write("/tmp/veneer-open-file/inner/file", "inside").unwrap();
+        let dir = Directory::open(CStr::from_bytes(b"/tmp/veneer-open-file\0")).unwrap();
+
+        let mut file = dir.open_file(CStr::from_bytes(b"inner/file\0")).unwrap();
+        let mut buf = [0; 16];
+        let n = crate::io::Read::read(&mut file, &mut buf).unwrap();
+        assert_eq!(&buf[..n], b"inside");
+        for path in [&b"../veneer-open-file/inner/file\0"[..], b"/etc/hostname\0"] {
+            let escape = dir.open_file(CStr::from_bytes(path));
+            assert_eq!(escape.err().map(|e| e.0), Some(uapi::EXDEV));
+        }
+    }
 }
@@ -0,0 +1,766 @@
+//! A filesystem which only exists in memory, for testing code built on [`fs`](super) without
+//! touching the disk or making system calls
+//!
+//! A [`MemFs`] starts out as an empty root directory. Files, directories, and symbolic links can be
+//! added with its own methods, which act as root and skip permission checks, or through the
+//! [`Backend`] methods, which check permissions as the owner of every inode does. Paths relative to
+//! `AT_FDCWD` start at the root. Directories list `.` and `..` first, then their entries in the
+//! order they were created. There are no hard links, timestamps, or umask, and the descriptors it
+//! hands out are only meaningful to the same `MemFs`.
+
+use super::backend::Backend;
+use crate::{
+    spinlock::SpinLock,
+    syscalls::{OpenFlags, OpenMode},
+    uapi::{self, c_int, mode_t},
+    CStr, Error,
+};
+use alloc::{vec, vec::Vec};
+use core::{convert::TryFrom, mem};
+
+// Like Linux, give up resolving a path after following this many symbolic links
+const MAX_SYMLINKS: usize = 40;
+// Descriptors are numbered after the standard streams'
+const FIRST_FD: c_int = 3;
+const ROOT: usize = 0;
+
+pub struct MemFs {
+    state: SpinLock<State>,
+}
+
+struct State {
+    inodes: Vec<Inode>,
+    files: Vec<Option<OpenFile>>,
+}
+
+struct Inode {
+    /// The type and permission bits, as in `st_mode`
+    mode: mode_t,
+    data: Data,
+}
+
+enum Data {
+    File(Vec<u8>),
+    Directory { parent: usize, entries: Vec<Entry> },
+    Symlink(Vec<u8>),
+}
+
+struct Entry {
+    name: Vec<u8>,
+    inode: usize,
+}
+
+struct OpenFile {
+    inode: usize,
+    flags: OpenFlags,
+    /// The position in a file, or the number of entries already listed from a directory
+    offset: usize,
+}
+
+/// Where a path led: the directory holding its last component, the component itself, and the
+/// inode it names if it exists
+struct Lookup {
+    parent: usize,
+    name: Vec<u8>,
+    inode: Option<usize>,
+}
+
+impl Default for MemFs {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemFs {
+    /// An empty filesystem with a root directory that anyone can use
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            state: SpinLock::new(State {
+                inodes: vec![Inode {
+                    mode: uapi::S_IFDIR | 0o777,
+                    data: Data::Directory {
+                        parent: ROOT,
+                        entries: Vec::new(),
+                    },
+                }],
+                files: Vec::new(),
+            }),
+        }
+    }
+
+    /// Create a directory at `path` with the permissions in `mode`, like `mkdir`
+    #[inline]
+    pub fn create_dir(&self, path: &[u8], mode: mode_t) -> Result<(), Error> {
+        self.create(path, uapi::S_IFDIR | (mode & 0o7777), |parent| {
+            Data::Directory {
+                parent,
+                entries: Vec::new(),
+            }
+        })
+    }
+
+    /// Create a file at `path` holding `contents` with the permissions in `mode`
+    #[inline]
+    pub fn create_file(&self, path: &[u8], contents: &[u8], mode: mode_t) -> Result<(), Error> {
+        self.create(path, uapi::S_IFREG | (mode & 0o7777), |_| {
+            Data::File(contents.to_vec())
+        })
+    }
+
+    /// Create a symbolic link at `path` which points to `target`, like `symlink`
+    #[inline]
+    pub fn symlink(&self, target: &[u8], path: &[u8]) -> Result<(), Error> {
+        self.create(path, uapi::S_IFLNK | 0o777, |_| {
+            Data::Symlink(target.to_vec())
+        })
+    }
+
+    /// Change the permissions of the inode at `path`, following symbolic links like `chmod`
+    #[inline]
+    pub fn set_permissions(&self, path: &[u8], mode: mode_t) -> Result<(), Error> {
+        let mut state = self.state.lock();
+        let inode = state
+            .lookup(uapi::AT_FDCWD, path, true, false)?
+            .inode
+            .ok_or(Error(uapi::ENOENT))?;
+        let inode = &mut state.inodes[inode];
+        inode.mode = (inode.mode & uapi::S_IFMT) | (mode & 0o7777);
+        Ok(())
+    }
+
+    /// Remove the file, symbolic link, or empty directory at `path`
+    ///
+    /// Descriptors which are open on it keep working.
+    #[inline]
+    pub fn remove(&self, path: &[u8]) -> Result<(), Error> {
+        let mut state = self.state.lock();
+        let found = state.lookup(uapi::AT_FDCWD, path, false, false)?;
+        let inode = found.inode.ok_or(Error(uapi::ENOENT))?;
+        if inode == ROOT {
+            return Err(Error(uapi::EBUSY));
+        }
+        if let b"." | b".." = &found.name[..] {
+            return Err(Error(uapi::EINVAL));
+        }
+        if let Data::Directory { entries, .. } = &state.inodes[inode].data {
+            if !entries.is_empty() {
+                return Err(Error(uapi::ENOTEMPTY));
+            }
+        }
+        if let Data::Directory { entries, .. } = &mut state.inodes[found.parent].data {
+            entries.retain(|entry| entry.name != found.name);
+        }
+        Ok(())
+    }
+
+    fn create(
+        &self,
+        path: &[u8],
+        mode: mode_t,
+        data: impl FnOnce(usize) -> Data,
+    ) -> Result<(), Error> {
+        let mut state = self.state.lock();
+        let found = state.lookup(uapi::AT_FDCWD, path, false, false)?;
+        if found.inode.is_some() {
+            return Err(Error(uapi::EEXIST));
+        }
+        let data = data(found.parent);
+        state.link(found.parent, found.name, Inode { mode, data });
+        Ok(())
+    }
+}
+
+impl State {
+    /// Resolve `path` relative to the directory open at `at_fd`, following a symbolic link in the
+    /// last component if `follow` is set and checking that directories can be searched if `check`
+    /// is set
+    fn lookup(
+        &self,
+        at_fd: c_int,
+        path: &[u8],
+        follow: bool,
+        check: bool,
+    ) -> Result<Lookup, Error> {
+        if path.is_empty() {
+            return Err(Error(uapi::ENOENT));
+        }
+        let start = if path[0] == b'/' || at_fd == uapi::AT_FDCWD {
+            ROOT
+        } else {
+            let inode = self.file(at_fd)?.inode;
+            match self.inodes[inode].data {
+                Data::Directory { .. } => inode,
+                _ => return Err(Error(uapi::ENOTDIR)),
+            }
+        };
+        // A trailing slash means the path must name a directory, through any symbolic link
+        let trailing_slash = path.ends_with(b"/");
+        let found = self.walk(start, path, follow || trailing_slash, check, &mut 0)?;
+        match found.inode {
+            Some(inode) if trailing_slash && !self.is_dir(inode) => Err(Error(uapi::ENOTDIR)),
+            _ => Ok(found),
+        }
+    }
+
+    fn walk(
+        &self,
+        start: usize,
+        path: &[u8],
+        follow: bool,
+        check: bool,
+        links: &mut usize,
+    ) -> Result<Lookup, Error> {
+        let mut dir = if path.first() == Some(&b'/') {
+            ROOT
+        } else {
+            start
+        };
+        let components = path
+            .split(|&b| b == b'/')
+            .filter(|c| !c.is_empty())
+            .collect::<Vec<_>>();
+        let (last, components) = match components.split_last() {
+            Some(split) => split,
+            None => {
+                return Ok(Lookup {
+                    parent: dir,
+                    name: b".".to_vec(),
+                    inode: Some(dir),
+                })
+            }
+        };
+        for name in components {
+            let child = self.child(dir, name, check)?.ok_or(Error(uapi::ENOENT))?;
+            dir = match self.inodes[child].data {
+                Data::Symlink(_) => self
+                    .follow(dir, child, check, links)?
+                    .inode
+                    .ok_or(Error(uapi::ENOENT))?,
+                _ => child,
+            };
+        }
+        let child = self.child(dir, last, check)?;
+        match child {
+            Some(link) if follow && matches!(self.inodes[link].data, Data::Symlink(_)) => {
+                self.follow(dir, link, check, links)
+            }
+            _ => Ok(Lookup {
+                parent: dir,
+                name: last.to_vec(),
+                inode: child,
+            }),
+        }
+    }
+
+    /// Resolve the target of the symbolic link `link`, which is in the directory `dir`
+    fn follow(
+        &self,
+        dir: usize,
+        link: usize,
+        check: bool,
+        links: &mut usize,
+    ) -> Result<Lookup, Error> {
+        *links += 1;
+        if *links > MAX_SYMLINKS {
+            return Err(Error(uapi::ELOOP));
+        }
+        match &self.inodes[link].data {
+            Data::Symlink(target) if !target.is_empty() => {
+                self.walk(dir, target, true, check, links)
+            }
+            _ => Err(Error(uapi::ENOENT)),
+        }
+    }
+
+    /// The entry called `name` in the directory `dir`
+    fn child(&self, dir: usize, name: &[u8], check: bool) -> Result<Option<usize>, Error> {
+        let (parent, entries) = match &self.inodes[dir].data {
+            Data::Directory { parent, entries } => (*parent, entries),
+            _ => return Err(Error(uapi::ENOTDIR)),
+        };
+        if check && !self.permits(dir, uapi::X_OK) {
+            return Err(Error(uapi::EACCES));
+        }
+        Ok(match name {
+            b"." => Some(dir),
+            b".." => Some(parent),
+            _ => entries
+                .iter()
+                .find(|entry| entry.name == name)
+                .map(|entry| entry.inode),
+        })
+    }
+
+    fn is_dir(&self, inode: usize) -> bool {
+        matches!(self.inodes[inode].data, Data::Directory { .. })
+    }
+
+    /// Whether the owner's permission bits allow every access in `access`, a mask of `R_OK`,
+    /// `W_OK`, and `X_OK`
+    fn permits(&self, inode: usize, access: c_int) -> bool {
+        let allowed = (self.inodes[inode].mode >> 6) as c_int & 0o7;
+        allowed & access == access
+    }
+
+    /// Add `inode` to the directory `parent` as `name`
+    fn link(&mut self, parent: usize, name: Vec<u8>, inode: Inode) -> usize {
+        let index = self.inodes.len();
+        self.inodes.push(inode);
+        if let Data::Directory { entries, .. } = &mut self.inodes[parent].data {
+            entries.push(Entry { name, inode: index });
+        }
+        index
+    }
+
+    fn file(&self, fd: c_int) -> Result<&OpenFile, Error> {
+        usize::try_from(fd - FIRST_FD)
+            .ok()
+            .and_then(|i| self.files.get(i)?.as_ref())
+            .ok_or(Error(uapi::EBADF))
+    }
+
+    fn open(
+        &mut self,
+        at_fd: c_int,
+        path: &[u8],
+        flags: OpenFlags,
+        mode: OpenMode,
+    ) -> Result<c_int, Error> {
+        let access = flags.bits() & uapi::O_ACCMODE;
+        let (reads, writes) = (access != uapi::O_WRONLY, access != uapi::O_RDONLY);
+        let exclusive = flags.contains(OpenFlags::CREAT | OpenFlags::EXCL);
+        let follow = !flags.contains(OpenFlags::NOFOLLOW) && !exclusive;
+        let found = self.lookup(at_fd, path, follow, true)?;
+        let inode = match found.inode {
+            Some(_) if exclusive => return Err(Error(uapi::EEXIST)),
+            Some(inode) => {
+                match self.inodes[inode].data {
+                    Data::Symlink(_) => return Err(Error(uapi::ELOOP)),
+                    Data::Directory { .. } if writes => return Err(Error(uapi::EISDIR)),
+                    Data::File(_) if flags.contains(OpenFlags::DIRECTORY) => {
+                        return Err(Error(uapi::ENOTDIR))
+                    }
+                    _ => {}
+                }
+                if (reads && !self.permits(inode, uapi::R_OK))
+                    || (writes && !self.permits(inode, uapi::W_OK))
+                {
+                    return Err(Error(uapi::EACCES));
+                }
+                if let Data::File(contents) = &mut self.inodes[inode].data {
+                    if writes && flags.contains(OpenFlags::TRUNC) {
+                        contents.clear();
+                    }
+                }
+                inode
+            }
+            None if flags.contains(OpenFlags::CREAT) => {
+                if path.ends_with(b"/") {
+                    return Err(Error(uapi::EISDIR));
+                }
+                if !self.permits(found.parent, uapi::W_OK | uapi::X_OK) {
+                    return Err(Error(uapi::EACCES));
+                }
+                let inode = Inode {
+                    mode: uapi::S_IFREG | (mode.bits() & 0o7777),
+                    data: Data::File(Vec::new()),
+                };
+                self.link(found.parent, found.name, inode)
+            }
+            None => return Err(Error(uapi::ENOENT)),
+        };
+
+        let file = OpenFile {
+            inode,
+            flags,
+            offset: 0,
+        };
+        // Like the kernel, use the lowest free descriptor
+        let index = match self.files.iter().position(Option::is_none) {
+            Some(index) => {
+                self.files[index] = Some(file);
+                index
+            }
+            None => {
+                self.files.push(Some(file));
+                self.files.len() - 1
+            }
+        };
+        Ok(index as c_int + FIRST_FD)
+    }
+
+    fn stat(&self, inode: usize) -> uapi::stat {
+        let (nlink, size) = match &self.inodes[inode].data {
+            Data::File(contents) => (1, contents.len()),
+            Data::Directory { entries, .. } => {
+                let subdirs = entries.iter().filter(|e| self.is_dir(e.inode)).count();
+                (2 + subdirs, 4096)
+            }
+            Data::Symlink(target) => (1, target.len()),
+        };
+        // Zeroing covers the padding, which differs between architectures
+        let mut stat: uapi::stat = unsafe { mem::zeroed() };
+        // Inode 0 means a deleted entry to readdir, so numbers start at 1
+        stat.st_ino = (inode + 1) as _;
+        stat.st_nlink = nlink as _;
+        stat.st_mode = self.inodes[inode].mode;
+        stat.st_size = size as _;
+        stat.st_blksize = 4096;
+        stat.st_blocks = size.div_ceil(512) as _;
+        stat
+    }
+}
+
+/// Finds the open file `fd` in `files`, without borrowing the rest of the state
+fn file_mut(files: &mut [Option<OpenFile>], fd: c_int) -> Result<&mut OpenFile, Error> {
+    usize::try_from(fd - FIRST_FD)
+        .ok()
+        .and_then(move |i| files.get_mut(i)?.as_mut())
+        .ok_or(Error(uapi::EBADF))
+}
+
+impl Backend for MemFs {
+    #[inline]
+    fn openat(
+        &self,
+        at_fd: c_int,
+        path: CStr,
+        flags: OpenFlags,
+        mode: OpenMode,
+    ) -> Result<c_int, Error> {
+        self.state.lock().open(at_fd, path.as_bytes(), flags, mode)
+    }
+
+    #[inline]
+    fn close(&self, fd: c_int) -> Result<(), Error> {
+        let mut state = self.state.lock();
+        file_mut(&mut state.files, fd)?;
+        state.files[(fd - FIRST_FD) as usize] = None;
+        Ok(())
+    }
+
+    #[inline]
+    fn read(&self, fd: c_int, bytes: &mut [u8]) -> Result<usize, Error> {
+        let state = &mut *self.state.lock();
+        let file = file_mut(&mut state.files, fd)?;
+        if file.flags.bits() & uapi::O_ACCMODE == uapi::O_WRONLY {
+            return Err(Error(uapi::EBADF));
+        }
+        match &state.inodes[file.inode].data {
+            Data::File(contents) => {
+                let rest = contents.get(file.offset..).unwrap_or_default();
+                let n = rest.len().min(bytes.len());
+                bytes[..n].copy_from_slice(&rest[..n]);
+                file.offset += n;
+                Ok(n)
+            }
+            Data::Directory { .. } => Err(Error(uapi::EISDIR)),
+            Data::Symlink(_) => Err(Error(uapi::EBADF)),
+        }
+    }
+
+    #[inline]
+    fn write(&self, fd: c_int, bytes: &[u8]) -> Result<usize, Error> {
+        let state = &mut *self.state.lock();
+        let file = file_mut(&mut state.files, fd)?;
+        if file.flags.bits() & uapi::O_ACCMODE == uapi::O_RDONLY {
+            return Err(Error(uapi::EBADF));
+        }
+        match &mut state.inodes[file.inode].data {
+            Data::File(contents) => {
+                if file.flags.contains(OpenFlags::APPEND) {
+                    file.offset = contents.len();
+                }
+                let end = file.offset + bytes.len();
+                if contents.len() < end {
+                    contents.resize(end, 0);
+                }
+                contents[file.offset..end].copy_from_slice(bytes);
+                file.offset = end;
+                Ok(bytes.len())
+            }
+            _ => Err(Error(uapi::EBADF)),
+        }
+    }
+
+    #[inline]
+    fn fstatat(&self, fd: c_int, path: CStr) -> Result<uapi::stat, Error> {
+        let state = self.state.lock();
+        let found = state.lookup(fd, path.as_bytes(), true, true)?;
+        Ok(state.stat(found.inode.ok_or(Error(uapi::ENOENT))?))
+    }
+
+    #[inline]
+    fn lstatat(&self, fd: c_int, path: CStr) -> Result<uapi::stat, Error> {
+        let state = self.state.lock();
+        let found = state.lookup(fd, path.as_bytes(), false, true)?;
+        Ok(state.stat(found.inode.ok_or(Error(uapi::ENOENT))?))
+    }
+
+    #[inline]
+    fn getdents64(&self, fd: c_int, buf: &mut [u8]) -> Result<usize, Error> {
+        let state = &mut *self.state.lock();
+        let file = file_mut(&mut state.files, fd)?;
+        let (parent, entries) = match &state.inodes[file.inode].data {
+            Data::Directory { parent, entries } => (*parent, entries),
+            _ => return Err(Error(uapi::ENOTDIR)),
+        };
+        let name_offset = mem::offset_of!(uapi::linux_dirent64, d_name);
+        let mut used = 0;
+        loop {
+            let (name, inode) = match file.offset {
+                0 => (&b"."[..], file.inode),
+                1 => (&b".."[..], parent),
+                n => match entries.get(n - 2) {
+                    Some(entry) => (&entry.name[..], entry.inode),
+                    None => break,
+                },
+            };
+            // Records are padded to keep the next one aligned, and the name is null-terminated
+            let reclen = (name_offset + name.len() + 1 + 7) & !7;
+            if buf.len() - used < reclen {
+                if used == 0 {
+                    return Err(Error(uapi::EINVAL));
+                }
+                break;
+            }
+            let d_type = match state.inodes[inode].data {
+                Data::File(_) => uapi::DT_REG,
+                Data::Directory { .. } => uapi::DT_DIR,
+                Data::Symlink(_) => uapi::DT_LNK,
+            };
+            let record = &mut buf[used..used + reclen];
+            record.fill(0);
+            record[..8].copy_from_slice(&(inode as u64 + 1).to_ne_bytes());
+            record[8..16].copy_from_slice(&(file.offset as i64 + 1).to_ne_bytes());
+            record[16..18].copy_from_slice(&(reclen as u16).to_ne_bytes());
+            record[18] = d_type;
+            record[name_offset..name_offset + name.len()].copy_from_slice(name);
+            used += reclen;
+            file.offset += 1;
+        }
+        Ok(used)
+    }
+
+    #[inline]
+    fn readlinkat<'a>(&self, fd: c_int, path: CStr, buf: &'a mut [u8]) -> Result<&'a [u8], Error> {
+        let state = self.state.lock();
+        let found = state.lookup(fd, path.as_bytes(), false, true)?;
+        match &state.inodes[found.inode.ok_or(Error(uapi::ENOENT))?].data {
+            Data::Symlink(target) => {
+                let n = target.len().min(buf.len());
+                buf[..n].copy_from_slice(&target[..n]);
+                Ok(&buf[..n])
+            }
+            _ => Err(Error(uapi::EINVAL)),
+        }
+    }
+
+    #[inline]
+    fn faccessat(&self, fd: c_int, path: CStr, mode: c_int) -> Result<(), Error> {
+        if mode & !(uapi::R_OK | uapi::W_OK | uapi::X_OK) != 0 {
+            return Err(Error(uapi::EINVAL));
+        }
+        let state = self.state.lock();
+        let found = state.lookup(fd, path.as_bytes(), true, true)?;
+        let inode = found.inode.ok_or(Error(uapi::ENOENT))?;
+        if state.permits(inode, mode) {
+            Ok(())
+        } else {
+            Err(Error(uapi::EACCES))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const READ: OpenFlags = OpenFlags::RDONLY;
+
+    fn open(fs: &MemFs, path: &[u8], flags: OpenFlags) -> Result<c_int, Error> {
+        fs.openat(
+            uapi::AT_FDCWD,
+            CStr::from_bytes(path),
+            flags,
+            OpenMode::RUSR | OpenMode::WUSR,
+        )
+    }
+
+    fn errno<T>(result: Result<T, Error>) -> c_int {
+        result.map(|_| ()).unwrap_err().0
+    }
+
+    /// The names and types in the records `getdents64` wrote
+    fn names(mut records: &[u8]) -> Vec<(Vec<u8>, u8)> {
+        let mut names = Vec::new();
+        while !records.is_empty() {
+            let reclen = u16::from_ne_bytes([records[16], records[17]]) as usize;
+            let name = &records[19..reclen];
+            let end = name.iter().position(|&b| b == 0).unwrap();
+            names.push((name[..end].to_vec(), records[18]));
+            records = &records[reclen..];
+        }
+        names
+    }
+
+    #[test]
+    fn files() {
+        let fs = MemFs::new();
+        let fd = open(&fs, b"/notes\0", OpenFlags::WRONLY | OpenFlags::CREAT).unwrap();
+        assert_eq!(fd, 3);
+        assert_eq!(fs.write(fd, b"hello").unwrap(), 5);
+        assert_eq!(errno(fs.read(fd, &mut [0; 4])), uapi::EBADF);
+        fs.close(fd).unwrap();
+        assert_eq!(errno(fs.close(fd)), uapi::EBADF);
+
+        let fd = open(&fs, b"notes\0", OpenFlags::WRONLY | OpenFlags::APPEND).unwrap();
+        fs.write(fd, b", world").unwrap();
+        let stat = fs
+            .fstatat(uapi::AT_FDCWD, CStr::from_bytes(b"/notes\0"))
+            .unwrap();
+        assert_eq!(stat.st_size, 12);
+        assert_eq!(stat.st_mode, uapi::S_IFREG | 0o600);
+
+        // The lowest free descriptor is reused
+        let other = open(&fs, b"/notes\0", READ).unwrap();
+        fs.close(fd).unwrap();
+        assert_eq!(open(&fs, b"/notes\0", READ).unwrap(), fd);
+        let mut buf = [0; 8];
+        assert_eq!(fs.read(other, &mut buf).unwrap(), 8);
+        assert_eq!(fs.read(other, &mut buf).unwrap(), 4);
+        assert_eq!(&buf[..4], b"orld");
+        assert_eq!(fs.read(other, &mut buf).unwrap(), 0);
+
+        let fd = open(&fs, b"/notes\0", OpenFlags::RDWR | OpenFlags::TRUNC).unwrap();
+        assert_eq!(fs.read(fd, &mut buf).unwrap(), 0);
+
+        let exclusive = OpenFlags::WRONLY | OpenFlags::CREAT | OpenFlags::EXCL;
+        assert_eq!(errno(open(&fs, b"/notes\0", exclusive)), uapi::EEXIST);
+        assert_eq!(errno(open(&fs, b"/missing\0", READ)), uapi::ENOENT);
+        assert_eq!(errno(open(&fs, b"/notes/\0", READ)), uapi::ENOTDIR);
+        assert_eq!(errno(open(&fs, b"/notes/x\0", READ)), uapi::ENOTDIR);
+    }
+
+    #[test]
+    fn directories() {
+        let fs = MemFs::new();
+        fs.create_dir(b"/dir", 0o755).unwrap();
+        fs.create_file(b"/dir/b", b"", 0o644).unwrap();
+        fs.create_dir(b"/dir/sub", 0o755).unwrap();
+        fs.create_file(b"/dir/a", b"", 0o644).unwrap();
+        fs.symlink(b"a", b"/dir/link").unwrap();
+        fs.remove(b"/dir/b").unwrap();
+        assert_eq!(errno(fs.create_dir(b"/dir/sub", 0o755)), uapi::EEXIST);
+        assert_eq!(errno(fs.remove(b"/dir")), uapi::ENOTEMPTY);
+
+        let fd = open(&fs, b"/dir\0", READ | OpenFlags::DIRECTORY).unwrap();
+        // Too small for even one record
+        assert_eq!(errno(fs.getdents64(fd, &mut [0; 16])), uapi::EINVAL);
+        // Room for two records at a time, so listing takes several calls
+        let mut listed = Vec::new();
+        let mut buf = [0; 48];
+        loop {
+            let n = fs.getdents64(fd, &mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            listed.extend(names(&buf[..n]));
+        }
+        let expected = [
+            (&b"."[..], uapi::DT_DIR),
+            (b"..", uapi::DT_DIR),
+            (b"sub", uapi::DT_DIR),
+            (b"a", uapi::DT_REG),
+            (b"link", uapi::DT_LNK),
+        ];
+        assert_eq!(listed.len(), expected.len());
+        for ((name, d_type), (expected_name, expected_type)) in listed.iter().zip(expected) {
+            assert_eq!((&name[..], *d_type), (expected_name, expected_type));
+        }
+
+        assert_eq!(errno(fs.read(fd, &mut buf)), uapi::EISDIR);
+        assert_eq!(errno(open(&fs, b"/dir\0", OpenFlags::RDWR)), uapi::EISDIR);
+        assert_eq!(
+            errno(open(&fs, b"/dir/a\0", READ | OpenFlags::DIRECTORY)),
+            uapi::ENOTDIR
+        );
+
+        // Relative paths start at the directory the descriptor is open on
+        let stat = fs.fstatat(fd, CStr::from_bytes(b"sub/../a\0")).unwrap();
+        assert_eq!(stat.st_mode & uapi::S_IFMT, uapi::S_IFREG);
+        let stat = fs.fstatat(fd, CStr::from_bytes(b".\0")).unwrap();
+        assert_eq!(stat.st_nlink, 3);
+        let stat = fs.fstatat(fd, CStr::from_bytes(b"/..\0")).unwrap();
+        assert_eq!(stat.st_ino, 1);
+    }
+
+    #[test]
+    fn symlinks() {
+        let fs = MemFs::new();
+        fs.create_dir(b"/etc", 0o755).unwrap();
+        fs.create_file(b"/etc/real", b"contents", 0o644).unwrap();
+        fs.symlink(b"real", b"/etc/relative").unwrap();
+        fs.symlink(b"/etc", b"/dir").unwrap();
+        fs.symlink(b"/loop", b"/loop").unwrap();
+        fs.symlink(b"/nowhere", b"/dangling").unwrap();
+
+        let fd = open(&fs, b"/dir/relative\0", READ).unwrap();
+        let mut buf = [0; 16];
+        assert_eq!(fs.read(fd, &mut buf).unwrap(), 8);
+
+        let stat = fs
+            .lstatat(uapi::AT_FDCWD, CStr::from_bytes(b"/dir/relative\0"))
+            .unwrap();
+        assert_eq!(stat.st_mode, uapi::S_IFLNK | 0o777);
+        assert_eq!(stat.st_size, 4);
+        let target = fs
+            .readlinkat(uapi::AT_FDCWD, CStr::from_bytes(b"/dir\0"), &mut buf)
+            .unwrap();
+        assert_eq!(target, b"/etc");
+        assert_eq!(
+            errno(fs.readlinkat(uapi::AT_FDCWD, CStr::from_bytes(b"/etc\0"), &mut buf)),
+            uapi::EINVAL
+        );
+
+        let nofollow = READ | OpenFlags::NOFOLLOW;
+        assert_eq!(errno(open(&fs, b"/etc/relative\0", nofollow)), uapi::ELOOP);
+        assert_eq!(errno(open(&fs, b"/loop\0", READ)), uapi::ELOOP);
+        assert_eq!(errno(open(&fs, b"/dangling\0", READ)), uapi::ENOENT);
+        // Creating through a dangling link creates its target
+        open(&fs, b"/dangling\0", OpenFlags::WRONLY | OpenFlags::CREAT).unwrap();
+        fs.fstatat(uapi::AT_FDCWD, CStr::from_bytes(b"/nowhere\0"))
+            .unwrap();
+    }
+
+    #[test]
+    fn permissions() {
+        let fs = MemFs::new();
+        fs.create_dir(b"/private", 0o600).unwrap();
+        fs.create_file(b"/private/file", b"", 0o644).unwrap();
+        fs.create_file(b"/write-only", b"", 0o200).unwrap();
+
+        let access = |path: &[u8], mode| fs.faccessat(uapi::AT_FDCWD, CStr::from_bytes(path), mode);
+        assert_eq!(errno(access(b"/private/file\0", uapi::F_OK)), uapi::EACCES);
+        assert_eq!(errno(access(b"/write-only\0", uapi::R_OK)), uapi::EACCES);
+        access(b"/write-only\0", uapi::W_OK).unwrap();
+        assert_eq!(errno(access(b"/write-only\0", 8)), uapi::EINVAL);
+
+        assert_eq!(errno(open(&fs, b"/write-only\0", READ)), uapi::EACCES);
+        open(&fs, b"/write-only\0", OpenFlags::WRONLY).unwrap();
+        assert_eq!(
+            errno(open(
+                &fs,
+                b"/private/new\0",
+                OpenFlags::WRONLY | OpenFlags::CREAT
+            )),
+            uapi::EACCES
+        );
+
+        fs.set_permissions(b"/private", 0o700).unwrap();
+        access(b"/private/file\0", uapi::R_OK | uapi::W_OK).unwrap();
+        open(&fs, b"/private/new\0", OpenFlags::WRONLY | OpenFlags::CREAT).unwrap();
+    }
+}
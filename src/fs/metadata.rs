@@ -0,0 +1,128 @@
+use super::backend;
+use crate::{
+    uapi::{self, c_int},
+    CStr, Error,
+};
+
+/// Information about a file, from `statx` where the kernel has it and `newfstatat` otherwise
+#[derive(Clone, Copy, Debug)]
+pub struct Metadata {
+    stx: uapi::statx,
+}
+
+impl Metadata {
+    #[inline]
+    pub fn size(&self) -> u64 {
+        self.stx.stx_size
+    }
+
+    /// The file type and permission bits, as in `st_mode`
+    #[inline]
+    pub fn mode(&self) -> uapi::mode_t {
+        uapi::mode_t::from(self.stx.stx_mode)
+    }
+
+    #[inline]
+    pub fn is_dir(&self) -> bool {
+        self.mode() & uapi::S_IFMT == uapi::S_IFDIR
+    }
+
+    #[inline]
+    pub fn is_file(&self) -> bool {
+        self.mode() & uapi::S_IFMT == uapi::S_IFREG
+    }
+
+    #[inline]
+    pub fn is_symlink(&self) -> bool {
+        self.mode() & uapi::S_IFMT == uapi::S_IFLNK
+    }
+
+    #[inline]
+    pub fn inode(&self) -> u64 {
+        self.stx.stx_ino
+    }
+
+    #[inline]
+    pub fn modified(&self) -> uapi::statx_timestamp {
+        self.stx.stx_mtime
+    }
+
+    /// When the file was created, if the kernel and filesystem record it
+    #[inline]
+    pub fn created(&self) -> Option<uapi::statx_timestamp> {
+        (self.stx.stx_mask & uapi::STATX_BTIME != 0).then_some(self.stx.stx_btime)
+    }
+
+    /// Everything that is known, where `stx_mask` says which fields are filled in
+    #[inline]
+    pub fn as_raw(&self) -> &uapi::statx {
+        &self.stx
+    }
+}
+
+/// Get information about the file at `path`, following symbolic links
+#[inline]
+pub fn metadata(path: &[u8]) -> Result<Metadata, Error> {
+    stat(path, 0)
+}
+
+/// Get information about the file at `path`, or about the symbolic link if it is one
+#[inline]
+pub fn symlink_metadata(path: &[u8]) -> Result<Metadata, Error> {
+    stat(path, uapi::AT_SYMLINK_NOFOLLOW)
+}
+
+fn stat(path: &[u8], flags: c_int) -> Result<Metadata, Error> {
+    let backend = backend::backend();
+    let path = CStr::from_bytes(path);
+    let mask = uapi::STATX_BASIC_STATS | uapi::STATX_BTIME;
+    match backend.statx(uapi::AT_FDCWD, path, flags, mask) {
+        Err(Error(uapi::ENOSYS)) => {}
+        result => return result.map(|stx| Metadata { stx }),
+    }
+    let stat = if flags & uapi::AT_SYMLINK_NOFOLLOW != 0 {
+        backend.lstatat(uapi::AT_FDCWD, path)?
+    } else {
+        backend.fstatat(uapi::AT_FDCWD, path)?
+    };
+    Ok(Metadata {
+        stx: from_stat(&stat),
+    })
+}
+
+/// The `statx` that holds the same information as `stat`, which has no birth time
+fn from_stat(stat: &uapi::stat) -> uapi::statx {
+    let timestamp = |sec, nsec| uapi::statx_timestamp {
+        tv_sec: sec as i64,
+        tv_nsec: nsec as _,
+        __reserved: 0,
+    };
+    // How glibc's major() and minor() split a dev_t
+    let device = |dev: u64| {
+        (
+            (((dev >> 8) & 0xfff) | ((dev >> 32) & !0xfff)) as u32,
+            ((dev & 0xff) | ((dev >> 12) & !0xff)) as u32,
+        )
+    };
+    let (dev_major, dev_minor) = device(stat.st_dev as _);
+    let (rdev_major, rdev_minor) = device(stat.st_rdev as _);
+    uapi::statx {
+        stx_mask: uapi::STATX_BASIC_STATS,
+        stx_blksize: stat.st_blksize as _,
+        stx_nlink: stat.st_nlink as _,
+        stx_uid: stat.st_uid,
+        stx_gid: stat.st_gid,
+        stx_mode: stat.st_mode as u16,
+        stx_ino: stat.st_ino as _,
+        stx_size: stat.st_size as u64,
+        stx_blocks: stat.st_blocks as _,
+        stx_atime: timestamp(stat.st_atime, stat.st_atime_nsec),
+        stx_ctime: timestamp(stat.st_ctime, stat.st_ctime_nsec),
+        stx_mtime: timestamp(stat.st_mtime, stat.st_mtime_nsec),
+        stx_rdev_major: rdev_major,
+        stx_rdev_minor: rdev_minor,
+        stx_dev_major: dev_major,
+        stx_dev_minor: dev_minor,
+        ..Default::default()
+    }
+}
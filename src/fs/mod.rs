@@ -1,69 +1,122 @@
 use crate::{
+    fd::{AsRawFd, FromRawFd, IntoRawFd, RawFd},
     io::{Read, Write},
-    syscalls,
     syscalls::{OpenFlags, OpenMode},
-    CStr, Error,
+    uapi, CStr, Error,
 };
 use alloc::{vec, vec::Vec};
-use libc::c_int;
+use core::mem;
 
+pub mod backend;
 mod directory;
+mod memfs;
+mod metadata;
+pub use backend::{set_backend, Backend};
 pub use directory::*;
+pub use memfs::MemFs;
+pub use metadata::*;
 
-pub struct File(c_int);
+/// A file open through the [`Backend`] that was selected when it was opened
+///
+/// Its descriptor is only one the kernel knows about when that backend is
+/// [`Kernel`](backend::Kernel), so this implements [`AsRawFd`] but not [`AsFd`](crate::fd::AsFd).
+pub struct File {
+    fd: RawFd,
+    backend: &'static dyn Backend,
+}
 
 impl File {
     #[inline]
     pub fn open(path: &[u8]) -> Result<Self, Error> {
-        Ok(Self(syscalls::openat(
-            libc::AT_FDCWD,
+        let backend = backend::backend();
+        let fd = backend.openat(
+            uapi::AT_FDCWD,
             CStr::from_bytes(path),
             OpenFlags::RDONLY | OpenFlags::CLOEXEC,
             OpenMode::empty(),
-        )?))
+        )?;
+        Ok(Self { fd, backend })
     }
 
     #[inline]
     pub fn create(path: &[u8]) -> Result<Self, Error> {
-        Ok(Self(syscalls::openat(
-            libc::AT_FDCWD,
-            CStr::from_bytes(path),
+        Self::create_with(
+            path,
             OpenFlags::RDWR | OpenFlags::CREAT | OpenFlags::CLOEXEC,
+        )
+    }
+
+    fn create_with(path: &[u8], flags: OpenFlags) -> Result<Self, Error> {
+        let backend = backend::backend();
+        let fd = backend.openat(
+            uapi::AT_FDCWD,
+            CStr::from_bytes(path),
+            flags,
             OpenMode::RUSR
                 | OpenMode::WUSR
                 | OpenMode::RGRP
                 | OpenMode::WGRP
                 | OpenMode::ROTH
                 | OpenMode::WOTH,
-        )?))
+        )?;
+        Ok(Self { fd, backend })
     }
 }
 
 impl Drop for File {
     #[inline]
     fn drop(&mut self) {
-        let _ = syscalls::close(self.0);
+        let _ = self.backend.close(self.fd);
+    }
+}
+
+impl AsRawFd for File {
+    #[inline]
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl IntoRawFd for File {
+    #[inline]
+    fn into_raw_fd(self) -> RawFd {
+        let fd = self.fd;
+        mem::forget(self);
+        fd
+    }
+}
+
+impl FromRawFd for File {
+    /// Take ownership of a kernel descriptor, which is then read and written with system calls
+    /// whatever the backend is
+    #[inline]
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        Self {
+            fd,
+            backend: &backend::Kernel,
+        }
     }
 }
 
 impl Read for File {
     #[inline]
     fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
-        syscalls::read(self.0, buf)
+        self.backend.read(self.fd, buf)
     }
 }
 
 impl Write for File {
     #[inline]
     fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
-        syscalls::write(self.0, buf)
+        self.backend.write(self.fd, buf)
     }
 }
 
 #[inline]
 pub fn read(path: &[u8]) -> Result<Vec<u8>, Error> {
-    let file_len =
-        syscalls::fstatat(libc::AT_FDCWD, CStr::from_bytes(path)).map(|stat| stat.st_size)?;
+    let file_len = backend::backend()
+        .fstatat(uapi::AT_FDCWD, CStr::from_bytes(path))
+        .map(|stat| stat.st_size)?;
     let mut file = File::open(path)?;
     let mut bytes = vec![0; file_len as usize];
     let mut buf = &mut bytes[..];
@@ -71,13 +124,50 @@ pub fn read(path: &[u8]) -> Result<Vec<u8>, Error> {
         match file.read(buf) {
             Ok(0) => break,
             Ok(n) => buf = &mut buf[n..],
-            Err(Error(libc::EAGAIN)) => {}
+            Err(Error(uapi::EAGAIN | uapi::EINTR)) => {}
             Err(e) => return Err(e),
         }
     }
     Ok(bytes)
 }
 
+/// Copy the contents of the file at `from` to the file at `to`, which is created or truncated,
+/// returning the number of bytes copied
+///
+/// The kernel copies the data itself with `copy_file_range` where it can.
+#[inline]
+pub fn copy(from: &[u8], to: &[u8]) -> Result<u64, Error> {
+    let mut input = File::open(from)?;
+    let mut output = File::create_with(
+        to,
+        OpenFlags::WRONLY | OpenFlags::CREAT | OpenFlags::TRUNC | OpenFlags::CLOEXEC,
+    )?;
+    let mut copied = 0;
+    loop {
+        match input.backend.copy_file_range(input.fd, output.fd, 1 << 30) {
+            Ok(0) => return Ok(copied),
+            Ok(n) => copied += n as u64,
+            Err(Error(uapi::EINTR)) => {}
+            // Before Linux 5.3 it cannot copy between filesystems
+            Err(Error(uapi::ENOSYS | uapi::EXDEV)) => break,
+            Err(e) => return Err(e),
+        }
+    }
+    // Both offsets have moved past anything copied so far
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        match input.read(&mut buf) {
+            Ok(0) => return Ok(copied),
+            Ok(n) => {
+                output.write_all(&buf[..n])?;
+                copied += n as u64;
+            }
+            Err(Error(uapi::EAGAIN | uapi::EINTR)) => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -98,4 +188,35 @@ mod tests {
         let contents = read(b"/tmp/test.foo\0").unwrap();
         assert_eq!(contents, expected_contents);
     }
+
+    #[test]
+    fn copies_and_metadata() {
+        let contents = b"copied contents\n".repeat(1000);
+        File::create(b"/tmp/test-copy-from\0")
+            .unwrap()
+            .write_all(&contents)
+            .unwrap();
+        // Copying over a longer file truncates it
+        File::create(b"/tmp/test-copy-to\0")
+            .unwrap()
+            .write_all(&[b'x'; 20000])
+            .unwrap();
+        let copied = copy(b"/tmp/test-copy-from\0", b"/tmp/test-copy-to\0").unwrap();
+        assert_eq!(copied, contents.len() as u64);
+        assert_eq!(read(b"/tmp/test-copy-to\0").unwrap(), contents);
+
+        let metadata = metadata(b"/tmp/test-copy-to\0").unwrap();
+        assert!(metadata.is_file());
+        assert_eq!(metadata.size(), contents.len() as u64);
+        let expected = std::fs::metadata("/tmp/test-copy-to").unwrap();
+        assert_eq!(
+            metadata.modified().tv_sec,
+            std::os::unix::fs::MetadataExt::mtime(&expected)
+        );
+        assert!(super::metadata(b"/tmp\0").unwrap().is_dir());
+        assert_eq!(
+            super::metadata(b"/nonexistent\0").unwrap_err(),
+            uapi::ENOENT
+        );
+    }
 }
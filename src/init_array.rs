@@ -0,0 +1,59 @@
+//! Static constructors and destructors, which the linker collects into the `.preinit_array`,
+//! `.init_array`, and `.fini_array` sections from C objects, instrumentation runtimes, and crates
+//! that register functions with `#[link_section = ".init_array"]`.
+
+use crate::uapi::c_int;
+use core::sync::atomic::{AtomicBool, Ordering::SeqCst};
+
+type Constructor = unsafe extern "C" fn(c_int, *const *const u8, *const *const u8);
+type Destructor = unsafe extern "C" fn();
+
+// The linker defines these around each section, even when it is empty
+extern "C" {
+    static __preinit_array_start: [Constructor; 0];
+    static __preinit_array_end: [Constructor; 0];
+    static __init_array_start: [Constructor; 0];
+    static __init_array_end: [Constructor; 0];
+    static __fini_array_start: [Destructor; 0];
+    static __fini_array_end: [Destructor; 0];
+}
+
+static FINISHED: AtomicBool = AtomicBool::new(false);
+
+/// Run the constructors in order, passing them argc, argv, and envp like glibc does
+///
+/// `_start` calls this once the runtime is initialized and just before `__veneer_main`.
+#[no_mangle]
+unsafe extern "C" fn __veneer_run_init_array() {
+    let argc = crate::env::ARGC.load(SeqCst) as c_int;
+    let argv = crate::env::ARGV.load(SeqCst);
+    let envp = crate::env::ENVP.load(SeqCst);
+    for (start, end) in [
+        (&__preinit_array_start, &__preinit_array_end),
+        (&__init_array_start, &__init_array_end),
+    ] {
+        let mut constructor = start.as_ptr();
+        while constructor < end.as_ptr() {
+            (*constructor)(argc, argv, envp);
+            constructor = constructor.add(1);
+        }
+    }
+}
+
+/// Run the destructors in the reverse of their order in `.fini_array`
+///
+/// Only the first call does anything, so a destructor which calls [`exit`](crate::process::exit)
+/// does not start them over.
+pub(crate) fn run_fini_array() {
+    if FINISHED.swap(true, SeqCst) {
+        return;
+    }
+    unsafe {
+        let start = __fini_array_start.as_ptr();
+        let mut destructor = __fini_array_end.as_ptr();
+        while destructor > start {
+            destructor = destructor.sub(1);
+            (*destructor)();
+        }
+    }
+}
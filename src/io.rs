@@ -1,4 +1,4 @@
-use crate::Error;
+use crate::{spinlock::SpinLock, uapi, Error};
 
 pub type Result<T> = core::result::Result<T, Error>;
 
@@ -9,15 +9,21 @@ pub trait Read {
 pub trait Write {
     fn write(&mut self, buf: &[u8]) -> Result<usize>;
 
+    /// Write out anything this writer has buffered
+    #[inline]
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+
     #[inline]
     fn write_all(&mut self, mut buf: &[u8]) -> Result<()> {
         while !buf.is_empty() {
             match self.write(buf) {
                 Ok(0) => {
-                    return Err(Error(libc::EBADF));
+                    return Err(Error(uapi::EBADF));
                 }
                 Ok(n) => buf = buf.get(n..).unwrap_or_default(),
-                Err(Error(libc::EAGAIN)) => {}
+                Err(Error(uapi::EAGAIN | uapi::EINTR)) => {}
                 Err(e) => return Err(e),
             }
         }
@@ -30,7 +36,7 @@ pub struct Stdout;
 impl Write for Stdout {
     #[inline]
     fn write(&mut self, buf: &[u8]) -> Result<usize> {
-        crate::syscalls::write(libc::STDOUT_FILENO, buf)
+        crate::syscalls::write(crate::fd::STDOUT, buf)
     }
 }
 
@@ -49,7 +55,7 @@ pub struct Stderr;
 impl Write for Stderr {
     #[inline]
     fn write(&mut self, buf: &[u8]) -> Result<usize> {
-        crate::syscalls::write(libc::STDERR_FILENO, buf)
+        crate::syscalls::write(crate::fd::STDERR, buf)
     }
 }
 
@@ -62,3 +68,145 @@ impl core::fmt::Write for Stderr {
         }
     }
 }
+
+/// Collects small writes into a fixed-size buffer and passes them to `W` in larger chunks
+///
+/// Anything still buffered when this is dropped is written out, ignoring errors.
+pub struct BufWriter<W: Write> {
+    inner: W,
+    buf: [u8; 4096],
+    len: usize,
+}
+
+impl<W: Write> BufWriter<W> {
+    #[inline]
+    pub const fn new(inner: W) -> Self {
+        Self {
+            inner,
+            buf: [0; 4096],
+            len: 0,
+        }
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    fn flush_buf(&mut self) -> Result<()> {
+        let result = self.inner.write_all(&self.buf[..self.len]);
+        self.len = 0;
+        result
+    }
+}
+
+impl<W: Write> Write for BufWriter<W> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        if self.len + buf.len() > self.buf.len() {
+            self.flush_buf()?;
+        }
+        if buf.len() >= self.buf.len() {
+            self.inner.write(buf)
+        } else {
+            self.buf[self.len..self.len + buf.len()].copy_from_slice(buf);
+            self.len += buf.len();
+            Ok(buf.len())
+        }
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<()> {
+        self.flush_buf()?;
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Drop for BufWriter<W> {
+    #[inline]
+    fn drop(&mut self) {
+        let _ = self.flush_buf();
+    }
+}
+
+static BUFFERED_STDOUT: SpinLock<BufWriter<Stdout>> = SpinLock::new(BufWriter::new(Stdout));
+
+/// A handle to a process-wide buffer in front of stdout
+///
+/// Output written here is only guaranteed to appear once it is flushed, either explicitly or by
+/// [`process::exit`](crate::process::exit). Output written to [`Stdout`] bypasses the buffer, so
+/// mixing the two can reorder output.
+pub struct BufferedStdout;
+
+impl Write for BufferedStdout {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        BUFFERED_STDOUT.lock().write(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<()> {
+        BUFFERED_STDOUT.lock().flush()
+    }
+}
+
+impl core::fmt::Write for BufferedStdout {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        match self.write_all(s.as_bytes()) {
+            Ok(_) => Ok(()),
+            Err(_) => panic!("Unable to write to stdout"),
+        }
+    }
+}
+
+/// Flush the buffered standard streams, as part of exiting the process
+///
+/// If the process is exiting because of a panic while a stream was locked, that stream is skipped
+/// instead of deadlocking.
+pub(crate) fn flush_std_streams() {
+    if let Some(mut stdout) = BUFFERED_STDOUT.try_lock() {
+        let _ = stdout.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    struct Recorder(Vec<Vec<u8>>);
+
+    impl Write for Recorder {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            self.0.push(buf.to_vec());
+            Ok(buf.len())
+        }
+    }
+
+    #[test]
+    fn buf_writer() {
+        let mut writer = BufWriter::new(Recorder(Vec::new()));
+        writer.write_all(b"hello ").unwrap();
+        writer.write_all(b"world").unwrap();
+        assert!(writer.get_ref().0.is_empty());
+        assert_eq!(writer.buffer(), b"hello world");
+
+        writer.flush().unwrap();
+        assert_eq!(writer.get_ref().0, [b"hello world".to_vec()]);
+        assert!(writer.buffer().is_empty());
+
+        // Writes that do not fit in the buffer go straight through
+        let big = [b'x'; 5000];
+        writer.write_all(b"a").unwrap();
+        writer.write_all(&big).unwrap();
+        assert_eq!(writer.get_ref().0.len(), 3);
+        assert_eq!(writer.get_ref().0[1], b"a");
+        assert_eq!(writer.get_ref().0[2].len(), 5000);
+    }
+}
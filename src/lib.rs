@@ -1,8 +1,9 @@
 #![cfg_attr(not(test), no_std)]
 #![feature(naked_functions, alloc_error_handler, lang_items)]
 #![warn(clippy::missing_inline_in_public_items)]
-#![feature(cfg_target_has_atomic, core_intrinsics, linkage)]
+#![feature(cfg_target_has_atomic, core_intrinsics, linkage, never_type)]
 #![allow(internal_features)] // Must use lang_items to implement a Rust runtime
+#![cfg_attr(feature = "c-abi", feature(c_variadic, thread_local))]
 
 #[cfg(not(target_os = "linux"))]
 core::compile_error!(
@@ -14,23 +15,35 @@ core::compile_error!(
     open an issue on https://github.com/saethlin/veneer."
 );
 
-#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
-compile_error!("This crate is only implemented for x86_64 and aarch64");
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64", target_arch = "x86")))]
+compile_error!("This crate is only implemented for x86_64, aarch64, and i686");
 
 extern crate alloc;
 
 #[cfg(target_os = "linux")]
 mod allocator;
+#[cfg(all(target_os = "linux", feature = "backtrace"))]
+pub mod backtrace;
+#[cfg(all(target_os = "linux", feature = "c-abi"))]
+pub mod c_abi;
 #[cfg(target_os = "linux")]
 mod cstr;
 #[cfg(target_os = "linux")]
+mod elf;
+#[cfg(target_os = "linux")]
 pub mod env;
 #[cfg(target_os = "linux")]
 mod error;
+#[cfg(all(target_os = "linux", feature = "rt-signals", not(test)))]
+mod fatal_signal;
+#[cfg(target_os = "linux")]
+pub mod fd;
 #[cfg(target_os = "linux")]
 pub mod fmt;
 #[cfg(target_os = "linux")]
 pub mod fs;
+#[cfg(all(target_os = "linux", feature = "rt-start", not(test)))]
+mod init_array;
 #[cfg(target_os = "linux")]
 pub mod io;
 #[cfg(target_os = "linux")]
@@ -38,80 +51,226 @@ mod mem;
 #[cfg(target_os = "linux")]
 pub mod net;
 #[cfg(target_os = "linux")]
+pub mod panic;
+#[cfg(target_os = "linux")]
 pub mod prelude;
 #[cfg(target_os = "linux")]
+pub mod process;
+#[cfg(all(target_os = "linux", feature = "rt-start", not(test)))]
+mod relocate;
+#[cfg(target_os = "linux")]
 mod spinlock;
+#[cfg(all(target_os = "linux", feature = "rt-start", not(test)))]
+mod stack_protector;
 #[cfg(target_os = "linux")]
 pub mod syscalls;
+#[cfg(target_os = "linux")]
+pub mod testing;
+#[cfg(target_os = "linux")]
+pub mod tls;
+#[cfg(target_os = "linux")]
+pub mod uapi;
+#[cfg(target_os = "linux")]
+mod vdso;
 
 #[cfg(target_os = "linux")]
 pub use allocator::Allocator;
 #[cfg(target_os = "linux")]
 pub use cstr::CStr;
 #[cfg(target_os = "linux")]
-pub use error::Error;
+pub use error::{Error, ErrorKind};
 #[cfg(target_os = "linux")]
-pub use veneer_macros::main;
+pub use testing::test_main;
+#[cfg(target_os = "linux")]
+pub use uapi::Errno;
+#[cfg(target_os = "linux")]
+pub use veneer_macros::{main, test};
 
-#[cfg(all(feature = "rt", not(test)))]
+#[cfg(all(feature = "rt-personality", not(test)))]
 #[lang = "eh_personality"]
 #[no_mangle]
 pub extern "C" fn eh_personality() {}
 
-#[cfg(all(feature = "rt", not(test)))]
+#[cfg(all(feature = "rt-alloc", not(test)))]
 #[alloc_error_handler]
 fn alloc_error(layout: core::alloc::Layout) -> ! {
     panic!("memory allocation of {} bytes failed", layout.size());
 }
 
-#[cfg(all(target_os = "linux", feature = "rt", not(test)))]
+#[cfg(all(target_os = "linux", feature = "rt-panic", not(test)))]
 #[panic_handler]
 fn panic(info: &core::panic::PanicInfo) -> ! {
-    crate::eprintln!("{}", info);
-    let _ = crate::syscalls::kill(0, 6);
-    crate::syscalls::exit(-1)
+    crate::panic::begin_panic(info)
 }
 
-#[cfg(all(target_os = "linux", feature = "rt", not(test), target_arch = "x86_64"))]
+#[cfg(all(
+    target_os = "linux",
+    feature = "rt-start",
+    not(test),
+    target_arch = "x86_64"
+))]
 #[no_mangle]
 #[unsafe(naked)]
 unsafe extern "C" fn _start() {
-    // Just move argc and argv into the right registers and call main
+    // Apply our own relocations if we are position-independent, then just move argc, argv, and
+    // envp into the right registers and call main
     core::arch::naked_asm!(
+        // The linker only defines _DYNAMIC in position-independent executables
+        ".weak _DYNAMIC",
+        ".hidden _DYNAMIC",
+        "xor ebp, ebp", // A null frame pointer marks the outermost frame
+        // Code built with a stack protector reads its canary from fs:0x28, so until the real
+        // thread control block is set up, point fs at a zeroed one
+        "mov eax, 158", // arch_prctl
+        "mov edi, 0x1002", // ARCH_SET_FS
+        "lea rsi, [rip + {boot_tcb}]",
+        "syscall",
+        "mov rdi, rsp",
+        "lea rsi, [rip + _DYNAMIC]",
+        "call __veneer_relocate",
         "mov rdi, [rsp]", // The value of rsp is actually a pointer to argc
         "mov rsi, rsp",
         "add rsi, 8", // But for argv we just increment the rsp pointer by 1 (offset by 8)
+        "lea rdx, [rsi + rdi*8 + 8]", // envp starts after the null pointer that terminates argv
         "call __veneer_init",
+        // The canary is in the thread control block, which exists by now, with glibc's pointer
+        // guard next to it
+        "call __veneer_stack_guards",
+        "mov fs:0x28, rax",
+        "mov fs:0x30, rdx",
+        "mov [rip + __stack_chk_guard], rax",
+        "call __veneer_run_init_array",
         "call __veneer_main",
+        boot_tcb = sym crate::stack_protector::BOOT_TCB,
     )
 }
 
 #[cfg(all(
     target_os = "linux",
-    feature = "rt",
+    feature = "rt-start",
+    not(test),
+    target_arch = "x86"
+))]
+#[no_mangle]
+#[unsafe(naked)]
+unsafe extern "C" fn _start() {
+    // Arguments go on the stack, which must be 16-byte aligned at each call. esi keeps the initial
+    // stack pointer, which is aligned, so the stack is reset to it after each call. Statics are
+    // addressed relative to the GOT, whose address is in ebx.
+    core::arch::naked_asm!(
+        ".weak _DYNAMIC",
+        ".hidden _DYNAMIC",
+        "xor ebp, ebp",
+        "mov esi, esp",
+        // _GLOBAL_OFFSET_TABLE_ in an immediate is its distance from the start of the
+        // instruction, which is one byte after the address the call pushes
+        "call 2f",
+        "2:",
+        "pop ebx",
+        "add ebx, offset _GLOBAL_OFFSET_TABLE_ + 1",
+        // Code built with a stack protector reads its canary from gs:0x14, so until the real
+        // thread control block is set up, point gs at a zeroed one. That takes a user_desc for
+        // set_thread_area, which is built on the stack.
+        "sub esp, 16",
+        "mov dword ptr [esp], -1", // entry_number, which the kernel picks
+        "lea eax, [ebx + {boot_tcb}@GOTOFF]",
+        "mov [esp + 4], eax", // base_addr
+        "mov dword ptr [esp + 8], 0xfffff", // limit
+        "mov dword ptr [esp + 12], 0x51", // seg_32bit | limit_in_pages | useable
+        "mov eax, 243", // set_thread_area
+        "push ebx",
+        "lea ebx, [esp + 4]",
+        "int 0x80",
+        "pop ebx",
+        "mov eax, [esp]",
+        "lea eax, [eax * 8 + 3]",
+        "mov gs, eax",
+        "mov esp, esi",
+        "lea eax, [ebx + _DYNAMIC@GOTOFF]",
+        "sub esp, 8",
+        "push eax",
+        "push esi",
+        "call __veneer_relocate",
+        "mov esp, esi",
+        "mov eax, [esi]", // argc
+        "lea ecx, [esi + 4]", // argv
+        "lea edx, [ecx + eax * 4 + 4]", // envp starts after the null pointer that terminates argv
+        "sub esp, 4",
+        "push edx",
+        "push ecx",
+        "push eax",
+        "call __veneer_init",
+        "mov esp, esi",
+        // The guards are returned through a pointer to space on the stack
+        "sub esp, 12",
+        "mov edi, esp",
+        "push edi",
+        "call __veneer_stack_guards",
+        "mov eax, [edi]",
+        "mov edx, [edi + 4]",
+        "mov gs:0x14, eax",
+        "mov gs:0x18, edx",
+        "mov [ebx + __stack_chk_guard@GOTOFF], eax",
+        "mov esp, esi",
+        "call __veneer_run_init_array",
+        "call __veneer_main",
+        boot_tcb = sym crate::stack_protector::BOOT_TCB,
+    )
+}
+
+#[cfg(all(
+    target_os = "linux",
+    feature = "rt-start",
     not(test),
     target_arch = "aarch64"
 ))]
 #[no_mangle]
-#[naked]
+#[unsafe(naked)]
 unsafe extern "C" fn _start() {
     core::arch::naked_asm!(
+        ".weak _DYNAMIC",
+        ".hidden _DYNAMIC",
+        "mov x29, xzr",
+        "mov x30, xzr",
+        "mov x0, sp",
+        "adrp x1, _DYNAMIC",
+        "add x1, x1, :lo12:_DYNAMIC",
+        "bl __veneer_relocate",
         "ldr x0, [sp]",
         "mov x1, sp",
         "add x1, x1, 0x8",
+        "add x2, x1, x0, lsl 3",
+        "add x2, x2, 0x8",
         "bl __veneer_init",
+        "bl __veneer_stack_guards",
+        "adrp x2, __stack_chk_guard",
+        "str x0, [x2, :lo12:__stack_chk_guard]",
+        "bl __veneer_run_init_array",
         "bl __veneer_main",
     )
 }
 
-#[cfg(all(target_os = "linux", target_os = "linux", feature = "rt", not(test)))]
+#[cfg(all(target_os = "linux", feature = "rt-start", not(test)))]
 #[no_mangle]
-unsafe extern "C" fn __veneer_init(argc: isize, argv: *mut *const u8) {
+unsafe extern "C" fn __veneer_init(argc: isize, argv: *mut *const u8, envp: *mut *const u8) {
     crate::env::ARGC.store(argc, core::sync::atomic::Ordering::SeqCst);
     crate::env::ARGV.store(argv.cast(), core::sync::atomic::Ordering::SeqCst);
+    crate::env::ENVP.store(envp.cast(), core::sync::atomic::Ordering::SeqCst);
+
+    // The auxiliary vector starts after the null pointer that terminates envp
+    let mut auxv = envp;
+    while !(*auxv).is_null() {
+        auxv = auxv.add(1);
+    }
+    crate::env::AUXV.store(auxv.add(1).cast(), core::sync::atomic::Ordering::SeqCst);
+
+    crate::tls::init_main_thread();
+
+    #[cfg(feature = "rt-signals")]
+    crate::fatal_signal::install();
 }
 
-#[cfg(all(target_os = "linux", feature = "rt", not(test)))]
+#[cfg(all(target_os = "linux", feature = "rt-alloc", not(test)))]
 #[global_allocator]
 static ALLOC: crate::Allocator = crate::Allocator::new();
 
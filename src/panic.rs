@@ -0,0 +1,92 @@
+use crate::spinlock::SpinLock;
+use alloc::boxed::Box;
+use core::panic::PanicInfo;
+#[cfg(all(feature = "rt-panic", not(test)))]
+use core::sync::atomic::{AtomicBool, Ordering::SeqCst};
+
+pub type Hook = Box<dyn Fn(&PanicInfo<'_>) + Sync + Send + 'static>;
+
+static HOOK: SpinLock<Option<Hook>> = SpinLock::new(None);
+#[cfg(all(feature = "rt-panic", not(test)))]
+static PANICKING: AtomicBool = AtomicBool::new(false);
+
+/// Replace the function which is called to report a panic, before the process aborts
+#[inline]
+pub fn set_hook(hook: Hook) {
+    *HOOK.lock() = Some(hook);
+}
+
+/// Unregister the current panic hook and return it, or the default hook if none was registered
+#[inline]
+pub fn take_hook() -> Hook {
+    HOOK.lock().take().unwrap_or_else(|| Box::new(default_hook))
+}
+
+/// Print the panic message and location to stderr
+#[cfg(not(feature = "minimal-panic"))]
+#[inline]
+pub fn default_hook(info: &PanicInfo<'_>) {
+    crate::eprintln!("{}", info);
+    #[cfg(feature = "backtrace")]
+    print_backtrace();
+}
+
+/// Print the panic location, and the message if it does not need formatting, to stderr
+///
+/// With the `minimal-panic` feature this avoids `core::fmt` entirely, which keeps it out of
+/// programs that do not otherwise use it.
+#[cfg(feature = "minimal-panic")]
+#[inline]
+pub fn default_hook(info: &PanicInfo<'_>) {
+    use crate::io::{Stderr, Write};
+    let mut number = crate::fmt::Buffer::new();
+    let _ = Stderr.write_all(b"panicked");
+    if let Some(location) = info.location() {
+        let _ = Stderr.write_all(b" at ");
+        let _ = Stderr.write_all(location.file().as_bytes());
+        let _ = Stderr.write_all(b":");
+        let _ = Stderr.write_all(number.format(u64::from(location.line())));
+        let _ = Stderr.write_all(b":");
+        let _ = Stderr.write_all(number.format(u64::from(location.column())));
+    }
+    if let Some(message) = info.message().as_str() {
+        let _ = Stderr.write_all(b":\n");
+        let _ = Stderr.write_all(message.as_bytes());
+    }
+    let _ = Stderr.write_all(b"\n");
+    #[cfg(feature = "backtrace")]
+    print_backtrace();
+}
+
+#[cfg(feature = "backtrace")]
+fn print_backtrace() {
+    use crate::io::{Stderr, Write};
+    if crate::backtrace::enabled() {
+        let _ = crate::backtrace::Backtrace::capture().print(&mut Stderr);
+    } else {
+        let _ = Stderr.write_all(
+            b"note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace\n",
+        );
+    }
+}
+
+/// Run the panic hook then abort; the body of the runtime's `#[panic_handler]`
+#[cfg(all(feature = "rt-panic", not(test)))]
+pub(crate) fn begin_panic(info: &PanicInfo<'_>) -> ! {
+    // A panic inside the hook must not try to run the hook again
+    if PANICKING.swap(true, SeqCst) {
+        let _ = crate::io::Write::write_all(
+            &mut crate::io::Stderr,
+            b"thread panicked while processing panic. aborting.\n",
+        );
+        crate::process::abort();
+    }
+    // The process aborts after this, so the hook can be taken rather than run with the lock held,
+    // which would deadlock a hook that calls set_hook or take_hook
+    let hook = HOOK.lock().take();
+    match hook {
+        Some(hook) => hook(info),
+        None => default_hook(info),
+    }
+    crate::process::abort()
+}
@@ -0,0 +1,282 @@
+use crate::{
+    fd::{AsFd, AsRawFd, BorrowedFd},
+    spinlock::SpinLock,
+    syscalls::{
+        self,
+        probe::{self, Capability},
+        ClockId, SigmaskHow,
+    },
+    uapi, Error,
+};
+use core::{fmt::Display, time::Duration};
+
+// POSIX requires room for at least 32 atexit functions
+struct Hooks {
+    slots: [Option<fn()>; 32],
+    len: usize,
+}
+
+static HOOKS: SpinLock<Hooks> = SpinLock::new(Hooks {
+    slots: [None; 32],
+    len: 0,
+});
+
+/// Register a function to be called by [`exit`]
+///
+/// Hooks run in the reverse order of their registration. Returns `ENOMEM` if there is no room for
+/// another hook.
+#[inline]
+pub fn atexit(hook: fn()) -> Result<(), Error> {
+    let mut hooks = HOOKS.lock();
+    let len = hooks.len;
+    match hooks.slots.get_mut(len) {
+        Some(slot) => {
+            *slot = Some(hook);
+            hooks.len += 1;
+            Ok(())
+        }
+        None => Err(Error(uapi::ENOMEM)),
+    }
+}
+
+fn run_hooks() {
+    loop {
+        // Release the lock before running each hook, so that hooks may register more hooks
+        let hook = {
+            let mut hooks = HOOKS.lock();
+            if hooks.len == 0 {
+                return;
+            }
+            hooks.len -= 1;
+            let len = hooks.len;
+            hooks.slots[len].take()
+        };
+        if let Some(hook) = hook {
+            hook();
+        }
+    }
+}
+
+/// Terminate the process after running the hooks registered with [`atexit`] and the static
+/// destructors, and flushing [`BufferedStdout`](crate::io::BufferedStdout)
+#[inline]
+pub fn exit(code: i32) -> ! {
+    run_hooks();
+    #[cfg(all(feature = "rt-start", not(test)))]
+    crate::init_array::run_fini_array();
+    crate::io::flush_std_streams();
+    #[cfg(feature = "trace")]
+    syscalls::trace::report();
+    syscalls::exit_group(code)
+}
+
+/// Terminate the process abnormally with `SIGABRT`, without running any hooks or flushing
+/// buffered output
+///
+/// Any handler for `SIGABRT` is uninstalled first, and the signal is only sent to the calling
+/// thread, never to the rest of the process group.
+#[inline]
+pub fn abort() -> ! {
+    let default = syscalls::SigAction::default();
+    let _ = syscalls::sigaction(uapi::SIGABRT, &default, &mut syscalls::SigAction::default());
+    let _ = syscalls::sigprocmask(syscalls::SigmaskHow::Unblock, 1 << (uapi::SIGABRT - 1));
+    let _ = syscalls::tgkill(syscalls::getpid(), syscalls::gettid(), uapi::SIGABRT);
+    // Delivering the signal should have killed us, but we still must not return
+    syscalls::exit_group(128 + uapi::SIGABRT)
+}
+
+/// Wait up to `timeout` for the child `pid` to exit, returning its wait status, or `None` if it is
+/// still running
+///
+/// The wait polls a pidfd for the child, or on kernels before 5.3, checks on it with `wait4` at
+/// intervals which grow to 50ms. Signals that are blocked stay blocked throughout.
+#[inline]
+pub fn wait_timeout(pid: uapi::pid_t, timeout: Duration) -> Result<Option<i32>, Error> {
+    // A pidfd can only refer to one process, not a process group
+    let pidfd = if pid > 0 {
+        match probe::attempt(Capability::PidfdOpen, || syscalls::pidfd_open(pid, 0)) {
+            Ok(fd) => Some(fd),
+            Err(Error(uapi::ENOSYS)) => None,
+            Err(e) => return Err(e),
+        }
+    } else {
+        None
+    };
+    wait_until(pid, pidfd.as_ref().map(AsFd::as_fd), timeout)
+}
+
+fn wait_until(
+    pid: uapi::pid_t,
+    pidfd: Option<BorrowedFd<'_>>,
+    timeout: Duration,
+) -> Result<Option<i32>, Error> {
+    let now = || {
+        syscalls::clock_gettime(ClockId::Monotonic)
+            .map(|ts| Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32))
+    };
+    let start = now()?;
+    // Only reading the mask, so that ppoll leaves it as it is
+    let sigmask = syscalls::sigprocmask(SigmaskHow::Block, 0)?;
+    let mut interval = Duration::from_millis(1);
+    loop {
+        match syscalls::wait4(pid, uapi::WNOHANG) {
+            Ok((0, _)) | Err(Error(uapi::EINTR)) => {}
+            Ok((_, status)) => return Ok(Some(status)),
+            Err(e) => return Err(e),
+        }
+        let remaining = match timeout.checked_sub(now()?.saturating_sub(start)) {
+            Some(remaining) if !remaining.is_zero() => remaining,
+            _ => return Ok(None),
+        };
+        // poll skips negative descriptors, so without a pidfd this only sleeps
+        let mut fds = [uapi::pollfd {
+            fd: pidfd.map_or(-1, |fd| fd.as_raw_fd()),
+            events: uapi::POLLIN,
+            revents: 0,
+        }];
+        let sleep = if pidfd.is_some() {
+            remaining
+        } else {
+            let sleep = remaining.min(interval);
+            interval = (interval * 2).min(Duration::from_millis(50));
+            sleep
+        };
+        let sleep = uapi::timespec {
+            tv_sec: sleep.as_secs() as uapi::time_t,
+            tv_nsec: sleep.subsec_nanos() as uapi::c_long,
+        };
+        match syscalls::ppoll(&mut fds, &sleep, &sigmask) {
+            Ok(_) | Err(Error(uapi::EINTR)) => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// The status code a process reports to its parent when it exits
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExitCode(u8);
+
+impl ExitCode {
+    pub const SUCCESS: ExitCode = ExitCode(0);
+    pub const FAILURE: ExitCode = ExitCode(1);
+
+    #[inline]
+    pub fn to_i32(self) -> i32 {
+        i32::from(self.0)
+    }
+}
+
+impl From<u8> for ExitCode {
+    #[inline]
+    fn from(code: u8) -> Self {
+        ExitCode(code)
+    }
+}
+
+/// Types that can be returned from a `#[veneer::main]` function
+pub trait Termination {
+    /// Convert this value into the process's exit code, printing anything the user should see
+    fn report(self) -> ExitCode;
+}
+
+impl Termination for () {
+    #[inline]
+    fn report(self) -> ExitCode {
+        ExitCode::SUCCESS
+    }
+}
+
+impl Termination for ExitCode {
+    #[inline]
+    fn report(self) -> ExitCode {
+        self
+    }
+}
+
+impl Termination for u8 {
+    #[inline]
+    fn report(self) -> ExitCode {
+        ExitCode(self)
+    }
+}
+
+impl Termination for ! {
+    #[inline]
+    fn report(self) -> ExitCode {
+        self
+    }
+}
+
+impl Termination for core::convert::Infallible {
+    #[inline]
+    fn report(self) -> ExitCode {
+        match self {}
+    }
+}
+
+impl<T: Termination, E: Display> Termination for Result<T, E> {
+    #[inline]
+    fn report(self) -> ExitCode {
+        match self {
+            Ok(value) => value.report(),
+            Err(e) => {
+                crate::eprintln!("Error: {}", e);
+                ExitCode::FAILURE
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+
+    #[test]
+    fn report() {
+        assert_eq!(().report(), ExitCode::SUCCESS);
+        assert_eq!(2u8.report().to_i32(), 2);
+        assert_eq!(ExitCode::from(2).report(), ExitCode::from(2));
+        assert_eq!(Ok::<(), Error>(()).report(), ExitCode::SUCCESS);
+        assert_eq!(Ok::<u8, Error>(2).report().to_i32(), 2);
+        assert_eq!(
+            Err::<(), Error>(Error(uapi::ENOENT)).report(),
+            ExitCode::FAILURE
+        );
+    }
+
+    static ORDER: AtomicUsize = AtomicUsize::new(0);
+
+    #[test]
+    fn hooks_run_in_reverse() {
+        atexit(|| assert_eq!(ORDER.fetch_add(1, SeqCst), 1)).unwrap();
+        atexit(|| assert_eq!(ORDER.fetch_add(1, SeqCst), 0)).unwrap();
+        run_hooks();
+        assert_eq!(ORDER.load(SeqCst), 2);
+
+        // Every hook only runs once
+        run_hooks();
+        assert_eq!(ORDER.load(SeqCst), 2);
+    }
+
+    #[test]
+    fn waits_with_a_timeout() {
+        let pid = syscalls::fork().unwrap();
+        if pid == 0 {
+            let nap = uapi::timespec {
+                tv_sec: 0,
+                tv_nsec: 200_000_000,
+            };
+            let _ = syscalls::ppoll(&mut [], &nap, &0);
+            syscalls::exit_group(3);
+        }
+        assert_eq!(wait_timeout(pid, Duration::from_millis(10)).unwrap(), None);
+        let status = wait_timeout(pid, Duration::from_secs(10)).unwrap().unwrap();
+        assert!(uapi::WIFEXITED(status));
+        assert_eq!(uapi::WEXITSTATUS(status), 3);
+        assert_eq!(
+            wait_timeout(pid, Duration::from_millis(10)).unwrap_err(),
+            uapi::ESRCH
+        );
+    }
+}
@@ -0,0 +1,149 @@
+//! Applies the executable's own relocations, which lets the runtime work in a static
+//! position-independent executable, where there is no dynamic loader to do it.
+//!
+//! This runs first thing in `_start`, and until it is done every pointer stored in a static is
+//! wrong, including the GOT entries that calls to other functions may go through. So this must
+//! not call anything, not even the small helpers from `core` which only get inlined in optimized
+//! builds; everything is done with plain arithmetic on addresses.
+
+use crate::{
+    elf::{self, Dyn, Phdr, Rel, Rela},
+    env::{AT_NULL, AT_PHDR, AT_PHENT, AT_PHNUM},
+};
+
+#[cfg(target_arch = "x86_64")]
+const R_RELATIVE: elf::Xword = elf::R_X86_64_RELATIVE as elf::Xword;
+#[cfg(target_arch = "aarch64")]
+const R_RELATIVE: elf::Xword = elf::R_AARCH64_RELATIVE as elf::Xword;
+#[cfg(target_arch = "x86")]
+const R_RELATIVE: elf::Xword = elf::R_386_RELATIVE as elf::Xword;
+
+// The relocation type is in the low 32 bits of r_info in a 64-bit ELF, and the low 8 in a 32-bit
+// one
+#[cfg(target_pointer_width = "64")]
+const R_TYPE: elf::Xword = 0xffff_ffff;
+#[cfg(target_pointer_width = "32")]
+const R_TYPE: elf::Xword = 0xff;
+
+const WORD: usize = core::mem::size_of::<usize>();
+const DYN_SIZE: usize = core::mem::size_of::<Dyn>();
+const RELA_SIZE: usize = core::mem::size_of::<Rela>();
+const REL_SIZE: usize = core::mem::size_of::<Rel>();
+
+/// Relocate the executable, given the initial stack pointer and the address of `_DYNAMIC`, which
+/// is 0 if the executable was not linked as position-independent
+#[no_mangle]
+unsafe extern "C" fn __veneer_relocate(sp: usize, dynamic: usize) {
+    if dynamic == 0 {
+        return;
+    }
+
+    // The auxiliary vector is after argc, argv, and envp
+    let argc = *(sp as *const usize);
+    let mut auxv = sp + (argc + 2) * WORD;
+    while *(auxv as *const usize) != 0 {
+        auxv += WORD;
+    }
+    auxv += WORD;
+
+    let mut phdrs = 0;
+    let mut phent = 0;
+    let mut phnum = 0;
+    while *(auxv as *const usize) != AT_NULL {
+        let value = *((auxv + WORD) as *const usize);
+        match *(auxv as *const usize) {
+            AT_PHDR => phdrs = value,
+            AT_PHENT => phent = value,
+            AT_PHNUM => phnum = value,
+            _ => {}
+        }
+        auxv += 2 * WORD;
+    }
+
+    // How far the kernel moved the executable from the addresses it was linked at
+    let mut base = 0;
+    let mut i = 0;
+    while i < phnum {
+        let phdr = &*((phdrs + i * phent) as *const Phdr);
+        if phdr.p_type == elf::PT_DYNAMIC {
+            base = dynamic - phdr.p_vaddr as usize;
+        }
+        i += 1;
+    }
+    if base == 0 {
+        return;
+    }
+
+    let mut rela = 0;
+    let mut rela_size = 0;
+    let mut rela_entry = RELA_SIZE;
+    let mut rel = 0;
+    let mut rel_size = 0;
+    let mut rel_entry = REL_SIZE;
+    let mut relr = 0;
+    let mut relr_size = 0;
+    let mut entry = dynamic;
+    while (*(entry as *const Dyn)).d_tag != elf::DT_NULL {
+        let value = (*(entry as *const Dyn)).d_val as usize;
+        match (*(entry as *const Dyn)).d_tag {
+            elf::DT_RELA => rela = base + value,
+            elf::DT_RELASZ => rela_size = value,
+            elf::DT_RELAENT => rela_entry = value,
+            elf::DT_REL => rel = base + value,
+            elf::DT_RELSZ => rel_size = value,
+            elf::DT_RELENT => rel_entry = value,
+            elf::DT_RELR => relr = base + value,
+            elf::DT_RELRSZ => relr_size = value,
+            _ => {}
+        }
+        entry += DYN_SIZE;
+    }
+
+    // A static executable has no symbols to look up, so every relocation the linker left in it
+    // is relative to where it was loaded
+    let mut offset = 0;
+    while offset < rela_size {
+        let rela = &*((rela + offset) as *const Rela);
+        if rela.r_info & R_TYPE == R_RELATIVE {
+            *((base + rela.r_offset as usize) as *mut usize) =
+                (base as elf::Sxword + rela.r_addend) as usize;
+        }
+        offset += rela_entry;
+    }
+
+    // i386 keeps the addends of its relocations in place, like RELR does
+    let mut offset = 0;
+    while offset < rel_size {
+        let rel = &*((rel + offset) as *const Rel);
+        if rel.r_info & R_TYPE == R_RELATIVE {
+            *((base + rel.r_offset as usize) as *mut usize) += base;
+        }
+        offset += rel_entry;
+    }
+
+    // RELR is a compressed list of relative relocations whose addends are stored in place. An
+    // even entry is the address of a word to relocate; an odd entry is a bitmap of which of the
+    // words after the last one relocated to relocate next.
+    let mut target = 0;
+    let mut offset = 0;
+    while offset < relr_size {
+        let entry = *((relr + offset) as *const usize);
+        if entry & 1 == 0 {
+            target = base + entry;
+            *(target as *mut usize) += base;
+            target += WORD;
+        } else {
+            let mut bits = entry >> 1;
+            let mut word = target;
+            while bits != 0 {
+                if bits & 1 != 0 {
+                    *(word as *mut usize) += base;
+                }
+                bits >>= 1;
+                word += WORD;
+            }
+            target += (8 * WORD - 1) * WORD;
+        }
+        offset += WORD;
+    }
+}
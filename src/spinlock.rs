@@ -36,6 +36,13 @@ impl<T> SpinLock<T> {
         }
         SpinLockGuard { lock: self }
     }
+
+    pub fn try_lock(&self) -> Option<SpinLockGuard<'_, T>> {
+        self.locked
+            .compare_exchange(false, true, Acquire, Acquire)
+            .ok()
+            .map(|_| SpinLockGuard { lock: self })
+    }
 }
 
 impl<T> Drop for SpinLockGuard<'_, T> {
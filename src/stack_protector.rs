@@ -0,0 +1,60 @@
+//! Support for code built with `-Z stack-protector`, which saves a random canary below the return
+//! address of each protected function and checks it is intact before returning.
+//!
+//! On x86_64 the compiler reads the canary from `fs:0x28` in the thread control block, on i386
+//! from `gs:0x14`, and on aarch64 from the `__stack_chk_guard` global. All three are seeded from
+//! the random bytes the kernel passes in the auxiliary vector. Every protected function that is
+//! running when the canary changes would find it clobbered on return, so `_start` stores the new
+//! values itself, after `__veneer_init` has returned and before `__veneer_main` is called. Until
+//! then the canary is 0, which on x86_64 and i386 comes from [`BOOT_TCB`].
+
+use crate::syscalls;
+
+/// The canary which protected functions check, on targets that keep it in a global
+#[no_mangle]
+pub static mut __stack_chk_guard: usize = 0;
+
+/// The thread control block `_start` installs before anything else runs, which is replaced by the
+/// main thread's real one once the thread-local storage is set up
+#[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
+pub(crate) static mut BOOT_TCB: [usize; 8] = [0; 8];
+
+/// The values `_start` stores as the stack protector canary and the pointer guard, in that order
+#[repr(C)]
+struct Guards {
+    canary: usize,
+    pointer: usize,
+}
+
+#[no_mangle]
+extern "C" fn __veneer_stack_guards() -> Guards {
+    let random = match crate::env::random_bytes() {
+        Some(random) => random,
+        None => {
+            return Guards {
+                canary: 0,
+                pointer: 0,
+            }
+        }
+    };
+    let mut canary = [0; 8];
+    let mut pointer = [0; 8];
+    canary.copy_from_slice(&random[..8]);
+    pointer.copy_from_slice(&random[8..]);
+    Guards {
+        // A zero byte first stops an overflow through a string function from reading the canary
+        // or writing it back, since those stop at the terminator
+        canary: u64::from_le_bytes(canary) as usize & !0xff,
+        pointer: u64::from_le_bytes(pointer) as usize,
+    }
+}
+
+/// Called by a protected function which found its canary overwritten
+#[no_mangle]
+extern "C" fn __stack_chk_fail() -> ! {
+    let _ = syscalls::write(
+        crate::fd::STDERR,
+        b"fatal runtime error: stack smashing detected\n",
+    );
+    crate::process::abort();
+}
@@ -0,0 +1,491 @@
+//! System calls on aarch64, which take the number in x8 and arguments in x0 through x5, and
+//! return in x0.
+
+use super::RawSyscallResult;
+use core::arch::asm;
+
+/// The numbers of the system calls veneer uses
+pub mod nr {
+    pub const DUP: usize = 23;
+    pub const DUP3: usize = 24;
+    pub const FCNTL: usize = 25;
+    pub const IOCTL: usize = 29;
+    pub const FACCESSAT: usize = 48;
+    pub const OPENAT: usize = 56;
+    pub const CLOSE: usize = 57;
+    pub const PIPE2: usize = 59;
+    pub const GETDENTS64: usize = 61;
+    pub const LSEEK: usize = 62;
+    pub const READ: usize = 63;
+    pub const WRITE: usize = 64;
+    pub const READV: usize = 65;
+    pub const WRITEV: usize = 66;
+    pub const PREAD64: usize = 67;
+    pub const PWRITE64: usize = 68;
+    pub const PPOLL: usize = 73;
+    pub const READLINKAT: usize = 78;
+    pub const NEWFSTATAT: usize = 79;
+    pub const FSTAT: usize = 80;
+    pub const EXIT: usize = 93;
+    pub const EXIT_GROUP: usize = 94;
+    pub const FUTEX: usize = 98;
+    pub const CLOCK_GETTIME: usize = 113;
+    pub const SCHED_YIELD: usize = 124;
+    pub const KILL: usize = 129;
+    pub const TGKILL: usize = 131;
+    pub const SIGALTSTACK: usize = 132;
+    pub const RT_SIGACTION: usize = 134;
+    pub const RT_SIGPROCMASK: usize = 135;
+    pub const GETCPU: usize = 168;
+    pub const GETTIMEOFDAY: usize = 169;
+    pub const GETPID: usize = 172;
+    pub const GETTID: usize = 178;
+    pub const SHMGET: usize = 194;
+    pub const BRK: usize = 214;
+    pub const MUNMAP: usize = 215;
+    pub const MREMAP: usize = 216;
+    pub const CLONE: usize = 220;
+    pub const EXECVE: usize = 221;
+    pub const MMAP: usize = 222;
+    pub const MPROTECT: usize = 226;
+    pub const MSYNC: usize = 227;
+    pub const MINCORE: usize = 232;
+    pub const MADVISE: usize = 233;
+    pub const WAIT4: usize = 260;
+    pub const PRLIMIT64: usize = 261;
+    pub const COPY_FILE_RANGE: usize = 285;
+    pub const STATX: usize = 291;
+    pub const PIDFD_OPEN: usize = 434;
+    pub const CLONE3: usize = 435;
+    pub const OPENAT2: usize = 437;
+}
+
+/// Make system call `n` with no arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them.
+#[inline(always)]
+pub unsafe fn syscall0(n: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "svc 0",
+        in("x8") n,
+        lateout("x0") ret,
+        options(nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with one argument
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them.
+#[inline(always)]
+pub unsafe fn syscall1(n: usize, a: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "svc 0",
+        in("x8") n,
+        inlateout("x0") a => ret,
+        options(nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with two arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them.
+#[inline(always)]
+pub unsafe fn syscall2(n: usize, a: usize, b: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "svc 0",
+        in("x8") n,
+        inlateout("x0") a => ret,
+        in("x1") b,
+        options(nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with three arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them.
+#[inline(always)]
+pub unsafe fn syscall3(n: usize, a: usize, b: usize, c: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "svc 0",
+        in("x8") n,
+        inlateout("x0") a => ret,
+        in("x1") b,
+        in("x2") c,
+        options(nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with four arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them.
+#[inline(always)]
+pub unsafe fn syscall4(n: usize, a: usize, b: usize, c: usize, d: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "svc 0",
+        in("x8") n,
+        inlateout("x0") a => ret,
+        in("x1") b,
+        in("x2") c,
+        in("x3") d,
+        options(nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with five arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them.
+#[inline(always)]
+pub unsafe fn syscall5(
+    n: usize,
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+    e: usize,
+) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "svc 0",
+        in("x8") n,
+        inlateout("x0") a => ret,
+        in("x1") b,
+        in("x2") c,
+        in("x3") d,
+        in("x4") e,
+        options(nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with six arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them.
+#[inline(always)]
+pub unsafe fn syscall6(
+    n: usize,
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+    e: usize,
+    f: usize,
+) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "svc 0",
+        in("x8") n,
+        inlateout("x0") a => ret,
+        in("x1") b,
+        in("x2") c,
+        in("x3") d,
+        in("x4") e,
+        in("x5") f,
+        options(nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with no arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them, and it
+/// must not write to memory.
+#[inline(always)]
+pub unsafe fn syscall0_readonly(n: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "svc 0",
+        in("x8") n,
+        lateout("x0") ret,
+        options(nostack, preserves_flags, readonly),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with one argument
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them, and it
+/// must not write to memory.
+#[inline(always)]
+pub unsafe fn syscall1_readonly(n: usize, a: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "svc 0",
+        in("x8") n,
+        inlateout("x0") a => ret,
+        options(nostack, preserves_flags, readonly),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with two arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them, and it
+/// must not write to memory.
+#[inline(always)]
+pub unsafe fn syscall2_readonly(n: usize, a: usize, b: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "svc 0",
+        in("x8") n,
+        inlateout("x0") a => ret,
+        in("x1") b,
+        options(nostack, preserves_flags, readonly),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with three arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them, and it
+/// must not write to memory.
+#[inline(always)]
+pub unsafe fn syscall3_readonly(n: usize, a: usize, b: usize, c: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "svc 0",
+        in("x8") n,
+        inlateout("x0") a => ret,
+        in("x1") b,
+        in("x2") c,
+        options(nostack, preserves_flags, readonly),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with four arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them, and it
+/// must not write to memory.
+#[inline(always)]
+pub unsafe fn syscall4_readonly(
+    n: usize,
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "svc 0",
+        in("x8") n,
+        inlateout("x0") a => ret,
+        in("x1") b,
+        in("x2") c,
+        in("x3") d,
+        options(nostack, preserves_flags, readonly),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with five arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them, and it
+/// must not write to memory.
+#[inline(always)]
+pub unsafe fn syscall5_readonly(
+    n: usize,
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+    e: usize,
+) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "svc 0",
+        in("x8") n,
+        inlateout("x0") a => ret,
+        in("x1") b,
+        in("x2") c,
+        in("x3") d,
+        in("x4") e,
+        options(nostack, preserves_flags, readonly),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with six arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them, and it
+/// must not write to memory.
+#[inline(always)]
+pub unsafe fn syscall6_readonly(
+    n: usize,
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+    e: usize,
+    f: usize,
+) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "svc 0",
+        in("x8") n,
+        inlateout("x0") a => ret,
+        in("x1") b,
+        in("x2") c,
+        in("x3") d,
+        in("x4") e,
+        in("x5") f,
+        options(nostack, preserves_flags, readonly),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with no arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, and it must not read or write memory.
+#[inline(always)]
+pub unsafe fn syscall0_nomem(n: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "svc 0",
+        in("x8") n,
+        lateout("x0") ret,
+        options(nomem, nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with one argument
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, and it must not read or write memory.
+#[inline(always)]
+pub unsafe fn syscall1_nomem(n: usize, a: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "svc 0",
+        in("x8") n,
+        inlateout("x0") a => ret,
+        options(nomem, nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with two arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, and it must not read or write memory.
+#[inline(always)]
+pub unsafe fn syscall2_nomem(n: usize, a: usize, b: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "svc 0",
+        in("x8") n,
+        inlateout("x0") a => ret,
+        in("x1") b,
+        options(nomem, nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with three arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, and it must not read or write memory.
+#[inline(always)]
+pub unsafe fn syscall3_nomem(n: usize, a: usize, b: usize, c: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "svc 0",
+        in("x8") n,
+        inlateout("x0") a => ret,
+        in("x1") b,
+        in("x2") c,
+        options(nomem, nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with four arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, and it must not read or write memory.
+#[inline(always)]
+pub unsafe fn syscall4_nomem(n: usize, a: usize, b: usize, c: usize, d: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "svc 0",
+        in("x8") n,
+        inlateout("x0") a => ret,
+        in("x1") b,
+        in("x2") c,
+        in("x3") d,
+        options(nomem, nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with five arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, and it must not read or write memory.
+#[inline(always)]
+pub unsafe fn syscall5_nomem(
+    n: usize,
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+    e: usize,
+) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "svc 0",
+        in("x8") n,
+        inlateout("x0") a => ret,
+        in("x1") b,
+        in("x2") c,
+        in("x3") d,
+        in("x4") e,
+        options(nomem, nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
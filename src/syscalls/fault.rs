@@ -0,0 +1,253 @@
+//! Failures injected into the wrappers in [`syscalls`](super), so that tests can reach the error
+//! handling of the code built on them
+//!
+//! With the `fault-injection` feature, each wrapper checks the rules added with [`inject`] before
+//! it enters the kernel. A rule either fails the call with an errno, or lowers the number of bytes
+//! `read`, `write`, `pread64`, `pwrite64`, and `getdents64` pass to the kernel so that they make
+//! short reads and writes. Calls that the vDSO answers and calls that do not return, like
+//! `exit_group`, are never affected.
+//!
+//! Rules can also be listed in `VENEER_FAULTS`, separated by commas, which is read the first time
+//! a wrapper is called after the runtime has started:
+//!
+//! * `getdents64@3=EIO` fails the third call to `getdents64` with `EIO`
+//! * `read%2=EINTR` fails every second call to `read` with `EINTR`, starting with the first, so a
+//!   read that is retried once succeeds
+//! * `close=EBADF` fails every call to `close`
+//! * `write<=7` makes every `write` pass at most 7 bytes to the kernel
+//!
+//! The names are those of the wrappers, which are the ones [`trace`](super::trace) logs.
+
+use super::raw::syscall;
+use crate::{
+    spinlock::{SpinLock, SpinLockGuard},
+    uapi::{self, c_int},
+    Error,
+};
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// What happens to a call which a rule applies to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// Return this errno without entering the kernel
+    Fail(c_int),
+    /// Pass at most this many bytes to the kernel
+    Limit(usize),
+}
+
+/// Which calls a rule applies to, counting from 1
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Schedule {
+    /// Only this call
+    Nth(usize),
+    /// This call and every `n`th one after it, starting with the first, so `Every(1)` is every
+    /// call
+    Every(usize),
+}
+
+#[derive(Clone, Copy)]
+struct Rule {
+    syscall: &'static str,
+    fault: Fault,
+    schedule: Schedule,
+    calls: usize,
+}
+
+impl Rule {
+    /// Count a call and return whether the rule applies to it
+    fn matches(&mut self) -> bool {
+        self.calls += 1;
+        match self.schedule {
+            Schedule::Nth(n) => self.calls == n,
+            Schedule::Every(n) => (self.calls - 1).is_multiple_of(n.max(1)),
+        }
+    }
+}
+
+struct Rules {
+    slots: [Option<Rule>; 16],
+    len: usize,
+}
+
+impl Rules {
+    fn push(&mut self, rule: Rule) -> Result<(), Error> {
+        let len = self.len;
+        match self.slots.get_mut(len) {
+            Some(slot) => {
+                *slot = Some(rule);
+                self.len += 1;
+                Ok(())
+            }
+            None => Err(Error(uapi::ENOMEM)),
+        }
+    }
+
+    fn for_syscall<'a>(&'a mut self, syscall: &'a str) -> impl Iterator<Item = &'a mut Rule> {
+        self.slots[..self.len]
+            .iter_mut()
+            .flatten()
+            .filter(move |rule| rule.syscall == syscall)
+    }
+}
+
+static RULES: SpinLock<Rules> = SpinLock::new(Rules {
+    slots: [None; 16],
+    len: 0,
+});
+
+static LOADED: AtomicBool = AtomicBool::new(false);
+
+/// Apply `fault` to the calls to the wrapper named `syscall` which `schedule` selects
+///
+/// Rules apply in the order they were added, and the first matching `Fail` wins. Returns `ENOMEM`
+/// if there is no room for another rule.
+#[inline]
+pub fn inject(syscall: &'static str, fault: Fault, schedule: Schedule) -> Result<(), Error> {
+    rules().push(Rule {
+        syscall,
+        fault,
+        schedule,
+        calls: 0,
+    })
+}
+
+/// Remove every rule, including those from `VENEER_FAULTS`
+#[inline]
+pub fn clear() {
+    let mut rules = rules();
+    rules.slots = [None; 16];
+    rules.len = 0;
+}
+
+fn rules() -> SpinLockGuard<'static, Rules> {
+    let mut rules = RULES.lock();
+    if !LOADED.load(Ordering::Relaxed) && !crate::env::ENVP.load(Ordering::SeqCst).is_null() {
+        LOADED.store(true, Ordering::Relaxed);
+        if let Some(list) = crate::env::var(b"VENEER_FAULTS") {
+            for text in list.as_bytes().split(|&b| b == b',') {
+                let rule = parse(text).ok_or(Error(uapi::EINVAL));
+                if rule.and_then(|rule| rules.push(rule)).is_err() {
+                    ignored(text);
+                }
+            }
+        }
+    }
+    rules
+}
+
+fn ignored(rule: &[u8]) {
+    // Through the wrapper this would try to take the lock again
+    for part in [&b"veneer: ignoring fault rule '"[..], rule, b"'\n"] {
+        let _ = unsafe { syscall!(WRITE, uapi::STDERR_FILENO, part.as_ptr(), part.len()) };
+    }
+}
+
+/// Parse one of the rules in `VENEER_FAULTS`, like `read%2=EINTR` or `write<=7`
+fn parse(text: &'static [u8]) -> Option<Rule> {
+    let text = core::str::from_utf8(text).ok()?;
+    let (target, fault) = match text.find("<=") {
+        Some(i) => (&text[..i], Fault::Limit(text[i + 2..].parse().ok()?)),
+        None => {
+            let (target, errno) = text.split_at(text.find('=')?);
+            (target, Fault::Fail(parse_errno(&errno[1..])?))
+        }
+    };
+    let (syscall, schedule) = match target.find(['@', '%']) {
+        Some(i) => {
+            let n = target[i + 1..].parse().ok().filter(|&n| n > 0)?;
+            let schedule = if target.as_bytes()[i] == b'@' {
+                Schedule::Nth(n)
+            } else {
+                Schedule::Every(n)
+            };
+            (&target[..i], schedule)
+        }
+        None => (target, Schedule::Every(1)),
+    };
+    if syscall.is_empty() {
+        return None;
+    }
+    Some(Rule {
+        syscall,
+        fault,
+        schedule,
+        calls: 0,
+    })
+}
+
+fn parse_errno(name: &str) -> Option<c_int> {
+    match name.parse() {
+        Ok(errno) => Some(errno),
+        Err(_) => (1..4096).find(|&errno| uapi::errno_name(errno) == Some(name)),
+    }
+}
+
+/// The errno to fail this call to `syscall` with, if a rule says it should fail
+pub(crate) fn failure(syscall: &str) -> Option<c_int> {
+    let mut failure = None;
+    for rule in rules().for_syscall(syscall) {
+        if let Fault::Fail(errno) = rule.fault {
+            if rule.matches() && failure.is_none() {
+                failure = Some(errno);
+            }
+        }
+    }
+    failure
+}
+
+/// How many of `len` bytes this call to `syscall` should pass to the kernel
+pub(crate) fn limit(syscall: &str, len: usize) -> usize {
+    let mut limit = len;
+    for rule in rules().for_syscall(syscall) {
+        if let Fault::Limit(max) = rule.fault {
+            if rule.matches() {
+                limit = limit.min(max);
+            }
+        }
+    }
+    limit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(text: &'static str) -> Option<(&'static str, Fault, Schedule)> {
+        parse(text.as_bytes()).map(|rule| (rule.syscall, rule.fault, rule.schedule))
+    }
+
+    #[test]
+    fn parses_rules() {
+        assert_eq!(
+            parsed("getdents64@3=EIO"),
+            Some(("getdents64", Fault::Fail(uapi::EIO), Schedule::Nth(3)))
+        );
+        assert_eq!(
+            parsed("read%2=EINTR"),
+            Some(("read", Fault::Fail(uapi::EINTR), Schedule::Every(2)))
+        );
+        assert_eq!(
+            parsed("close=9"),
+            Some(("close", Fault::Fail(uapi::EBADF), Schedule::Every(1)))
+        );
+        assert_eq!(
+            parsed("write<=7"),
+            Some(("write", Fault::Limit(7), Schedule::Every(1)))
+        );
+        assert_eq!(parsed("read=ENOTANERRNO"), None);
+        assert_eq!(parsed("read@0=EIO"), None);
+        assert_eq!(parsed("=EIO"), None);
+        assert_eq!(parsed("read"), None);
+    }
+
+    #[test]
+    fn schedules() {
+        let mut rule = parse(b"read%2=EINTR").unwrap();
+        let fails = (0..5).map(|_| rule.matches()).collect::<Vec<_>>();
+        assert_eq!(fails, [true, false, true, false, true]);
+
+        let mut rule = parse(b"read@2=EIO").unwrap();
+        let fails = (0..3).map(|_| rule.matches()).collect::<Vec<_>>();
+        assert_eq!(fails, [false, true, false]);
+    }
+}
@@ -0,0 +1,519 @@
+//! System calls on i386, which take the number in eax and arguments in ebx, ecx, edx, esi, edi,
+//! and ebp, and return in eax. They go through `int 0x80` rather than the vDSO's
+//! `__kernel_vsyscall`, which works the same on every kernel at the cost of a slower entry.
+//!
+//! The compiler keeps esi and ebp for itself, so the fourth argument is swapped into esi around
+//! the instruction, and with six arguments the fourth and sixth are loaded from memory.
+
+use super::RawSyscallResult;
+use core::arch::asm;
+
+/// The numbers of the system calls veneer uses
+///
+/// The names are those of the calls with the same purpose on the 64-bit targets, so `FSTAT` is
+/// `fstat64`, `NEWFSTATAT` is `fstatat64`, `MMAP` is `mmap2`, which takes the offset in pages, and
+/// `FCNTL` is `fcntl64`.
+pub mod nr {
+    pub const EXIT: usize = 1;
+    pub const READ: usize = 3;
+    pub const WRITE: usize = 4;
+    pub const CLOSE: usize = 6;
+    pub const EXECVE: usize = 11;
+    pub const GETPID: usize = 20;
+    pub const KILL: usize = 37;
+    pub const DUP: usize = 41;
+    pub const BRK: usize = 45;
+    pub const IOCTL: usize = 54;
+    pub const GETTIMEOFDAY: usize = 78;
+    pub const MUNMAP: usize = 91;
+    pub const WAIT4: usize = 114;
+    pub const CLONE: usize = 120;
+    pub const MPROTECT: usize = 125;
+    pub const _LLSEEK: usize = 140;
+    pub const MSYNC: usize = 144;
+    pub const READV: usize = 145;
+    pub const WRITEV: usize = 146;
+    pub const SCHED_YIELD: usize = 158;
+    pub const MREMAP: usize = 163;
+    pub const RT_SIGACTION: usize = 174;
+    pub const RT_SIGPROCMASK: usize = 175;
+    pub const PREAD64: usize = 180;
+    pub const PWRITE64: usize = 181;
+    pub const SIGALTSTACK: usize = 186;
+    pub const MMAP: usize = 192;
+    pub const FSTAT: usize = 197;
+    pub const MINCORE: usize = 218;
+    pub const MADVISE: usize = 219;
+    pub const GETDENTS64: usize = 220;
+    pub const FCNTL: usize = 221;
+    pub const GETTID: usize = 224;
+    pub const FUTEX: usize = 240;
+    pub const SET_THREAD_AREA: usize = 243;
+    pub const EXIT_GROUP: usize = 252;
+    pub const CLOCK_GETTIME: usize = 265;
+    pub const TGKILL: usize = 270;
+    pub const OPENAT: usize = 295;
+    pub const NEWFSTATAT: usize = 300;
+    pub const READLINKAT: usize = 305;
+    pub const FACCESSAT: usize = 307;
+    pub const PPOLL: usize = 309;
+    pub const GETCPU: usize = 318;
+    pub const DUP3: usize = 330;
+    pub const PIPE2: usize = 331;
+    pub const PRLIMIT64: usize = 340;
+    pub const COPY_FILE_RANGE: usize = 377;
+    pub const STATX: usize = 383;
+    pub const SHMGET: usize = 395;
+    pub const PIDFD_OPEN: usize = 434;
+    pub const CLONE3: usize = 435;
+    pub const OPENAT2: usize = 437;
+}
+
+/// Make system call `n` with no arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them.
+#[inline(always)]
+pub unsafe fn syscall0(n: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "int 0x80",
+        inlateout("eax") n => ret,
+        options(nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with one argument
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them.
+#[inline(always)]
+pub unsafe fn syscall1(n: usize, a: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "int 0x80",
+        inlateout("eax") n => ret,
+        in("ebx") a,
+        options(nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with two arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them.
+#[inline(always)]
+pub unsafe fn syscall2(n: usize, a: usize, b: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "int 0x80",
+        inlateout("eax") n => ret,
+        in("ebx") a,
+        in("ecx") b,
+        options(nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with three arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them.
+#[inline(always)]
+pub unsafe fn syscall3(n: usize, a: usize, b: usize, c: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "int 0x80",
+        inlateout("eax") n => ret,
+        in("ebx") a,
+        in("ecx") b,
+        in("edx") c,
+        options(nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with four arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them.
+#[inline(always)]
+pub unsafe fn syscall4(n: usize, a: usize, b: usize, c: usize, d: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "xchg esi, {d}",
+        "int 0x80",
+        "xchg esi, {d}",
+        inlateout("eax") n => ret,
+        in("ebx") a,
+        in("ecx") b,
+        in("edx") c,
+        d = in(reg) d,
+        options(nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with five arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them.
+#[inline(always)]
+pub unsafe fn syscall5(
+    n: usize,
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+    e: usize,
+) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "xchg esi, {d}",
+        "int 0x80",
+        "xchg esi, {d}",
+        inlateout("eax") n => ret,
+        in("ebx") a,
+        in("ecx") b,
+        in("edx") c,
+        d = in(reg) d,
+        in("edi") e,
+        options(nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with six arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them.
+#[inline(always)]
+pub unsafe fn syscall6(
+    n: usize,
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+    e: usize,
+    f: usize,
+) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "push ebp",
+        "push esi",
+        "mov esi, [eax]",
+        "mov ebp, [eax + 4]",
+        "mov eax, [eax + 8]",
+        "int 0x80",
+        "pop esi",
+        "pop ebp",
+        inlateout("eax") [d, f, n].as_ptr() => ret,
+        in("ebx") a,
+        in("ecx") b,
+        in("edx") c,
+        in("edi") e,
+        options(preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with no arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them, and it
+/// must not write to memory.
+#[inline(always)]
+pub unsafe fn syscall0_readonly(n: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "int 0x80",
+        inlateout("eax") n => ret,
+        options(nostack, preserves_flags, readonly),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with one argument
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them, and it
+/// must not write to memory.
+#[inline(always)]
+pub unsafe fn syscall1_readonly(n: usize, a: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "int 0x80",
+        inlateout("eax") n => ret,
+        in("ebx") a,
+        options(nostack, preserves_flags, readonly),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with two arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them, and it
+/// must not write to memory.
+#[inline(always)]
+pub unsafe fn syscall2_readonly(n: usize, a: usize, b: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "int 0x80",
+        inlateout("eax") n => ret,
+        in("ebx") a,
+        in("ecx") b,
+        options(nostack, preserves_flags, readonly),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with three arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them, and it
+/// must not write to memory.
+#[inline(always)]
+pub unsafe fn syscall3_readonly(n: usize, a: usize, b: usize, c: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "int 0x80",
+        inlateout("eax") n => ret,
+        in("ebx") a,
+        in("ecx") b,
+        in("edx") c,
+        options(nostack, preserves_flags, readonly),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with four arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them, and it
+/// must not write to memory.
+#[inline(always)]
+pub unsafe fn syscall4_readonly(
+    n: usize,
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "xchg esi, {d}",
+        "int 0x80",
+        "xchg esi, {d}",
+        inlateout("eax") n => ret,
+        in("ebx") a,
+        in("ecx") b,
+        in("edx") c,
+        d = in(reg) d,
+        options(nostack, preserves_flags, readonly),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with five arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them, and it
+/// must not write to memory.
+#[inline(always)]
+pub unsafe fn syscall5_readonly(
+    n: usize,
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+    e: usize,
+) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "xchg esi, {d}",
+        "int 0x80",
+        "xchg esi, {d}",
+        inlateout("eax") n => ret,
+        in("ebx") a,
+        in("ecx") b,
+        in("edx") c,
+        d = in(reg) d,
+        in("edi") e,
+        options(nostack, preserves_flags, readonly),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with six arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them, and it
+/// must not write to memory.
+#[inline(always)]
+pub unsafe fn syscall6_readonly(
+    n: usize,
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+    e: usize,
+    f: usize,
+) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "push ebp",
+        "push esi",
+        "mov esi, [eax]",
+        "mov ebp, [eax + 4]",
+        "mov eax, [eax + 8]",
+        "int 0x80",
+        "pop esi",
+        "pop ebp",
+        inlateout("eax") [d, f, n].as_ptr() => ret,
+        in("ebx") a,
+        in("ecx") b,
+        in("edx") c,
+        in("edi") e,
+        options(preserves_flags, readonly),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with no arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, and it must not read or write memory.
+#[inline(always)]
+pub unsafe fn syscall0_nomem(n: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "int 0x80",
+        inlateout("eax") n => ret,
+        options(nomem, nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with one argument
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, and it must not read or write memory.
+#[inline(always)]
+pub unsafe fn syscall1_nomem(n: usize, a: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "int 0x80",
+        inlateout("eax") n => ret,
+        in("ebx") a,
+        options(nomem, nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with two arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, and it must not read or write memory.
+#[inline(always)]
+pub unsafe fn syscall2_nomem(n: usize, a: usize, b: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "int 0x80",
+        inlateout("eax") n => ret,
+        in("ebx") a,
+        in("ecx") b,
+        options(nomem, nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with three arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, and it must not read or write memory.
+#[inline(always)]
+pub unsafe fn syscall3_nomem(n: usize, a: usize, b: usize, c: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "int 0x80",
+        inlateout("eax") n => ret,
+        in("ebx") a,
+        in("ecx") b,
+        in("edx") c,
+        options(nomem, nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with four arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, and it must not read or write memory.
+#[inline(always)]
+pub unsafe fn syscall4_nomem(n: usize, a: usize, b: usize, c: usize, d: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "xchg esi, {d}",
+        "int 0x80",
+        "xchg esi, {d}",
+        inlateout("eax") n => ret,
+        in("ebx") a,
+        in("ecx") b,
+        in("edx") c,
+        d = in(reg) d,
+        options(nomem, nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with five arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, and it must not read or write memory.
+#[inline(always)]
+pub unsafe fn syscall5_nomem(
+    n: usize,
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+    e: usize,
+) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "xchg esi, {d}",
+        "int 0x80",
+        "xchg esi, {d}",
+        inlateout("eax") n => ret,
+        in("ebx") a,
+        in("ecx") b,
+        in("edx") c,
+        d = in(reg) d,
+        in("edi") e,
+        options(nomem, nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
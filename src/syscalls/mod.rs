@@ -0,0 +1,1384 @@
+use crate::{
+    fd::{AsRawFd, BorrowedFd, FromRawFd, OwnedFd, RawFd},
+    uapi, CStr, Error,
+};
+use core::{marker::PhantomData, mem};
+use uapi::c_int;
+
+pub mod raw;
+use raw::{syscall, syscall_nomem, syscall_readonly, RawSyscallResult};
+#[cfg(feature = "fault-injection")]
+pub mod fault;
+pub mod probe;
+#[cfg(feature = "trace")]
+pub(crate) mod trace;
+
+/// Make a wrapper's system call by evaluating `$result`, unless the `fault-injection` feature
+/// fails it first, and log the call with its decoded arguments when the `trace` feature is enabled
+///
+/// The form without a result logs a call which does not return, before it is made.
+macro_rules! traced {
+    ($name:ident($($arg:expr),* $(,)?), $result:expr $(,)?) => {{
+        #[cfg(feature = "fault-injection")]
+        let result = match fault::failure(stringify!($name)) {
+            Some(errno) => RawSyscallResult::from_raw((errno as usize).wrapping_neg()),
+            None => $result,
+        };
+        #[cfg(not(feature = "fault-injection"))]
+        let result: RawSyscallResult = $result;
+        #[cfg(feature = "trace")]
+        {
+            static CALLS: trace::Calls = trace::Calls::new(stringify!($name));
+            trace::record(&CALLS, &[$(&$arg as &dyn core::fmt::Debug),*], result);
+        }
+        result
+    }};
+    ($name:ident($($arg:expr),* $(,)?)) => {
+        #[cfg(feature = "trace")]
+        {
+            static CALLS: trace::Calls = trace::Calls::new(stringify!($name));
+            trace::record_start(&CALLS, &[$(&$arg as &dyn core::fmt::Debug),*]);
+        }
+    };
+}
+
+#[inline]
+pub fn read(fd: BorrowedFd<'_>, bytes: &mut [u8]) -> Result<usize, Error> {
+    #[cfg(feature = "fault-injection")]
+    let bytes = {
+        let len = fault::limit("read", bytes.len());
+        &mut bytes[..len]
+    };
+    traced!(read(fd, bytes.len()), unsafe {
+        syscall!(READ, fd.as_raw_fd(), bytes.as_mut_ptr(), bytes.len())
+    })
+    .usize_result()
+}
+
+#[inline]
+pub fn write(fd: BorrowedFd<'_>, bytes: &[u8]) -> Result<usize, Error> {
+    #[cfg(feature = "fault-injection")]
+    let bytes = &bytes[..fault::limit("write", bytes.len())];
+    traced!(write(fd, bytes.len()), unsafe {
+        syscall!(WRITE, fd.as_raw_fd(), bytes.as_ptr(), bytes.len())
+    })
+    .usize_result()
+}
+
+// For directories RDONLY | DIRECTORY | CLOEXEC
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct OpenFlags: c_int {
+        const RDONLY = uapi::O_RDONLY;
+        const WRONLY = uapi::O_WRONLY;
+        const RDWR = uapi::O_RDWR;
+        const APPEND = uapi::O_APPEND;
+        const ASYNC = uapi::O_ASYNC;
+        const CLOEXEC = uapi::O_CLOEXEC;
+        const CREAT = uapi::O_CREAT;
+        const DIRECT = uapi::O_DIRECT;
+        const DIRECTORY = uapi::O_DIRECTORY;
+        const DSYNC = uapi::O_DSYNC;
+        const EXCL = uapi::O_EXCL;
+        const LARGEFILE = uapi::O_LARGEFILE;
+        const NOATIME = uapi::O_NOATIME;
+        const NOCTTY = uapi::O_NOCTTY;
+        const NOFOLLOW = uapi::O_NOFOLLOW;
+        const NONBLOCK = uapi::O_NONBLOCK;
+        const PATH = uapi::O_PATH;
+        const SYNC = uapi::O_SYNC;
+        const TMPFILE = uapi::O_TMPFILE;
+        const TRUNC = uapi::O_TRUNC;
+    }
+}
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct OpenMode: uapi::c_uint {
+        const RWXU = uapi::S_IRWXU;
+        const RUSR = uapi::S_IRUSR;
+        const WUSR = uapi::S_IWUSR;
+        const XUSR = uapi::S_IXUSR;
+        const RWXG = uapi::S_IRWXG;
+        const RGRP = uapi::S_IRGRP;
+        const WGRP = uapi::S_IWGRP;
+        const XGRP = uapi::S_IXGRP;
+        const RWXO = uapi::S_IRWXO;
+        const ROTH = uapi::S_IROTH;
+        const WOTH = uapi::S_IWOTH;
+        const XOTH = uapi::S_IXOTH;
+        const SUID = uapi::S_ISUID;
+        const SGID = uapi::S_ISGID;
+        const SVTX = uapi::S_ISVTX;
+    }
+}
+
+#[inline]
+pub fn openat(
+    at_fd: BorrowedFd<'_>,
+    path: CStr,
+    flags: OpenFlags,
+    mode: OpenMode,
+) -> Result<OwnedFd, Error> {
+    // The kernel only sets this itself on 64-bit targets, and without it a file over 2 GiB cannot
+    // be opened
+    #[cfg(target_pointer_width = "32")]
+    let flags = flags | OpenFlags::LARGEFILE;
+    traced!(openat(at_fd, path, flags, mode), unsafe {
+        syscall_readonly!(
+            OPENAT,
+            at_fd.as_raw_fd(),
+            path.as_ptr(),
+            flags.bits(),
+            mode.bits()
+        )
+    })
+    .to_result_and(|fd| unsafe { OwnedFd::from_raw_fd(fd as RawFd) })
+}
+
+/// Open a file like [`openat`], with the resolution rules and stricter flag checking of `how`
+///
+/// This is missing before Linux 5.6, where [`probe`] records it as unsupported.
+#[inline]
+pub fn openat2(at_fd: BorrowedFd<'_>, path: CStr, how: &uapi::open_how) -> Result<OwnedFd, Error> {
+    traced!(openat2(at_fd, path, how), unsafe {
+        syscall_readonly!(
+            OPENAT2,
+            at_fd.as_raw_fd(),
+            path.as_ptr(),
+            how as *const uapi::open_how,
+            mem::size_of::<uapi::open_how>()
+        )
+    })
+    .to_result_and(|fd| unsafe { OwnedFd::from_raw_fd(fd as RawFd) })
+}
+
+/// Close `fd`, returning the error which dropping it would ignore
+///
+/// The descriptor is released even when this fails, so it must not be closed again.
+#[inline]
+pub fn close(fd: OwnedFd) -> Result<(), Error> {
+    close_raw(crate::fd::IntoRawFd::into_raw_fd(fd))
+}
+
+#[inline]
+pub(crate) fn close_raw(fd: RawFd) -> Result<(), Error> {
+    traced!(close(fd), unsafe { syscall_nomem!(CLOSE, fd) }).null_result()
+}
+
+#[inline]
+pub fn fstat(fd: BorrowedFd<'_>) -> Result<uapi::stat, Error> {
+    unsafe {
+        let mut status: uapi::stat = mem::zeroed();
+        traced!(
+            fstat(fd),
+            syscall!(FSTAT, fd.as_raw_fd(), &mut status as *mut uapi::stat)
+        )
+        .to_result_with(status)
+    }
+}
+
+#[inline]
+pub fn lstat(path: CStr) -> Result<uapi::stat, Error> {
+    unsafe {
+        let mut status: uapi::stat = mem::zeroed();
+        traced!(
+            lstat(path),
+            syscall!(FSTAT, path.as_ptr(), &mut status as *mut uapi::stat)
+        )
+        .to_result_with(status)
+    }
+}
+
+#[inline]
+pub fn ppoll(
+    fds: &mut [uapi::pollfd],
+    timeout: &uapi::timespec,
+    sigmask: &uapi::sigset_t,
+) -> Result<usize, Error> {
+    traced!(ppoll(fds, timeout, sigmask), unsafe {
+        syscall!(
+            PPOLL,
+            fds.as_mut_ptr(),
+            fds.len(),
+            timeout as *const uapi::timespec,
+            sigmask as *const uapi::sigset_t,
+            mem::size_of::<uapi::sigset_t>()
+        )
+    })
+    .usize_result()
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum SeekFrom {
+    Start,
+    End,
+    Current,
+}
+
+#[inline]
+pub fn lseek(fd: BorrowedFd<'_>, seek_mode: SeekFrom, offset: usize) -> Result<usize, Error> {
+    let seek_mode = match seek_mode {
+        SeekFrom::Start => uapi::SEEK_SET,
+        SeekFrom::End => uapi::SEEK_END,
+        SeekFrom::Current => uapi::SEEK_CUR,
+    };
+    #[cfg(not(target_arch = "x86"))]
+    {
+        traced!(lseek(fd, offset, seek_mode), unsafe {
+            syscall_nomem!(LSEEK, fd.as_raw_fd(), offset, seek_mode)
+        })
+        .usize_result()
+    }
+    // i386 only has _llseek, which takes the offset in two registers, high half first, and
+    // writes the 64-bit result to memory
+    #[cfg(target_arch = "x86")]
+    {
+        let mut position: i64 = 0;
+        let high = if (offset as isize) < 0 { usize::MAX } else { 0 };
+        traced!(lseek(fd, offset, seek_mode), unsafe {
+            syscall!(
+                _LLSEEK,
+                fd.as_raw_fd(),
+                high,
+                offset,
+                &mut position as *mut i64,
+                seek_mode
+            )
+        })
+        .to_result_with(position as usize)
+    }
+}
+
+#[inline]
+pub fn mmap(
+    addr: *mut u8,
+    len: usize,
+    prot: i32,
+    flags: i32,
+    fd: Option<BorrowedFd<'_>>,
+    offset: isize,
+) -> Result<*mut u8, Error> {
+    // Anonymous mappings take -1
+    let fd = fd.map_or(-1, |fd| fd.as_raw_fd());
+    // mmap2 on i386 takes the offset in 4096-byte units
+    #[cfg(target_arch = "x86")]
+    let offset = offset / 4096;
+    traced!(mmap(addr, len, prot, flags, fd, offset), unsafe {
+        syscall!(MMAP, addr, len, prot, flags, fd, offset)
+    })
+    .to_result_and(|n| n as *mut u8)
+}
+
+#[inline]
+pub fn mprotect(memory: &[u8], protection: c_int) -> Result<(), Error> {
+    traced!(
+        mprotect(memory.as_ptr(), memory.len(), protection),
+        unsafe { syscall_readonly!(MPROTECT, memory.as_ptr(), memory.len(), protection) }
+    )
+    .null_result()
+}
+
+/// munmap
+///
+/// # Safety
+///
+/// The specified memory region must not be used after this function is called
+#[inline]
+pub unsafe fn munmap(addr: *mut u8, len: usize) -> Result<(), Error> {
+    traced!(munmap(addr, len), syscall!(MUNMAP, addr, len)).null_result()
+}
+
+#[inline]
+pub fn brk(addr: *mut u8) -> Result<*mut u8, Error> {
+    traced!(brk(addr), unsafe { syscall!(BRK, addr) }).to_result_and(|n| n as *mut u8)
+}
+
+/// The kernel's `struct sigaction`
+///
+/// This is not the same as glibc's `sigaction`, which has a 1024-bit signal mask and orders its
+/// fields differently.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SigAction {
+    /// `SIG_DFL`, `SIG_IGN`, or the address of a handler function
+    pub handler: usize,
+    pub flags: uapi::c_ulong,
+    pub restorer: usize,
+    pub mask: uapi::sigset_t,
+}
+
+impl SigAction {
+    pub const SIG_DFL: usize = 0;
+    pub const SIG_IGN: usize = 1;
+}
+
+#[inline]
+pub fn sigaction(
+    signal: c_int,
+    action: &SigAction,
+    old_action: &mut SigAction,
+) -> Result<(), Error> {
+    traced!(sigaction(signal, action), unsafe {
+        syscall!(
+            RT_SIGACTION,
+            signal,
+            action as *const SigAction,
+            old_action as *mut SigAction,
+            mem::size_of::<uapi::sigset_t>()
+        )
+    })
+    .to_result_with(())
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum SigmaskHow {
+    Block = 0,
+    Unblock = 1,
+    SetMask = 2,
+}
+
+/// Change the set of blocked signals, where bit `n - 1` of the mask corresponds to signal `n`
+#[inline]
+pub fn sigprocmask(how: SigmaskHow, set: u64) -> Result<u64, Error> {
+    let mut old = 0u64;
+    traced!(sigprocmask(how, set), unsafe {
+        syscall!(
+            RT_SIGPROCMASK,
+            how as c_int,
+            &set as *const u64,
+            &mut old as *mut u64,
+            mem::size_of::<u64>()
+        )
+    })
+    .to_result_with(old)
+}
+
+/// The kernel's `stack_t`, describing an alternate stack for signal handlers
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SigStack {
+    pub sp: *mut u8,
+    pub flags: c_int,
+    pub size: usize,
+}
+
+impl SigStack {
+    pub const SS_ONSTACK: c_int = 1;
+    pub const SS_DISABLE: c_int = 2;
+}
+
+/// Install `stack` as the alternate signal stack of the calling thread, returning the old one
+#[inline]
+pub fn sigaltstack(stack: &SigStack) -> Result<SigStack, Error> {
+    let mut old = SigStack {
+        sp: core::ptr::null_mut(),
+        flags: 0,
+        size: 0,
+    };
+    traced!(sigaltstack(stack), unsafe {
+        syscall!(
+            SIGALTSTACK,
+            stack as *const SigStack,
+            &mut old as *mut SigStack
+        )
+    })
+    .to_result_with(old)
+}
+
+// sigreturn
+
+#[macro_export]
+macro_rules! ioctl {
+    ($fd:expr, $request:expr, $($arg:expr),*) => {
+        unsafe { syscall!(IOCTL, $fd, $request, $($arg)*) }.usize_result()
+    };
+    ($fd:expr, $request:expr, $($arg:expr),*) => {
+        ioctl!($fd, $request, $($arg)*)
+    };
+}
+
+#[inline]
+pub fn pread64(fd: BorrowedFd<'_>, buf: &mut [u8], offset: usize) -> Result<usize, Error> {
+    #[cfg(feature = "fault-injection")]
+    let buf = {
+        let len = fault::limit("pread64", buf.len());
+        &mut buf[..len]
+    };
+    traced!(pread64(fd, buf.len(), offset), unsafe {
+        #[cfg(not(target_arch = "x86"))]
+        {
+            syscall!(PREAD64, fd.as_raw_fd(), buf.as_mut_ptr(), buf.len(), offset)
+        }
+        // On i386 the 64-bit offset is split into two registers, low half first
+        #[cfg(target_arch = "x86")]
+        {
+            syscall!(
+                PREAD64,
+                fd.as_raw_fd(),
+                buf.as_mut_ptr(),
+                buf.len(),
+                offset,
+                0
+            )
+        }
+    })
+    .usize_result()
+}
+
+#[inline]
+pub fn pwrite64(fd: BorrowedFd<'_>, buf: &[u8], offset: usize) -> Result<usize, Error> {
+    #[cfg(feature = "fault-injection")]
+    let buf = &buf[..fault::limit("pwrite64", buf.len())];
+    traced!(pwrite64(fd, buf.len(), offset), unsafe {
+        #[cfg(not(target_arch = "x86"))]
+        {
+            syscall!(PWRITE64, fd.as_raw_fd(), buf.as_ptr(), buf.len(), offset)
+        }
+        #[cfg(target_arch = "x86")]
+        {
+            syscall!(PWRITE64, fd.as_raw_fd(), buf.as_ptr(), buf.len(), offset, 0)
+        }
+    })
+    .usize_result()
+}
+
+pub struct IoVec<'a> {
+    #[allow(dead_code)]
+    iov_base: *mut u8,
+    #[allow(dead_code)]
+    iov_len: usize,
+    _danny: PhantomData<&'a mut u8>,
+}
+
+#[inline]
+pub fn readv(fd: BorrowedFd<'_>, iovec: &'_ mut [IoVec<'_>]) -> Result<usize, Error> {
+    traced!(readv(fd, iovec.len()), unsafe {
+        syscall!(READV, fd.as_raw_fd(), iovec.as_mut_ptr(), iovec.len())
+    })
+    .usize_result()
+}
+
+#[inline]
+pub fn writev(fd: BorrowedFd<'_>, iovec: &'_ [IoVec<'_>]) -> Result<usize, Error> {
+    traced!(writev(fd, iovec.len()), unsafe {
+        syscall!(WRITEV, fd.as_raw_fd(), iovec.as_ptr(), iovec.len())
+    })
+    .usize_result()
+}
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Mode: c_int {
+        const F_OK = 0;
+        const R_OK = 4;
+        const W_OK = 2;
+        const X_OK = 1;
+    }
+}
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Pipe2Flags: c_int {
+        const CLOEXEC = uapi::O_CLOEXEC;
+        const DIRECT = uapi::O_DIRECT;
+        const NONBLOCK = uapi::O_NONBLOCK;
+    }
+}
+
+/// Copy up to `len` bytes from `fd_in` to `fd_out` without passing them through userspace,
+/// starting at and advancing both files' offsets
+///
+/// This is missing before Linux 4.5, and before 5.3 it fails with `EXDEV` between filesystems.
+#[inline]
+pub fn copy_file_range(
+    fd_in: BorrowedFd<'_>,
+    fd_out: BorrowedFd<'_>,
+    len: usize,
+) -> Result<usize, Error> {
+    traced!(copy_file_range(fd_in, fd_out, len), unsafe {
+        syscall!(
+            COPY_FILE_RANGE,
+            fd_in.as_raw_fd(),
+            0,
+            fd_out.as_raw_fd(),
+            0,
+            len,
+            0
+        )
+    })
+    .usize_result()
+}
+
+/// Make a pipe, returning its read end and then its write end
+#[inline]
+pub fn pipe2(flags: Pipe2Flags) -> Result<(OwnedFd, OwnedFd), Error> {
+    let mut pipefd: [RawFd; 2] = [0, 0];
+    traced!(pipe2(flags), unsafe {
+        syscall!(PIPE2, pipefd.as_mut_ptr(), flags.bits())
+    })
+    .to_result_and(|_| unsafe {
+        (
+            OwnedFd::from_raw_fd(pipefd[0]),
+            OwnedFd::from_raw_fd(pipefd[1]),
+        )
+    })
+}
+
+#[inline]
+pub fn sched_yield() -> Result<(), Error> {
+    traced!(sched_yield(), unsafe { syscall_nomem!(SCHED_YIELD) }).null_result()
+}
+
+#[inline]
+pub fn mremap(
+    old_address: *mut u8,
+    old_size: usize,
+    new_size: usize,
+    flags: c_int,
+) -> Result<*mut u8, Error> {
+    traced!(mremap(old_address, old_size, new_size, flags), unsafe {
+        syscall!(MREMAP, old_address, old_size, new_size, flags)
+    })
+    .to_result_and(|n| n as *mut u8)
+}
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct MSync: c_int {
+        const ASYNC = uapi::MS_ASYNC;
+        const SYNC = uapi::MS_SYNC;
+        const INVALIDATE = uapi::MS_INVALIDATE;
+    }
+}
+
+#[inline]
+pub fn msync(memory: &[u8], flags: MSync) -> Result<(), Error> {
+    traced!(msync(memory.as_ptr(), memory.len(), flags), unsafe {
+        syscall_readonly!(MSYNC, memory.as_ptr(), memory.len(), flags.bits())
+    })
+    .null_result()
+}
+
+#[inline]
+pub fn mincore(memory: &[u8], status: &mut [u8]) -> Result<(), Error> {
+    if status.len() < memory.len().div_ceil(crate::env::page_size()) {
+        return Err(Error(uapi::EINVAL));
+    }
+    traced!(mincore(memory.as_ptr(), memory.len()), unsafe {
+        syscall!(MINCORE, memory.as_ptr(), memory.len(), status.as_mut_ptr())
+    })
+    .null_result()
+}
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Advice: c_int {
+        const NORMAL = uapi::MADV_NORMAL;
+        const RANDOM = uapi::MADV_RANDOM;
+        const SEQUENTIAL = uapi::MADV_SEQUENTIAL;
+        const WILLNEED = uapi::MADV_WILLNEED;
+        const DONTNEED = uapi::MADV_DONTNEED;
+        const REMOVE = uapi::MADV_REMOVE;
+        const DONTFORM = uapi::MADV_DONTFORK;
+        const DOFORK = uapi::MADV_DOFORK;
+        const HWPOISON = uapi::MADV_HWPOISON;
+        const MERGEABLE = uapi::MADV_MERGEABLE;
+        const UNMERGEABLE = uapi::MADV_UNMERGEABLE;
+        const SOFT_OFFLINE = uapi::MADV_SOFT_OFFLINE;
+        const HUGEPAGE = uapi::MADV_HUGEPAGE;
+        const NOHUGEPAGE = uapi::MADV_NOHUGEPAGE;
+        const DONTDUMP = uapi::MADV_DONTDUMP;
+        const DODUMP = uapi::MADV_DODUMP;
+        const FREE = uapi::MADV_FREE;
+        //const WIPEONFORK = uapi::MADV_WIPEONFORK;
+        //const KEEPONFORK = uapi::MADV_KEEPONFORK;
+    }
+}
+
+#[inline]
+pub fn madvise(memory: &[u8], advice: Advice) -> Result<(), Error> {
+    traced!(madvise(memory.as_ptr(), memory.len(), advice), unsafe {
+        syscall!(MADVISE, memory.as_ptr(), memory.len(), advice.bits())
+    })
+    .null_result()
+}
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct ShmFlags: c_int {
+        const CREAT = uapi::IPC_CREAT;
+        const EXCL = uapi::IPC_EXCL;
+        const HUGETLB = uapi::SHM_HUGETLB;
+        const HUGE_2MB = uapi::MAP_HUGE_2MB;
+        const HUGE_1GB = uapi::MAP_HUGE_1GB;
+        const NORESERVE = uapi::SHM_NORESERVE;
+    }
+}
+
+#[inline]
+pub fn shmget(key: uapi::key_t, size: usize, shmflg: ShmFlags) -> Result<uapi::key_t, Error> {
+    traced!(shmget(key, size, shmflg), unsafe {
+        syscall!(SHMGET, key, size, shmflg.bits())
+    })
+    .to_result_and(|key| key as uapi::key_t)
+}
+
+// shmctl
+
+/// Make a new descriptor for the same open file as `fd`, without `CLOEXEC`
+///
+/// [`fcntl_dupfd_cloexec`] is usually what is wanted instead, so that the copy is not inherited by
+/// programs this one runs.
+#[inline]
+pub fn dup(fd: BorrowedFd<'_>) -> Result<OwnedFd, Error> {
+    traced!(dup(fd), unsafe { syscall_nomem!(DUP, fd.as_raw_fd()) })
+        .to_result_and(|fd| unsafe { OwnedFd::from_raw_fd(fd as RawFd) })
+}
+
+// dup2
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct DupFlags: c_int {
+        const CLOEXEC = uapi::O_CLOEXEC;
+    }
+}
+
+/// Make `new_fd` refer to the same file as `old_fd`, closing whatever `new_fd` referred to
+///
+/// Unlike `dup2`, which aarch64 does not have, this fails with `EINVAL` if the two are equal.
+/// `new_fd` is borrowed because it stays owned by whatever owned it before, such as
+/// [`fd::STDOUT`](crate::fd::STDOUT).
+#[inline]
+pub fn dup3(old_fd: BorrowedFd<'_>, new_fd: BorrowedFd<'_>, flags: DupFlags) -> Result<(), Error> {
+    traced!(dup3(old_fd, new_fd, flags), unsafe {
+        syscall_nomem!(DUP3, old_fd.as_raw_fd(), new_fd.as_raw_fd(), flags.bits())
+    })
+    .null_result()
+}
+
+bitflags::bitflags! {
+    /// The flags of a descriptor itself, rather than of the open file it refers to
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct FdFlags: c_int {
+        const CLOEXEC = uapi::FD_CLOEXEC;
+    }
+}
+
+#[inline]
+pub fn fcntl_getfd(fd: BorrowedFd<'_>) -> Result<FdFlags, Error> {
+    traced!(fcntl(fd, uapi::F_GETFD), unsafe {
+        syscall_nomem!(FCNTL, fd.as_raw_fd(), uapi::F_GETFD)
+    })
+    .to_result_and(|flags| FdFlags::from_bits_retain(flags as c_int))
+}
+
+#[inline]
+pub fn fcntl_setfd(fd: BorrowedFd<'_>, flags: FdFlags) -> Result<(), Error> {
+    traced!(fcntl(fd, uapi::F_SETFD, flags), unsafe {
+        syscall_nomem!(FCNTL, fd.as_raw_fd(), uapi::F_SETFD, flags.bits())
+    })
+    .null_result()
+}
+
+/// Get the access mode and status flags of the open file `fd` refers to
+#[inline]
+pub fn fcntl_getfl(fd: BorrowedFd<'_>) -> Result<OpenFlags, Error> {
+    traced!(fcntl(fd, uapi::F_GETFL), unsafe {
+        syscall_nomem!(FCNTL, fd.as_raw_fd(), uapi::F_GETFL)
+    })
+    .to_result_and(|flags| OpenFlags::from_bits_retain(flags as c_int))
+}
+
+/// Set the status flags of the open file `fd` refers to
+///
+/// Only `APPEND`, `ASYNC`, `DIRECT`, `NOATIME`, and `NONBLOCK` can be changed, and the kernel
+/// ignores the rest.
+#[inline]
+pub fn fcntl_setfl(fd: BorrowedFd<'_>, flags: OpenFlags) -> Result<(), Error> {
+    traced!(fcntl(fd, uapi::F_SETFL, flags), unsafe {
+        syscall_nomem!(FCNTL, fd.as_raw_fd(), uapi::F_SETFL, flags.bits())
+    })
+    .null_result()
+}
+
+/// Make a new descriptor for the same open file as `fd`, with `CLOEXEC` set, using the lowest
+/// number that is at least `min`
+#[inline]
+pub fn fcntl_dupfd_cloexec(fd: BorrowedFd<'_>, min: RawFd) -> Result<OwnedFd, Error> {
+    traced!(fcntl(fd, uapi::F_DUPFD_CLOEXEC, min), unsafe {
+        syscall_nomem!(FCNTL, fd.as_raw_fd(), uapi::F_DUPFD_CLOEXEC, min)
+    })
+    .to_result_and(|fd| unsafe { OwnedFd::from_raw_fd(fd as RawFd) })
+}
+
+/// The capacity of the pipe `fd` refers to, in bytes
+#[inline]
+pub fn fcntl_getpipe_sz(fd: BorrowedFd<'_>) -> Result<usize, Error> {
+    traced!(fcntl(fd, uapi::F_GETPIPE_SZ), unsafe {
+        syscall_nomem!(FCNTL, fd.as_raw_fd(), uapi::F_GETPIPE_SZ)
+    })
+    .usize_result()
+}
+
+/// Resize the pipe `fd` refers to, returning the capacity it was given, which is `size` rounded up
+/// to a power-of-two number of pages
+///
+/// Without `CAP_SYS_RESOURCE` the size is limited by `/proc/sys/fs/pipe-max-size`.
+#[inline]
+pub fn fcntl_setpipe_sz(fd: BorrowedFd<'_>, size: usize) -> Result<usize, Error> {
+    traced!(fcntl(fd, uapi::F_SETPIPE_SZ, size), unsafe {
+        syscall_nomem!(FCNTL, fd.as_raw_fd(), uapi::F_SETPIPE_SZ, size)
+    })
+    .usize_result()
+}
+
+//
+// pause
+//
+// nanosleep
+//
+// getitimer
+//
+// alarm
+//
+// setitimer
+
+#[inline]
+pub fn getpid() -> uapi::pid_t {
+    traced!(getpid(), unsafe { syscall_nomem!(GETPID) }).raw() as uapi::pid_t
+}
+
+#[inline]
+pub fn gettid() -> uapi::pid_t {
+    traced!(gettid(), unsafe { syscall_nomem!(GETTID) }).raw() as uapi::pid_t
+}
+
+// sendfile
+//
+// socket
+//
+// connect
+//
+// accept
+//
+// sendto
+//
+// recvfrom
+//
+// sendmsg
+//
+// recvmsg
+//
+// shutdown
+//
+// bind
+//
+// listen
+//
+// getsockname
+//
+// getpeername
+//
+// socketpair
+//
+// setsockopt
+//
+// getsockopt
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct CloneFlags: i32 {
+        const VM = uapi::CLONE_VM;
+        const FS = uapi::CLONE_FS;
+        const FILES = uapi::CLONE_FILES;
+        const SIGHAND = uapi::CLONE_SIGHAND;
+        const THREAD = uapi::CLONE_THREAD;
+        const SYSVSEM = uapi::CLONE_SYSVSEM;
+        const SETTLS = uapi::CLONE_SETTLS;
+        const PARENT_SETTID = uapi::CLONE_PARENT_SETTID;
+        const CHILD_CLEARTID = uapi::CLONE_CHILD_CLEARTID;
+    }
+}
+
+/// # Safety
+///
+/// The memory pointed to by stack is given up to the thread and should be part of its own mapping
+#[inline]
+pub unsafe fn clone(
+    flags: CloneFlags,
+    stack: *mut u8,
+    parent_tid: &mut uapi::pid_t,
+    child_tid: &mut uapi::pid_t,
+    tls: &mut u8,
+) -> Result<uapi::pid_t, Error> {
+    traced!(
+        clone(flags, stack),
+        syscall!(
+            CLONE,
+            flags.bits(),
+            stack,
+            parent_tid as *mut uapi::pid_t,
+            tls as *mut u8,
+            child_tid as *mut uapi::pid_t
+        )
+    )
+    .to_result_and(|v| v as uapi::pid_t)
+}
+
+/// Create a process or thread as described by `args`, returning the child's tid in the parent
+/// and 0 in the child
+///
+/// This is missing before Linux 5.3.
+///
+/// # Safety
+///
+/// As with [`clone`], a stack in `args` is given up to the child, and a child which shares the
+/// address space must not return into the parent's frames
+#[inline]
+pub unsafe fn clone3(args: &mut uapi::clone_args) -> Result<uapi::pid_t, Error> {
+    traced!(
+        clone3(args),
+        syscall!(
+            CLONE3,
+            args as *mut uapi::clone_args,
+            mem::size_of::<uapi::clone_args>()
+        )
+    )
+    .to_result_and(|v| v as uapi::pid_t)
+}
+
+/// Create a child process which is a copy of this one, returning its pid in the parent and 0 in
+/// the child
+///
+/// Only the calling thread is copied into the child.
+#[inline]
+pub fn fork() -> Result<uapi::pid_t, Error> {
+    let mut args = uapi::clone_args {
+        exit_signal: uapi::SIGCHLD as u64,
+        ..Default::default()
+    };
+    match probe::attempt(probe::Capability::Clone3, || unsafe { clone3(&mut args) }) {
+        Err(Error(uapi::ENOSYS)) => {}
+        result => return result,
+    }
+    // aarch64 has no fork, but a clone with no flags other than the signal to send the parent on
+    // exit does the same thing
+    traced!(fork(), unsafe {
+        syscall!(CLONE, uapi::SIGCHLD, 0, 0, 0, 0)
+    })
+    .to_result_and(|pid| pid as uapi::pid_t)
+}
+
+/// Replace the current process image, only returning if that fails
+///
+/// # Safety
+///
+/// `argv` and `envp` must point to null-terminated arrays of pointers to null-terminated strings,
+/// such as the one returned by [`Environment::envp`](crate::env::Environment::envp)
+#[inline]
+pub unsafe fn execve(path: CStr, argv: *const *const u8, envp: *const *const u8) -> Error {
+    match traced!(
+        execve(path, argv, envp),
+        syscall!(EXECVE, path.as_ptr(), argv, envp)
+    )
+    .null_result()
+    {
+        Ok(()) => core::hint::unreachable_unchecked(),
+        Err(e) => e,
+    }
+}
+
+/// Terminate the calling thread
+///
+/// Other threads keep running. To end the whole process use [`exit_group`], or
+/// [`process::exit`](crate::process::exit) which also runs exit hooks.
+#[inline]
+pub fn exit(error_code: c_int) -> ! {
+    unsafe {
+        traced!(exit(error_code));
+        syscall_readonly!(EXIT, error_code);
+        core::hint::unreachable_unchecked();
+    }
+}
+
+/// Terminate every thread in the process
+#[inline]
+pub fn exit_group(error_code: c_int) -> ! {
+    unsafe {
+        traced!(exit_group(error_code));
+        syscall_readonly!(EXIT_GROUP, error_code);
+        core::hint::unreachable_unchecked();
+    }
+}
+
+/// Wait for a child process to change state, returning its pid and its wait status
+///
+/// `pid` selects which children to wait for the same way as in `waitpid`, such as -1 for any
+/// child.
+#[inline]
+pub fn wait4(pid: uapi::pid_t, options: c_int) -> Result<(uapi::pid_t, c_int), Error> {
+    let mut status: c_int = 0;
+    traced!(wait4(pid, options), unsafe {
+        syscall!(
+            WAIT4,
+            pid,
+            &mut status as *mut c_int,
+            options,
+            core::ptr::null_mut::<u8>()
+        )
+    })
+    .to_result_and(|pid| (pid as uapi::pid_t, status))
+}
+
+// Require that it is non-negative
+pub struct Pid(pub uapi::pid_t);
+
+pub enum SignalWhere {
+    Exactly(usize),
+    CurrentGroup,
+    AllValid,
+    Group(usize),
+}
+#[inline]
+pub fn kill(pid: usize, signal: i32) -> Result<(), Error> {
+    traced!(kill(pid, signal), unsafe { syscall!(KILL, pid, signal) }).null_result()
+}
+
+/// Send a signal to exactly one thread
+#[inline]
+pub fn tgkill(tgid: uapi::pid_t, tid: uapi::pid_t, signal: c_int) -> Result<(), Error> {
+    traced!(tgkill(tgid, tid, signal), unsafe {
+        syscall!(TGKILL, tgid, tid, signal)
+    })
+    .null_result()
+}
+
+/// Get a file descriptor which refers to the process `pid`, which becomes readable when it exits
+///
+/// This is missing before Linux 5.3.
+#[inline]
+pub fn pidfd_open(pid: uapi::pid_t, flags: uapi::c_uint) -> Result<OwnedFd, Error> {
+    traced!(pidfd_open(pid, flags), unsafe {
+        syscall_nomem!(PIDFD_OPEN, pid, flags)
+    })
+    .to_result_and(|fd| unsafe { OwnedFd::from_raw_fd(fd as RawFd) })
+}
+
+// uname
+
+pub enum FutexOp<'a> {
+    Wait {
+        expected: c_int,
+        timeout: Option<uapi::timespec>,
+    },
+    Wake {
+        wake_at_most: c_int,
+    },
+    Requeue {
+        wake_at_most: c_int,
+        requeue_onto: &'a mut c_int,
+        max_to_requeue: c_int,
+    },
+}
+
+#[inline]
+pub fn futex(lock: &mut c_int, op: FutexOp<'_>, private: bool) -> Result<(), Error> {
+    let lock = lock as *mut c_int;
+    let private = if private { uapi::FUTEX_PRIVATE_FLAG } else { 0 };
+    let wait_timeout;
+    // The fourth argument is a timeout for some operations and a number for others
+    let (op, value, timeout_or_value, other_lock) = match op {
+        FutexOp::Wait { expected, timeout } => {
+            wait_timeout = timeout;
+            let timeout = wait_timeout
+                .as_ref()
+                .map_or(core::ptr::null(), |t| t as *const uapi::timespec);
+            (
+                uapi::FUTEX_WAIT,
+                expected,
+                timeout as usize,
+                core::ptr::null_mut(),
+            )
+        }
+        FutexOp::Wake { wake_at_most } => {
+            (uapi::FUTEX_WAKE, wake_at_most, 0, core::ptr::null_mut())
+        }
+        FutexOp::Requeue {
+            wake_at_most,
+            requeue_onto,
+            max_to_requeue,
+        } => (
+            uapi::FUTEX_REQUEUE,
+            wake_at_most,
+            max_to_requeue as usize,
+            requeue_onto as *mut c_int,
+        ),
+    };
+    traced!(futex(lock, op | private, value), unsafe {
+        syscall!(
+            FUTEX,
+            lock,
+            op | private,
+            value,
+            timeout_or_value,
+            other_lock
+        )
+    })
+    .null_result()
+}
+
+#[inline]
+pub fn fstatat(fd: BorrowedFd<'_>, name: CStr) -> Result<uapi::stat, Error> {
+    unsafe {
+        let mut stats = mem::zeroed();
+        traced!(
+            fstatat(fd, name),
+            syscall!(
+                NEWFSTATAT,
+                fd.as_raw_fd(),
+                name.as_ptr(),
+                &mut stats as *mut uapi::stat,
+                0
+            )
+        )
+        .to_result_with(stats)
+    }
+}
+
+#[inline]
+pub fn lstatat(fd: BorrowedFd<'_>, name: CStr) -> Result<uapi::stat, Error> {
+    unsafe {
+        let mut stats = mem::zeroed();
+        traced!(
+            lstatat(fd, name),
+            syscall!(
+                NEWFSTATAT,
+                fd.as_raw_fd(),
+                name.as_ptr(),
+                &mut stats as *mut uapi::stat,
+                uapi::AT_SYMLINK_NOFOLLOW
+            )
+        )
+        .to_result_with(stats)
+    }
+}
+
+/// Get the fields of `mask` which the filesystem can provide for `path`, such as `STATX_BTIME`
+///
+/// This is missing before Linux 4.11.
+#[inline]
+pub fn statx(
+    fd: BorrowedFd<'_>,
+    path: CStr,
+    flags: c_int,
+    mask: uapi::c_uint,
+) -> Result<uapi::statx, Error> {
+    let mut stats = uapi::statx::default();
+    traced!(statx(fd, path, flags, mask), unsafe {
+        syscall!(
+            STATX,
+            fd.as_raw_fd(),
+            path.as_ptr(),
+            flags,
+            mask,
+            &mut stats as *mut uapi::statx
+        )
+    })
+    .to_result_with(stats)
+}
+
+#[inline]
+pub fn getdents64(fd: BorrowedFd<'_>, buf: &mut [u8]) -> Result<usize, Error> {
+    #[cfg(feature = "fault-injection")]
+    let buf = {
+        let len = fault::limit("getdents64", buf.len());
+        &mut buf[..len]
+    };
+    traced!(getdents64(fd, buf.len()), unsafe {
+        syscall!(GETDENTS64, fd.as_raw_fd(), buf.as_mut_ptr(), buf.len())
+    })
+    .to_result_and(|n| n)
+}
+
+#[inline]
+pub fn faccessat(fd: BorrowedFd<'_>, name: CStr, mode: c_int) -> Result<(), Error> {
+    traced!(faccessat(fd, name, mode), unsafe {
+        syscall_readonly!(FACCESSAT, fd.as_raw_fd(), name.as_ptr(), mode)
+    })
+    .null_result()
+}
+
+#[inline]
+pub fn readlinkat<'a>(
+    fd: BorrowedFd<'_>,
+    name: CStr,
+    buf: &'a mut [u8],
+) -> Result<&'a [u8], Error> {
+    match traced!(readlinkat(fd, name, buf.len()), unsafe {
+        syscall!(
+            READLINKAT,
+            fd.as_raw_fd(),
+            name.as_ptr(),
+            buf.as_mut_ptr(),
+            buf.len()
+        )
+    })
+    .to_result_and(|n| n)
+    {
+        Ok(n) => Ok(buf.get(..n).unwrap_or_default()),
+        Err(e) => Err(e),
+    }
+}
+
+#[inline]
+pub fn gettimeofday() -> Result<uapi::timeval, Error> {
+    let mut tv = uapi::timeval {
+        tv_sec: 0,
+        tv_usec: 0,
+    };
+    if let Some(vdso) = crate::vdso::gettimeofday() {
+        return RawSyscallResult::from_raw(unsafe { vdso(&mut tv, core::ptr::null_mut()) } as usize)
+            .to_result_with(tv);
+    }
+    traced!(gettimeofday(), unsafe {
+        syscall!(GETTIMEOFDAY, &mut tv as *mut uapi::timeval, 0)
+    })
+    .to_result_with(tv)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClockId {
+    Realtime = 0,
+    Monotonic = 1,
+    ProcessCputime = 2,
+    ThreadCputime = 3,
+    MonotonicRaw = 4,
+    RealtimeCoarse = 5,
+    MonotonicCoarse = 6,
+    Boottime = 7,
+}
+
+#[inline]
+pub fn clock_gettime(clock: ClockId) -> Result<uapi::timespec, Error> {
+    let mut ts = uapi::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    if let Some(vdso) = crate::vdso::clock_gettime() {
+        return RawSyscallResult::from_raw(unsafe { vdso(clock as c_int, &mut ts) } as usize)
+            .to_result_with(ts);
+    }
+    traced!(clock_gettime(clock), unsafe {
+        syscall!(
+            CLOCK_GETTIME,
+            clock as c_int,
+            &mut ts as *mut uapi::timespec
+        )
+    })
+    .to_result_with(ts)
+}
+
+/// Seconds since the Unix epoch
+#[inline]
+pub fn time() -> Result<uapi::time_t, Error> {
+    if let Some(vdso) = crate::vdso::time() {
+        return RawSyscallResult::from_raw(unsafe { vdso(core::ptr::null_mut()) } as usize)
+            .to_result_and(|t| t as uapi::time_t);
+    }
+    clock_gettime(ClockId::Realtime).map(|ts| ts.tv_sec)
+}
+
+/// The CPU and NUMA node the calling thread is running on
+#[inline]
+pub fn getcpu() -> Result<(u32, u32), Error> {
+    let mut cpu = 0u32;
+    let mut node = 0u32;
+    if let Some(vdso) = crate::vdso::getcpu() {
+        return RawSyscallResult::from_raw(unsafe {
+            vdso(&mut cpu, &mut node, core::ptr::null_mut())
+        } as usize)
+        .to_result_with((cpu, node));
+    }
+    traced!(getcpu(), unsafe {
+        syscall!(
+            GETCPU,
+            &mut cpu as *mut u32,
+            &mut node as *mut u32,
+            core::ptr::null_mut::<u8>()
+        )
+    })
+    .to_result_with((cpu, node))
+}
+
+/// Set architecture-specific thread state, such as the `fs` base address with `ARCH_SET_FS`
+///
+/// # Safety
+///
+/// Changing the thread pointer invalidates every thread-local variable of the calling thread
+#[cfg(target_arch = "x86_64")]
+#[inline]
+pub unsafe fn arch_prctl(code: c_int, address: usize) -> Result<(), Error> {
+    traced!(
+        arch_prctl(code, address as *const u8),
+        syscall!(ARCH_PRCTL, code, address)
+    )
+    .null_result()
+}
+
+/// Install `desc` as a thread-local storage segment, filling in `entry_number` if it is -1
+///
+/// # Safety
+///
+/// Changing the segment the thread pointer is in invalidates every thread-local variable of the
+/// calling thread
+#[cfg(target_arch = "x86")]
+#[inline]
+pub unsafe fn set_thread_area(desc: &mut uapi::user_desc) -> Result<(), Error> {
+    traced!(
+        set_thread_area(desc.entry_number, desc.base_addr),
+        syscall!(SET_THREAD_AREA, desc as *mut uapi::user_desc)
+    )
+    .null_result()
+}
+
+/// Read the soft and hard limits on a resource of the calling process
+#[inline]
+pub fn getrlimit(resource: c_int) -> Result<uapi::rlimit64, Error> {
+    let mut limit = uapi::rlimit64 {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    traced!(getrlimit(resource), unsafe {
+        syscall!(
+            PRLIMIT64,
+            0,
+            resource,
+            core::ptr::null::<uapi::rlimit64>(),
+            &mut limit as *mut uapi::rlimit64
+        )
+    })
+    .to_result_with(limit)
+}
+
+#[inline]
+pub fn winsize() -> Result<uapi::winsize, Error> {
+    unsafe {
+        let mut winsize: uapi::winsize = mem::zeroed();
+        traced!(
+            winsize(),
+            syscall!(
+                IOCTL,
+                uapi::STDOUT_FILENO,
+                uapi::TIOCGWINSZ,
+                &mut winsize as *mut uapi::winsize
+            )
+        )
+        .to_result_with(winsize)
+    }
+}
+
+trait SyscallRet: Sized {
+    fn to_result_with<T>(self, t: T) -> Result<T, Error>;
+    fn to_result_and<T, F>(self, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(usize) -> T;
+
+    fn usize_result(self) -> Result<usize, Error>;
+
+    #[inline]
+    fn null_result(self) -> Result<(), Error> {
+        self.to_result_with(())
+    }
+}
+
+impl SyscallRet for RawSyscallResult {
+    #[inline]
+    fn to_result_with<T>(self, t: T) -> Result<T, Error> {
+        self.to_result().map(|_| t)
+    }
+
+    #[inline]
+    fn to_result_and<T, F>(self, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(usize) -> T,
+    {
+        self.to_result().map(f)
+    }
+
+    #[inline]
+    fn usize_result(self) -> Result<usize, Error> {
+        self.to_result()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clocks_agree_with_std() {
+        crate::env::capture_for_test();
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as uapi::time_t;
+        assert!((clock_gettime(ClockId::Realtime).unwrap().tv_sec - now).abs() <= 1);
+        assert!((gettimeofday().unwrap().tv_sec - now).abs() <= 1);
+        assert!((time().unwrap() - now).abs() <= 1);
+
+        let before = clock_gettime(ClockId::Monotonic).unwrap();
+        let after = clock_gettime(ClockId::Monotonic).unwrap();
+        assert!((after.tv_sec, after.tv_nsec) >= (before.tv_sec, before.tv_nsec));
+
+        let (cpu, _node) = getcpu().unwrap();
+        assert!(cpu < 4096);
+    }
+
+    #[test]
+    fn sigaction_roundtrip() {
+        let ignore = SigAction {
+            handler: SigAction::SIG_IGN,
+            ..SigAction::default()
+        };
+        let mut old = SigAction::default();
+        sigaction(uapi::SIGUSR2, &ignore, &mut old).unwrap();
+        let mut current = SigAction::default();
+        sigaction(uapi::SIGUSR2, &old, &mut current).unwrap();
+        assert_eq!(current.handler, SigAction::SIG_IGN);
+    }
+
+    #[test]
+    fn stack_limit() {
+        let limit = getrlimit(uapi::RLIMIT_STACK).unwrap();
+        let mut expected = libc::rlimit64 {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        assert_eq!(
+            unsafe { libc::getrlimit64(libc::RLIMIT_STACK, &mut expected) },
+            0
+        );
+        assert_eq!(limit.rlim_cur, expected.rlim_cur);
+        assert_eq!(limit.rlim_max, expected.rlim_max);
+    }
+
+    #[test]
+    fn fork_and_wait() {
+        let pid = fork().unwrap();
+        if pid == 0 {
+            exit_group(3);
+        }
+        let (waited, status) = wait4(pid, 0).unwrap();
+        assert_eq!(waited, pid);
+        assert!(uapi::WIFEXITED(status));
+        assert_eq!(uapi::WEXITSTATUS(status), 3);
+    }
+
+    #[test]
+    fn tids() {
+        assert_eq!(getpid(), std::process::id() as uapi::pid_t);
+        assert_eq!(gettid(), unsafe { libc::gettid() });
+    }
+}
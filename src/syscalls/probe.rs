@@ -0,0 +1,205 @@
+//! Which of the system calls added by newer kernels are available, so that the APIs built on them
+//! can fall back to older ones
+//!
+//! The first time one of these APIs needs a [`Capability`], it makes the call. If the kernel
+//! answers `ENOSYS`, or `EINVAL` on that first call, the capability is recorded as unsupported and
+//! the API falls back, as do all later calls:
+//!
+//! * [`fs::metadata`](crate::fs::metadata) from `statx` to `newfstatat`, without a birth time
+//! * [`fork`](super::fork) from `clone3` to `clone`
+//! * [`Directory::open_file`](crate::fs::Directory::open_file) from `openat2` to `openat`
+//! * [`fs::copy`](crate::fs::copy) from `copy_file_range` to `read` and `write`
+//! * [`process::wait_timeout`](crate::process::wait_timeout) from polling a pidfd from
+//!   `pidfd_open` to checking with `wait4` at growing intervals
+//!
+//! An `EINVAL` from bad arguments on the first call is mistaken for a missing system call, which
+//! only costs the faster path since the fallback then reports the error itself. Tools which print
+//! what the kernel supports can probe every capability with [`report`].
+
+use super::raw::syscall_readonly;
+use crate::{uapi, Error};
+use core::sync::atomic::{AtomicU8, Ordering};
+
+/// A system call which older kernels do not have
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Capability {
+    /// `statx`, from Linux 4.11
+    Statx,
+    /// `clone3`, from Linux 5.3
+    Clone3,
+    /// `openat2`, from Linux 5.6
+    Openat2,
+    /// `copy_file_range`, from Linux 4.5
+    CopyFileRange,
+    /// `pidfd_open`, from Linux 5.3
+    PidfdOpen,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 5] = [
+        Capability::Statx,
+        Capability::Clone3,
+        Capability::Openat2,
+        Capability::CopyFileRange,
+        Capability::PidfdOpen,
+    ];
+
+    /// The name of the system call
+    #[inline]
+    pub fn syscall(self) -> &'static str {
+        match self {
+            Capability::Statx => "statx",
+            Capability::Clone3 => "clone3",
+            Capability::Openat2 => "openat2",
+            Capability::CopyFileRange => "copy_file_range",
+            Capability::PidfdOpen => "pidfd_open",
+        }
+    }
+}
+
+/// What is known about a [`Capability`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Support {
+    /// Nothing has needed it yet
+    Unknown,
+    Supported,
+    Unsupported,
+}
+
+const UNKNOWN: u8 = 0;
+const SUPPORTED: u8 = 1;
+const UNSUPPORTED: u8 = 2;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NOT_PROBED: AtomicU8 = AtomicU8::new(UNKNOWN);
+static STATES: [AtomicU8; 5] = [NOT_PROBED; 5];
+
+/// What is known about `capability`, without probing it
+#[inline]
+pub fn support(capability: Capability) -> Support {
+    match STATES[capability as usize].load(Ordering::Relaxed) {
+        SUPPORTED => Support::Supported,
+        UNSUPPORTED => Support::Unsupported,
+        _ => Support::Unknown,
+    }
+}
+
+/// Make the APIs which use `capability` fall back as if the kernel did not have it, such as to
+/// test the fallbacks on a newer kernel
+#[inline]
+pub fn disable(capability: Capability) {
+    STATES[capability as usize].store(UNSUPPORTED, Ordering::Relaxed);
+}
+
+/// Whether `capability` is supported, probing it with a call that has no effect if that is not
+/// yet known
+#[inline]
+pub fn detect(capability: Capability) -> bool {
+    let state = &STATES[capability as usize];
+    if state.load(Ordering::Relaxed) == UNKNOWN {
+        // Each of these fails on its arguments before doing anything, if the call exists at all
+        let result = unsafe {
+            match capability {
+                Capability::Statx => syscall_readonly!(STATX, -1i32, 0, 0, 0, 0),
+                Capability::Clone3 => syscall_readonly!(CLONE3, 0, 0),
+                Capability::Openat2 => syscall_readonly!(OPENAT2, -1i32, 0, 0, 0),
+                Capability::CopyFileRange => {
+                    syscall_readonly!(COPY_FILE_RANGE, -1i32, 0, -1i32, 0, 0, 0)
+                }
+                Capability::PidfdOpen => syscall_readonly!(PIDFD_OPEN, -1i32, 0),
+            }
+        };
+        let supported = !matches!(result.to_result(), Err(Error(uapi::ENOSYS)));
+        // A racing call which already decided wins
+        let _ = state.compare_exchange(
+            UNKNOWN,
+            if supported { SUPPORTED } else { UNSUPPORTED },
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+        );
+    }
+    state.load(Ordering::Relaxed) == SUPPORTED
+}
+
+/// Probe every capability, for tools which print what the kernel supports
+#[inline]
+pub fn report() -> [(Capability, Support); 5] {
+    Capability::ALL.map(|capability| {
+        detect(capability);
+        (capability, support(capability))
+    })
+}
+
+/// Make `call` unless `capability` is known to be unsupported, returning `ENOSYS` if it is or if
+/// this call finds out that it is
+pub(crate) fn attempt<T>(
+    capability: Capability,
+    call: impl FnOnce() -> Result<T, Error>,
+) -> Result<T, Error> {
+    attempt_with(&STATES[capability as usize], call)
+}
+
+fn attempt_with<T>(state: &AtomicU8, call: impl FnOnce() -> Result<T, Error>) -> Result<T, Error> {
+    match state.load(Ordering::Relaxed) {
+        UNSUPPORTED => Err(Error(uapi::ENOSYS)),
+        SUPPORTED => call(),
+        _ => match call() {
+            Err(Error(uapi::ENOSYS | uapi::EINVAL)) => {
+                state.store(UNSUPPORTED, Ordering::Relaxed);
+                Err(Error(uapi::ENOSYS))
+            }
+            result => {
+                if result.is_ok() {
+                    state.store(SUPPORTED, Ordering::Relaxed);
+                }
+                result
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_use_decides() {
+        let state = AtomicU8::new(UNKNOWN);
+        // Other errors say nothing about whether the call exists
+        let result: Result<(), Error> = attempt_with(&state, || Err(Error(uapi::EBADF)));
+        assert_eq!(result.unwrap_err(), uapi::EBADF);
+        assert_eq!(state.load(Ordering::Relaxed), UNKNOWN);
+
+        let result: Result<(), Error> = attempt_with(&state, || Err(Error(uapi::EINVAL)));
+        assert_eq!(result.unwrap_err(), uapi::ENOSYS);
+        let result: Result<(), Error> = attempt_with(&state, || panic!("called again"));
+        assert_eq!(result.unwrap_err(), uapi::ENOSYS);
+
+        let state = AtomicU8::new(UNKNOWN);
+        assert_eq!(attempt_with(&state, || Ok(1)).unwrap(), 1);
+        // Once the call is known to exist, its errors are passed on
+        let result: Result<(), Error> = attempt_with(&state, || Err(Error(uapi::EINVAL)));
+        assert_eq!(result.unwrap_err(), uapi::EINVAL);
+    }
+
+    #[test]
+    fn probes_match_the_kernel() {
+        for (capability, support) in report() {
+            assert_ne!(support, Support::Unknown, "{:?}", capability);
+            let errno = unsafe {
+                match capability {
+                    Capability::Statx => libc::syscall(libc::SYS_statx, -1, 0, 0, 0, 0),
+                    Capability::Clone3 => libc::syscall(libc::SYS_clone3, 0, 0),
+                    Capability::Openat2 => libc::syscall(libc::SYS_openat2, -1, 0, 0, 0),
+                    Capability::CopyFileRange => {
+                        libc::syscall(libc::SYS_copy_file_range, -1, 0, -1, 0, 0, 0)
+                    }
+                    Capability::PidfdOpen => libc::syscall(libc::SYS_pidfd_open, -1, 0),
+                }
+            };
+            assert_eq!(errno, -1);
+            let missing = std::io::Error::last_os_error().raw_os_error() == Some(libc::ENOSYS);
+            assert_eq!(support == Support::Unsupported, missing, "{:?}", capability);
+        }
+    }
+}
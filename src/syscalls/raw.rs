@@ -0,0 +1,229 @@
+//! The system call instruction itself, which the typed wrappers in [`syscalls`](super) are built
+//! on
+//!
+//! `syscallN` passes `N` arguments and tells the compiler the kernel may read and write any memory
+//! the arguments point to. The `_readonly` variants promise the kernel only reads memory, if any,
+//! which lets the compiler keep values in registers across calls like `openat`. The `_nomem`
+//! variants promise it touches no memory at all, for calls like `getpid` which only take and
+//! return numbers. Neither may be used for a call which can run a signal handler in this thread
+//! before it returns, which may write anything: `kill` or `tgkill` of this process, or a `write`
+//! which raises `SIGPIPE` or `SIGXFSZ`. There is no six-argument `_nomem` variant, because on
+//! i386 that many arguments are loaded from memory. The syscall numbers for the current
+//! architecture are in [`nr`].
+
+use crate::{uapi, Error};
+
+#[cfg_attr(target_arch = "x86_64", path = "x86_64.rs")]
+#[cfg_attr(target_arch = "aarch64", path = "aarch64.rs")]
+#[cfg_attr(target_arch = "x86", path = "i686.rs")]
+mod arch;
+pub use arch::*;
+
+/// The value the kernel returned from a system call, which is either a result or a negated errno
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct RawSyscallResult(usize);
+
+impl RawSyscallResult {
+    // The kernel only ever returns errors in the range -4095..=-1
+    const MAX_ERRNO: usize = 4095;
+
+    #[inline]
+    pub fn from_raw(value: usize) -> Self {
+        Self(value)
+    }
+
+    /// The register the kernel returned, without interpreting it
+    #[inline]
+    pub fn raw(self) -> usize {
+        self.0
+    }
+
+    #[inline]
+    pub fn is_error(self) -> bool {
+        self.0 >= Self::MAX_ERRNO.wrapping_neg()
+    }
+
+    #[inline]
+    pub fn to_result(self) -> Result<usize, Error> {
+        if self.is_error() {
+            Err(Error(self.0.wrapping_neg() as uapi::c_int))
+        } else {
+            Ok(self.0)
+        }
+    }
+}
+
+/// Make a system call by name, converting each argument with `as usize`
+macro_rules! syscall {
+    ($nr:ident) => {
+        $crate::syscalls::raw::syscall0($crate::syscalls::raw::nr::$nr)
+    };
+    ($nr:ident, $a:expr $(,)?) => {
+        $crate::syscalls::raw::syscall1($crate::syscalls::raw::nr::$nr, $a as usize)
+    };
+    ($nr:ident, $a:expr, $b:expr $(,)?) => {
+        $crate::syscalls::raw::syscall2($crate::syscalls::raw::nr::$nr, $a as usize, $b as usize)
+    };
+    ($nr:ident, $a:expr, $b:expr, $c:expr $(,)?) => {
+        $crate::syscalls::raw::syscall3(
+            $crate::syscalls::raw::nr::$nr,
+            $a as usize,
+            $b as usize,
+            $c as usize,
+        )
+    };
+    ($nr:ident, $a:expr, $b:expr, $c:expr, $d:expr $(,)?) => {
+        $crate::syscalls::raw::syscall4(
+            $crate::syscalls::raw::nr::$nr,
+            $a as usize,
+            $b as usize,
+            $c as usize,
+            $d as usize,
+        )
+    };
+    ($nr:ident, $a:expr, $b:expr, $c:expr, $d:expr, $e:expr $(,)?) => {
+        $crate::syscalls::raw::syscall5(
+            $crate::syscalls::raw::nr::$nr,
+            $a as usize,
+            $b as usize,
+            $c as usize,
+            $d as usize,
+            $e as usize,
+        )
+    };
+    ($nr:ident, $a:expr, $b:expr, $c:expr, $d:expr, $e:expr, $f:expr $(,)?) => {
+        $crate::syscalls::raw::syscall6(
+            $crate::syscalls::raw::nr::$nr,
+            $a as usize,
+            $b as usize,
+            $c as usize,
+            $d as usize,
+            $e as usize,
+            $f as usize,
+        )
+    };
+}
+
+/// Like [`syscall!`], for system calls which do not write to memory
+macro_rules! syscall_readonly {
+    ($nr:ident) => {
+        $crate::syscalls::raw::syscall0_readonly($crate::syscalls::raw::nr::$nr)
+    };
+    ($nr:ident, $a:expr $(,)?) => {
+        $crate::syscalls::raw::syscall1_readonly($crate::syscalls::raw::nr::$nr, $a as usize)
+    };
+    ($nr:ident, $a:expr, $b:expr $(,)?) => {
+        $crate::syscalls::raw::syscall2_readonly(
+            $crate::syscalls::raw::nr::$nr,
+            $a as usize,
+            $b as usize,
+        )
+    };
+    ($nr:ident, $a:expr, $b:expr, $c:expr $(,)?) => {
+        $crate::syscalls::raw::syscall3_readonly(
+            $crate::syscalls::raw::nr::$nr,
+            $a as usize,
+            $b as usize,
+            $c as usize,
+        )
+    };
+    ($nr:ident, $a:expr, $b:expr, $c:expr, $d:expr $(,)?) => {
+        $crate::syscalls::raw::syscall4_readonly(
+            $crate::syscalls::raw::nr::$nr,
+            $a as usize,
+            $b as usize,
+            $c as usize,
+            $d as usize,
+        )
+    };
+    ($nr:ident, $a:expr, $b:expr, $c:expr, $d:expr, $e:expr $(,)?) => {
+        $crate::syscalls::raw::syscall5_readonly(
+            $crate::syscalls::raw::nr::$nr,
+            $a as usize,
+            $b as usize,
+            $c as usize,
+            $d as usize,
+            $e as usize,
+        )
+    };
+    ($nr:ident, $a:expr, $b:expr, $c:expr, $d:expr, $e:expr, $f:expr $(,)?) => {
+        $crate::syscalls::raw::syscall6_readonly(
+            $crate::syscalls::raw::nr::$nr,
+            $a as usize,
+            $b as usize,
+            $c as usize,
+            $d as usize,
+            $e as usize,
+            $f as usize,
+        )
+    };
+}
+
+/// Like [`syscall!`], for system calls which neither read nor write memory
+macro_rules! syscall_nomem {
+    ($nr:ident) => {
+        $crate::syscalls::raw::syscall0_nomem($crate::syscalls::raw::nr::$nr)
+    };
+    ($nr:ident, $a:expr $(,)?) => {
+        $crate::syscalls::raw::syscall1_nomem($crate::syscalls::raw::nr::$nr, $a as usize)
+    };
+    ($nr:ident, $a:expr, $b:expr $(,)?) => {
+        $crate::syscalls::raw::syscall2_nomem($crate::syscalls::raw::nr::$nr, $a as usize, $b as usize)
+    };
+    ($nr:ident, $a:expr, $b:expr, $c:expr $(,)?) => {
+        $crate::syscalls::raw::syscall3_nomem(
+            $crate::syscalls::raw::nr::$nr,
+            $a as usize,
+            $b as usize,
+            $c as usize,
+        )
+    };
+    ($nr:ident, $a:expr, $b:expr, $c:expr, $d:expr $(,)?) => {
+        $crate::syscalls::raw::syscall4_nomem(
+            $crate::syscalls::raw::nr::$nr,
+            $a as usize,
+            $b as usize,
+            $c as usize,
+            $d as usize,
+        )
+    };
+    ($nr:ident, $a:expr, $b:expr, $c:expr, $d:expr, $e:expr $(,)?) => {
+        $crate::syscalls::raw::syscall5_nomem(
+            $crate::syscalls::raw::nr::$nr,
+            $a as usize,
+            $b as usize,
+            $c as usize,
+            $d as usize,
+            $e as usize,
+        )
+    };
+}
+
+pub(crate) use syscall;
+pub(crate) use syscall_nomem;
+pub(crate) use syscall_readonly;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors() {
+        assert_eq!(RawSyscallResult::from_raw(3).to_result().unwrap(), 3);
+        let enoent = RawSyscallResult::from_raw((uapi::ENOENT as usize).wrapping_neg());
+        assert!(enoent.is_error());
+        assert_eq!(enoent.to_result().unwrap_err(), uapi::ENOENT);
+        // Addresses in the top half are results, not errors
+        assert!(!RawSyscallResult::from_raw(usize::MAX - 4095).is_error());
+        assert!(RawSyscallResult::from_raw(usize::MAX - 4094).is_error());
+    }
+
+    #[test]
+    fn getpid() {
+        let pid = unsafe { syscall0_readonly(nr::GETPID) };
+        assert_eq!(pid.raw(), std::process::id() as usize);
+        let written = unsafe { syscall!(WRITE, -1i32, b"x".as_ptr(), 1) };
+        assert_eq!(written.to_result().unwrap_err(), uapi::EBADF);
+    }
+}
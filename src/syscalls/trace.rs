@@ -0,0 +1,237 @@
+//! Logging of every system call the wrappers in [`syscalls`](super) make, for debugging programs
+//! where strace is not available
+//!
+//! With the `trace` feature, setting `VENEER_TRACE` to anything other than an empty string or `0`
+//! prints each call to stderr as it returns, like `openat(-100, "/etc/hosts", OpenFlags(RDONLY |
+//! CLOEXEC), OpenMode(0x0)) = 3`. Setting `VENEER_TRACE_FD` to a file descriptor sends the log
+//! there instead. When the process exits, a count of the calls to each wrapper and how many of
+//! them failed is printed after the log.
+//!
+//! Calls that the vDSO answers, like most of `clock_gettime`, never enter the kernel and are not
+//! logged.
+
+use super::raw::{syscall, RawSyscallResult};
+use crate::uapi::{self, c_int};
+use core::{
+    fmt::{self, Write},
+    ptr,
+    sync::atomic::{AtomicBool, AtomicI32, AtomicPtr, AtomicUsize, Ordering},
+};
+
+// Not yet read from the environment, which is only possible once `ENVP` is set
+const UNKNOWN: i32 = -2;
+const DISABLED: i32 = -1;
+
+/// The file descriptor to log to, or one of the values above
+static OUTPUT: AtomicI32 = AtomicI32::new(UNKNOWN);
+
+/// The most recently used of the wrappers' counters, which links to the one used before it
+static USED: AtomicPtr<Calls> = AtomicPtr::new(ptr::null_mut());
+
+/// How many times one of the wrappers was called, and how many of those calls failed
+pub(crate) struct Calls {
+    name: &'static str,
+    calls: AtomicUsize,
+    errors: AtomicUsize,
+    linked: AtomicBool,
+    next: AtomicPtr<Calls>,
+}
+
+impl Calls {
+    pub(crate) const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            calls: AtomicUsize::new(0),
+            errors: AtomicUsize::new(0),
+            linked: AtomicBool::new(false),
+            next: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    fn count(&'static self, failed: bool) {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        if failed {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        if self.linked.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let this = self as *const Calls as *mut Calls;
+        let mut head = USED.load(Ordering::Acquire);
+        loop {
+            self.next.store(head, Ordering::Relaxed);
+            match USED.compare_exchange_weak(head, this, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+}
+
+fn output() -> Option<c_int> {
+    match OUTPUT.load(Ordering::Relaxed) {
+        DISABLED => None,
+        UNKNOWN => {
+            if crate::env::ENVP.load(Ordering::SeqCst).is_null() {
+                return None;
+            }
+            let fd = match crate::env::var(b"VENEER_TRACE") {
+                Some(value) if !matches!(value.as_bytes(), b"" | b"0") => {
+                    crate::env::var(b"VENEER_TRACE_FD")
+                        .and_then(|fd| parse_fd(fd.as_bytes()))
+                        .unwrap_or(uapi::STDERR_FILENO)
+                }
+                _ => DISABLED,
+            };
+            OUTPUT.store(fd, Ordering::Relaxed);
+            Some(fd).filter(|&fd| fd != DISABLED)
+        }
+        fd => Some(fd),
+    }
+}
+
+fn parse_fd(digits: &[u8]) -> Option<c_int> {
+    if digits.is_empty() {
+        return None;
+    }
+    digits.iter().try_fold(0 as c_int, |fd, &digit| {
+        if !digit.is_ascii_digit() {
+            return None;
+        }
+        fd.checked_mul(10)?.checked_add(c_int::from(digit - b'0'))
+    })
+}
+
+/// Log a call to `calls.name` with `args` which returned `result`
+pub(crate) fn record(calls: &'static Calls, args: &[&dyn fmt::Debug], result: RawSyscallResult) {
+    let fd = match output() {
+        Some(fd) => fd,
+        None => return,
+    };
+    calls.count(result.is_error());
+    let mut line = Line::new(fd);
+    write_call(&mut line, calls.name, args);
+    let _ = match result.to_result() {
+        Ok(value) if value <= i32::MAX as usize => writeln!(line, " = {}", value),
+        Ok(value) => writeln!(line, " = {:#x}", value),
+        Err(e) => match uapi::errno_name(e.0) {
+            Some(name) => writeln!(line, " = -1 {}", name),
+            None => writeln!(line, " = -1 errno {}", e.0),
+        },
+    };
+    line.flush();
+}
+
+/// Log a call to `calls.name` which does not return if it succeeds, like `exit_group`
+pub(crate) fn record_start(calls: &'static Calls, args: &[&dyn fmt::Debug]) {
+    let fd = match output() {
+        Some(fd) => fd,
+        None => return,
+    };
+    calls.count(false);
+    let mut line = Line::new(fd);
+    write_call(&mut line, calls.name, args);
+    let _ = line.write_str(" = ?\n");
+    line.flush();
+}
+
+fn write_call(line: &mut Line, name: &str, args: &[&dyn fmt::Debug]) {
+    let _ = line.write_str(name);
+    let _ = line.write_str("(");
+    for (i, arg) in args.iter().enumerate() {
+        if i > 0 {
+            let _ = line.write_str(", ");
+        }
+        let _ = write!(line, "{:?}", arg);
+    }
+    let _ = line.write_str(")");
+}
+
+/// Print how many times each wrapper was called, if tracing is enabled
+///
+/// [`process::exit`](crate::process::exit) calls this last, after the exit hooks and destructors
+/// have made their calls.
+pub(crate) fn report() {
+    let fd = match output() {
+        Some(fd) => fd,
+        None => return,
+    };
+    let mut line = Line::new(fd);
+    let _ = writeln!(line, "{:>10} {:>10} syscall", "calls", "errors");
+    let _ = writeln!(line, "{:->10} {:->10} {:->16}", "", "", "");
+    let (mut total_calls, mut total_errors) = (0, 0);
+    let mut calls = USED.load(Ordering::Acquire);
+    while let Some(c) = unsafe { calls.as_ref() } {
+        let (n, errors) = (
+            c.calls.load(Ordering::Relaxed),
+            c.errors.load(Ordering::Relaxed),
+        );
+        let _ = writeln!(line, "{:>10} {:>10} {}", n, errors, c.name);
+        total_calls += n;
+        total_errors += errors;
+        calls = c.next.load(Ordering::Acquire);
+    }
+    let _ = writeln!(line, "{:->10} {:->10} {:->16}", "", "", "");
+    let _ = writeln!(line, "{:>10} {:>10} total", total_calls, total_errors);
+    line.flush();
+}
+
+/// A buffer for a line of the log, so that it is written in one piece unless it is very long
+///
+/// The log can't go through [`syscalls::write`](super::write), since that would log the write.
+struct Line {
+    fd: c_int,
+    buf: [u8; 512],
+    len: usize,
+}
+
+impl Line {
+    fn new(fd: c_int) -> Self {
+        Self {
+            fd,
+            buf: [0; 512],
+            len: 0,
+        }
+    }
+
+    fn flush(&mut self) {
+        let mut written = 0;
+        while written < self.len {
+            let bytes = &self.buf[written..self.len];
+            match unsafe { syscall!(WRITE, self.fd, bytes.as_ptr(), bytes.len()) }.to_result() {
+                Ok(0) => break,
+                Ok(n) => written += n,
+                Err(e) if e == uapi::EINTR => {}
+                Err(_) => break,
+            }
+        }
+        self.len = 0;
+    }
+}
+
+impl Write for Line {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for byte in s.bytes() {
+            if self.len == self.buf.len() {
+                self.flush();
+            }
+            self.buf[self.len] = byte;
+            self.len += 1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_fds() {
+        assert_eq!(parse_fd(b"2"), Some(2));
+        assert_eq!(parse_fd(b"17"), Some(17));
+        assert_eq!(parse_fd(b""), None);
+        assert_eq!(parse_fd(b"-1"), None);
+        assert_eq!(parse_fd(b"99999999999"), None);
+    }
+}
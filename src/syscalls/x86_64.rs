@@ -0,0 +1,529 @@
+//! System calls on x86_64, which take the number in rax and arguments in rdi, rsi, rdx, r10, r8,
+//! and r9, and return in rax. The `syscall` instruction itself overwrites rcx and r11.
+
+use super::RawSyscallResult;
+use core::arch::asm;
+
+/// The numbers of the system calls veneer uses
+pub mod nr {
+    pub const READ: usize = 0;
+    pub const WRITE: usize = 1;
+    pub const CLOSE: usize = 3;
+    pub const FSTAT: usize = 5;
+    pub const LSEEK: usize = 8;
+    pub const MMAP: usize = 9;
+    pub const MPROTECT: usize = 10;
+    pub const MUNMAP: usize = 11;
+    pub const BRK: usize = 12;
+    pub const RT_SIGACTION: usize = 13;
+    pub const RT_SIGPROCMASK: usize = 14;
+    pub const IOCTL: usize = 16;
+    pub const PREAD64: usize = 17;
+    pub const PWRITE64: usize = 18;
+    pub const READV: usize = 19;
+    pub const WRITEV: usize = 20;
+    pub const SCHED_YIELD: usize = 24;
+    pub const MREMAP: usize = 25;
+    pub const MSYNC: usize = 26;
+    pub const MINCORE: usize = 27;
+    pub const MADVISE: usize = 28;
+    pub const SHMGET: usize = 29;
+    pub const DUP: usize = 32;
+    pub const GETPID: usize = 39;
+    pub const CLONE: usize = 56;
+    pub const EXECVE: usize = 59;
+    pub const EXIT: usize = 60;
+    pub const WAIT4: usize = 61;
+    pub const KILL: usize = 62;
+    pub const FCNTL: usize = 72;
+    pub const GETTIMEOFDAY: usize = 96;
+    pub const SIGALTSTACK: usize = 131;
+    pub const ARCH_PRCTL: usize = 158;
+    pub const GETTID: usize = 186;
+    pub const FUTEX: usize = 202;
+    pub const GETDENTS64: usize = 217;
+    pub const CLOCK_GETTIME: usize = 228;
+    pub const EXIT_GROUP: usize = 231;
+    pub const TGKILL: usize = 234;
+    pub const OPENAT: usize = 257;
+    pub const NEWFSTATAT: usize = 262;
+    pub const READLINKAT: usize = 267;
+    pub const FACCESSAT: usize = 269;
+    pub const PPOLL: usize = 271;
+    pub const DUP3: usize = 292;
+    pub const PIPE2: usize = 293;
+    pub const PRLIMIT64: usize = 302;
+    pub const GETCPU: usize = 309;
+    pub const COPY_FILE_RANGE: usize = 326;
+    pub const STATX: usize = 332;
+    pub const PIDFD_OPEN: usize = 434;
+    pub const CLONE3: usize = 435;
+    pub const OPENAT2: usize = 437;
+}
+
+/// Make system call `n` with no arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them.
+#[inline(always)]
+pub unsafe fn syscall0(n: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "syscall",
+        inlateout("rax") n => ret,
+        lateout("rcx") _,
+        lateout("r11") _,
+        options(nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with one argument
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them.
+#[inline(always)]
+pub unsafe fn syscall1(n: usize, a: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "syscall",
+        inlateout("rax") n => ret,
+        in("rdi") a,
+        lateout("rcx") _,
+        lateout("r11") _,
+        options(nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with two arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them.
+#[inline(always)]
+pub unsafe fn syscall2(n: usize, a: usize, b: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "syscall",
+        inlateout("rax") n => ret,
+        in("rdi") a,
+        in("rsi") b,
+        lateout("rcx") _,
+        lateout("r11") _,
+        options(nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with three arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them.
+#[inline(always)]
+pub unsafe fn syscall3(n: usize, a: usize, b: usize, c: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "syscall",
+        inlateout("rax") n => ret,
+        in("rdi") a,
+        in("rsi") b,
+        in("rdx") c,
+        lateout("rcx") _,
+        lateout("r11") _,
+        options(nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with four arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them.
+#[inline(always)]
+pub unsafe fn syscall4(n: usize, a: usize, b: usize, c: usize, d: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "syscall",
+        inlateout("rax") n => ret,
+        in("rdi") a,
+        in("rsi") b,
+        in("rdx") c,
+        in("r10") d,
+        lateout("rcx") _,
+        lateout("r11") _,
+        options(nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with five arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them.
+#[inline(always)]
+pub unsafe fn syscall5(
+    n: usize,
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+    e: usize,
+) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "syscall",
+        inlateout("rax") n => ret,
+        in("rdi") a,
+        in("rsi") b,
+        in("rdx") c,
+        in("r10") d,
+        in("r8") e,
+        lateout("rcx") _,
+        lateout("r11") _,
+        options(nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with six arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them.
+#[inline(always)]
+pub unsafe fn syscall6(
+    n: usize,
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+    e: usize,
+    f: usize,
+) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "syscall",
+        inlateout("rax") n => ret,
+        in("rdi") a,
+        in("rsi") b,
+        in("rdx") c,
+        in("r10") d,
+        in("r8") e,
+        in("r9") f,
+        lateout("rcx") _,
+        lateout("r11") _,
+        options(nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with no arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them, and it
+/// must not write to memory.
+#[inline(always)]
+pub unsafe fn syscall0_readonly(n: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "syscall",
+        inlateout("rax") n => ret,
+        lateout("rcx") _,
+        lateout("r11") _,
+        options(nostack, preserves_flags, readonly),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with one argument
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them, and it
+/// must not write to memory.
+#[inline(always)]
+pub unsafe fn syscall1_readonly(n: usize, a: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "syscall",
+        inlateout("rax") n => ret,
+        in("rdi") a,
+        lateout("rcx") _,
+        lateout("r11") _,
+        options(nostack, preserves_flags, readonly),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with two arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them, and it
+/// must not write to memory.
+#[inline(always)]
+pub unsafe fn syscall2_readonly(n: usize, a: usize, b: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "syscall",
+        inlateout("rax") n => ret,
+        in("rdi") a,
+        in("rsi") b,
+        lateout("rcx") _,
+        lateout("r11") _,
+        options(nostack, preserves_flags, readonly),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with three arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them, and it
+/// must not write to memory.
+#[inline(always)]
+pub unsafe fn syscall3_readonly(n: usize, a: usize, b: usize, c: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "syscall",
+        inlateout("rax") n => ret,
+        in("rdi") a,
+        in("rsi") b,
+        in("rdx") c,
+        lateout("rcx") _,
+        lateout("r11") _,
+        options(nostack, preserves_flags, readonly),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with four arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them, and it
+/// must not write to memory.
+#[inline(always)]
+pub unsafe fn syscall4_readonly(
+    n: usize,
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "syscall",
+        inlateout("rax") n => ret,
+        in("rdi") a,
+        in("rsi") b,
+        in("rdx") c,
+        in("r10") d,
+        lateout("rcx") _,
+        lateout("r11") _,
+        options(nostack, preserves_flags, readonly),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with five arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them, and it
+/// must not write to memory.
+#[inline(always)]
+pub unsafe fn syscall5_readonly(
+    n: usize,
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+    e: usize,
+) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "syscall",
+        inlateout("rax") n => ret,
+        in("rdi") a,
+        in("rsi") b,
+        in("rdx") c,
+        in("r10") d,
+        in("r8") e,
+        lateout("rcx") _,
+        lateout("r11") _,
+        options(nostack, preserves_flags, readonly),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with six arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, including any pointers among them, and it
+/// must not write to memory.
+#[inline(always)]
+pub unsafe fn syscall6_readonly(
+    n: usize,
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+    e: usize,
+    f: usize,
+) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "syscall",
+        inlateout("rax") n => ret,
+        in("rdi") a,
+        in("rsi") b,
+        in("rdx") c,
+        in("r10") d,
+        in("r8") e,
+        in("r9") f,
+        lateout("rcx") _,
+        lateout("r11") _,
+        options(nostack, preserves_flags, readonly),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with no arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, and it must not read or write memory.
+#[inline(always)]
+pub unsafe fn syscall0_nomem(n: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "syscall",
+        inlateout("rax") n => ret,
+        lateout("rcx") _,
+        lateout("r11") _,
+        options(nomem, nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with one argument
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, and it must not read or write memory.
+#[inline(always)]
+pub unsafe fn syscall1_nomem(n: usize, a: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "syscall",
+        inlateout("rax") n => ret,
+        in("rdi") a,
+        lateout("rcx") _,
+        lateout("r11") _,
+        options(nomem, nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with two arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, and it must not read or write memory.
+#[inline(always)]
+pub unsafe fn syscall2_nomem(n: usize, a: usize, b: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "syscall",
+        inlateout("rax") n => ret,
+        in("rdi") a,
+        in("rsi") b,
+        lateout("rcx") _,
+        lateout("r11") _,
+        options(nomem, nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with three arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, and it must not read or write memory.
+#[inline(always)]
+pub unsafe fn syscall3_nomem(n: usize, a: usize, b: usize, c: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "syscall",
+        inlateout("rax") n => ret,
+        in("rdi") a,
+        in("rsi") b,
+        in("rdx") c,
+        lateout("rcx") _,
+        lateout("r11") _,
+        options(nomem, nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with four arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, and it must not read or write memory.
+#[inline(always)]
+pub unsafe fn syscall4_nomem(n: usize, a: usize, b: usize, c: usize, d: usize) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "syscall",
+        inlateout("rax") n => ret,
+        in("rdi") a,
+        in("rsi") b,
+        in("rdx") c,
+        in("r10") d,
+        lateout("rcx") _,
+        lateout("r11") _,
+        options(nomem, nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
+
+/// Make system call `n` with five arguments
+///
+/// # Safety
+///
+/// The arguments must be valid for the system call, and it must not read or write memory.
+#[inline(always)]
+pub unsafe fn syscall5_nomem(
+    n: usize,
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+    e: usize,
+) -> RawSyscallResult {
+    let ret: usize;
+    asm!(
+        "syscall",
+        inlateout("rax") n => ret,
+        in("rdi") a,
+        in("rsi") b,
+        in("rdx") c,
+        in("r10") d,
+        in("r8") e,
+        lateout("rcx") _,
+        lateout("r11") _,
+        options(nomem, nostack, preserves_flags),
+    );
+    RawSyscallResult(ret)
+}
@@ -0,0 +1,332 @@
+//! A test harness for programs built on the runtime, so that tests run on top of the real
+//! `_start`, allocator, and panic handler instead of under std
+//!
+//! Each function marked `#[veneer::test]` is recorded in the `veneer_tests` section, which
+//! [`test_main`] reads back. Every test runs in a child process of its own with its stdout and
+//! stderr captured through a pipe, so a test which panics or crashes only fails itself. The
+//! output and the command line options follow libtest's.
+//!
+//! A test target sets `harness = false` in its manifest and provides the entry point itself:
+//!
+//! ```ignore
+//! #[veneer::test]
+//! fn adds() {
+//!     assert_eq!(1 + 1, 2);
+//! }
+//!
+//! #[veneer::main]
+//! fn main() -> veneer::process::ExitCode {
+//!     veneer::test_main()
+//! }
+//! ```
+
+use crate::{
+    fd::{self, AsFd},
+    io::{Stdout, Write},
+    process::ExitCode,
+    syscalls::{self, ClockId, DupFlags, Pipe2Flags},
+    uapi, CStr, Error,
+};
+use alloc::{string::String, vec::Vec};
+use core::fmt::Write as _;
+
+/// A test registered by `#[veneer::test]`
+#[doc(hidden)]
+pub struct Test {
+    pub module_path: &'static str,
+    pub name: &'static str,
+    /// Runs the test and returns the exit code it reports
+    pub run: fn() -> i32,
+    pub should_panic: ShouldPanic,
+    pub ignore: bool,
+}
+
+#[doc(hidden)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ShouldPanic {
+    No,
+    Yes,
+    /// The panic message must contain this string
+    YesWithMessage(&'static str),
+}
+
+// The linker defines these around the section, as its name is a valid C identifier
+extern "C" {
+    static __start_veneer_tests: u8;
+    static __stop_veneer_tests: u8;
+}
+
+// Makes sure the section exists, so that its bounds are defined in programs without any tests
+#[used]
+#[link_section = "veneer_tests"]
+static NO_TESTS: [Test; 0] = [];
+
+// libtest's exit code when a test fails
+const TESTS_FAILED: u8 = 101;
+
+fn registered() -> &'static [Test] {
+    unsafe {
+        let start = &__start_veneer_tests as *const u8;
+        let stop = &__stop_veneer_tests as *const u8;
+        core::slice::from_raw_parts(
+            start.cast::<Test>(),
+            (stop as usize - start as usize) / core::mem::size_of::<Test>(),
+        )
+    }
+}
+
+/// Run the tests selected by the command line arguments and report the results on stdout
+///
+/// Returns a failing exit code if any test failed or the arguments were not understood.
+#[inline]
+pub fn test_main() -> ExitCode {
+    let options = match Options::parse(crate::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            crate::eprintln!("error: {}", message);
+            return ExitCode::from(TESTS_FAILED);
+        }
+    };
+
+    let mut tests: Vec<(String, &Test)> = registered()
+        .iter()
+        .map(|test| (display_name(test), test))
+        .collect();
+    tests.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+    let total = tests.len();
+    tests.retain(|(name, _)| options.selects(name));
+    let filtered_out = total - tests.len();
+
+    if options.list {
+        for (name, _) in &tests {
+            crate::println!("{}: test", name);
+        }
+        crate::println!();
+        crate::println!("{} tests, 0 benchmarks", tests.len());
+        return ExitCode::SUCCESS;
+    }
+
+    crate::println!();
+    crate::println!(
+        "running {} test{}",
+        tests.len(),
+        if tests.len() == 1 { "" } else { "s" }
+    );
+
+    let start = now();
+    let (mut passed, mut ignored) = (0, 0);
+    let mut failures = Vec::new();
+    for (name, test) in &tests {
+        crate::print!("test {} ", name);
+        if test.should_panic != ShouldPanic::No {
+            crate::print!("- should panic ");
+        }
+        let ignore = if options.ignored {
+            !test.ignore
+        } else {
+            test.ignore && !options.include_ignored
+        };
+        if ignore {
+            crate::println!("... ignored");
+            ignored += 1;
+            continue;
+        }
+        match run(test, options.nocapture) {
+            Ok(None) => {
+                crate::println!("... ok");
+                passed += 1;
+            }
+            Ok(Some(output)) => {
+                crate::println!("... FAILED");
+                failures.push((name, output));
+            }
+            Err(e) => {
+                crate::println!("... FAILED");
+                let mut message = String::new();
+                let _ = writeln!(message, "failed to run test: {}", e);
+                failures.push((name, message.into_bytes()));
+            }
+        }
+    }
+
+    if !failures.is_empty() {
+        crate::println!();
+        crate::println!("failures:");
+        crate::println!();
+        for (name, output) in &failures {
+            crate::println!("---- {} stdout ----", name);
+            let _ = Stdout.write_all(output);
+            crate::println!();
+        }
+        crate::println!();
+        crate::println!("failures:");
+        for (name, _) in &failures {
+            crate::println!("    {}", name);
+        }
+    }
+
+    crate::println!();
+    crate::println!(
+        "test result: {}. {} passed; {} failed; {} ignored; 0 measured; {} filtered out; finished in {:.2}s",
+        if failures.is_empty() { "ok" } else { "FAILED" },
+        passed,
+        failures.len(),
+        ignored,
+        filtered_out,
+        now() - start
+    );
+    crate::println!();
+
+    if failures.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(TESTS_FAILED)
+    }
+}
+
+/// The name libtest would use, which leaves out the crate name
+fn display_name(test: &Test) -> String {
+    let mut name = String::new();
+    if let Some(i) = test.module_path.find("::") {
+        name.push_str(&test.module_path[i + 2..]);
+        name.push_str("::");
+    }
+    name.push_str(test.name);
+    name
+}
+
+fn now() -> f64 {
+    syscalls::clock_gettime(ClockId::Monotonic)
+        .map(|ts| ts.tv_sec as f64 + ts.tv_nsec as f64 / 1e9)
+        .unwrap_or(0.0)
+}
+
+#[derive(Default)]
+struct Options {
+    filters: Vec<CStr<'static>>,
+    skip: Vec<CStr<'static>>,
+    exact: bool,
+    ignored: bool,
+    include_ignored: bool,
+    nocapture: bool,
+    list: bool,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = CStr<'static>>) -> Result<Self, String> {
+        let mut options = Self::default();
+        while let Some(arg) = args.next() {
+            match arg.as_bytes() {
+                b"--exact" => options.exact = true,
+                b"--ignored" => options.ignored = true,
+                b"--include-ignored" => options.include_ignored = true,
+                b"--nocapture" => options.nocapture = true,
+                b"--list" => options.list = true,
+                b"--skip" => match args.next() {
+                    Some(filter) => options.skip.push(filter),
+                    None => return Err(String::from("argument to option 'skip' missing")),
+                },
+                flag if flag.starts_with(b"-") => {
+                    let mut message = String::new();
+                    let _ = write!(
+                        message,
+                        "unrecognized option: '{}'",
+                        core::str::from_utf8(flag).unwrap_or("?")
+                    );
+                    return Err(message);
+                }
+                _ => options.filters.push(arg),
+            }
+        }
+        Ok(options)
+    }
+
+    fn selects(&self, name: &str) -> bool {
+        let matches = |filter: &CStr| {
+            if self.exact {
+                name.as_bytes() == filter.as_bytes()
+            } else {
+                contains(name.as_bytes(), filter.as_bytes())
+            }
+        };
+        (self.filters.is_empty() || self.filters.iter().any(matches))
+            && !self.skip.iter().any(matches)
+    }
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    needle.is_empty() || haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+/// Run `test` in a child process, returning `None` if it passed or what it printed along with
+/// why it failed
+fn run(test: &Test, nocapture: bool) -> Result<Option<Vec<u8>>, Error> {
+    let pipe = if nocapture {
+        None
+    } else {
+        Some(syscalls::pipe2(Pipe2Flags::CLOEXEC)?)
+    };
+    // Anything still buffered would otherwise be written again by the child
+    crate::io::flush_std_streams();
+    let pid = syscalls::fork()?;
+    if pid == 0 {
+        if let Some((_, write)) = &pipe {
+            // The duplicates do not inherit close-on-exec
+            let _ = syscalls::dup3(write.as_fd(), fd::STDOUT, DupFlags::empty());
+            let _ = syscalls::dup3(write.as_fd(), fd::STDERR, DupFlags::empty());
+        }
+        crate::process::exit((test.run)());
+    }
+
+    let mut output = Vec::new();
+    if let Some((read, write)) = pipe {
+        // Otherwise the read would never see the end of the pipe
+        drop(write);
+        let mut buf = [0u8; 4096];
+        loop {
+            match syscalls::read(read.as_fd(), &mut buf) {
+                Ok(0) => break,
+                Ok(n) => output.extend_from_slice(&buf[..n]),
+                Err(e) if e == uapi::EINTR => {}
+                Err(_) => break,
+            }
+        }
+    }
+    let status = loop {
+        match syscalls::wait4(pid, 0) {
+            Ok((_, status)) => break status,
+            Err(e) if e == uapi::EINTR => {}
+            Err(e) => return Err(e),
+        }
+    };
+
+    // Panics end in an abort, since there is no unwinding
+    let panicked = uapi::WIFSIGNALED(status) && uapi::WTERMSIG(status) == uapi::SIGABRT;
+    let exited = uapi::WIFEXITED(status);
+    let mut note = String::new();
+    let _ = match test.should_panic {
+        ShouldPanic::No if exited && uapi::WEXITSTATUS(status) == 0 => return Ok(None),
+        ShouldPanic::No if exited || panicked => Ok(()),
+        ShouldPanic::No => writeln!(
+            note,
+            "note: test process was killed by signal {}",
+            uapi::WTERMSIG(status)
+        ),
+        ShouldPanic::Yes if panicked => return Ok(None),
+        ShouldPanic::YesWithMessage(expected) if panicked => {
+            if contains(&output, expected.as_bytes()) {
+                return Ok(None);
+            }
+            writeln!(
+                note,
+                "note: panic did not contain expected string\n expected substring: `{:?}`",
+                expected
+            )
+        }
+        ShouldPanic::Yes | ShouldPanic::YesWithMessage(_) => {
+            writeln!(note, "note: test did not panic as expected")
+        }
+    };
+    output.extend_from_slice(note.as_bytes());
+    Ok(Some(output))
+}
@@ -0,0 +1,257 @@
+//! Thread-local storage for `#[thread_local]` statics
+//!
+//! The linker collects every thread-local variable into the `PT_TLS` segment, which holds the
+//! initial values of the initialized ones followed by room for the zeroed ones. Each thread gets
+//! its own copy of that segment in a block next to its thread pointer, and compiled code finds
+//! its variables at fixed offsets from the thread pointer. On x86_64 and i386 the block is just
+//! below the thread pointer and the thread pointer points at a control block, whose first word
+//! points at itself. On aarch64 the thread pointer points at a 16-byte control block with the
+//! variables right after it.
+
+use crate::{elf, Error};
+use core::mem;
+
+/// The thread control block, which the compiler expects the thread pointer to point at
+///
+/// On x86_64 this leaves room for the fields that code compiled for glibc reads from it, like
+/// the stack protector canary at `fs:0x28` and the pointer guard at `fs:0x30`, which are at
+/// `gs:0x14` and `gs:0x18` on i386.
+#[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
+const TCB_SIZE: usize = 64;
+#[cfg(target_arch = "aarch64")]
+const TCB_SIZE: usize = 16;
+
+#[cfg(target_arch = "x86_64")]
+const ARCH_SET_FS: crate::uapi::c_int = 0x1002;
+
+/// Where each thread's copy of the thread-local variables and its control block go, so that the
+/// main thread and any thread created later are set up the same way
+#[derive(Clone, Copy, Debug)]
+pub struct TlsLayout {
+    /// The initial values of the thread-local variables which have them
+    image: *const u8,
+    image_size: usize,
+    /// The size of all the variables, including those which start out zeroed
+    size: usize,
+    align: usize,
+}
+
+unsafe impl Send for TlsLayout {}
+unsafe impl Sync for TlsLayout {}
+
+impl TlsLayout {
+    /// Read the layout from the running executable's `PT_TLS` program header
+    ///
+    /// An executable without thread-local variables still gets a control block.
+    #[inline]
+    pub fn current() -> Self {
+        let mut layout = Self {
+            image: core::ptr::null(),
+            image_size: 0,
+            size: 0,
+            align: mem::align_of::<usize>(),
+        };
+        if let Some(tls) = elf::program_headers().find(|phdr| phdr.p_type == elf::PT_TLS) {
+            layout.image = elf::load_base().wrapping_add(tls.p_vaddr as usize) as *const u8;
+            layout.image_size = tls.p_filesz as usize;
+            layout.size = tls.p_memsz as usize;
+            layout.align = layout.align.max(tls.p_align as usize);
+        }
+        layout
+    }
+
+    /// How many bytes a thread's block takes
+    #[inline]
+    pub fn block_size(&self) -> usize {
+        self.variables_size() + TCB_SIZE
+    }
+
+    /// How a thread's block must be aligned
+    #[inline]
+    pub fn block_align(&self) -> usize {
+        self.align
+    }
+
+    // The space reserved for the variables, which is padded so that both they and the control
+    // block are aligned
+    #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
+    fn variables_size(&self) -> usize {
+        round_up(self.size, self.align)
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    fn variables_size(&self) -> usize {
+        round_up(TCB_SIZE, self.align) - TCB_SIZE + self.size
+    }
+
+    /// Copy the initial values of the thread-local variables into `block` and set up the control
+    /// block, returning the value the new thread's thread pointer must have
+    ///
+    /// # Safety
+    ///
+    /// `block` must be valid for writes of [`block_size`](Self::block_size) bytes, and aligned to
+    /// [`block_align`](Self::block_align)
+    #[inline]
+    pub unsafe fn initialize(&self, block: *mut u8) -> *mut u8 {
+        #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
+        let (variables, thread_pointer) = {
+            let thread_pointer = block.add(self.variables_size());
+            // Code reads the thread pointer from fs:0, and glibc's own copy of it is at fs:0x10,
+            // which are gs:0 and gs:8 on i386
+            let tcb = thread_pointer.cast::<usize>();
+            core::ptr::write_bytes(tcb, 0, TCB_SIZE / mem::size_of::<usize>());
+            *tcb = thread_pointer as usize;
+            *tcb.add(2) = thread_pointer as usize;
+            (block, thread_pointer)
+        };
+        #[cfg(target_arch = "aarch64")]
+        let (variables, thread_pointer) = {
+            core::ptr::write_bytes(block, 0, TCB_SIZE);
+            (block.add(round_up(TCB_SIZE, self.align)), block)
+        };
+
+        if self.image_size > 0 {
+            core::ptr::copy_nonoverlapping(self.image, variables, self.image_size);
+        }
+        core::ptr::write_bytes(
+            variables.add(self.image_size),
+            0,
+            self.size - self.image_size,
+        );
+        thread_pointer
+    }
+}
+
+fn round_up(n: usize, align: usize) -> usize {
+    (n + align - 1) & !(align - 1)
+}
+
+/// Make `thread_pointer` the calling thread's thread pointer
+///
+/// # Safety
+///
+/// `thread_pointer` must have come from [`TlsLayout::initialize`], and the block it is in must
+/// live as long as the thread. Every reference to a thread-local variable of the calling thread
+/// is invalidated.
+#[inline]
+pub unsafe fn set_thread_pointer(thread_pointer: *mut u8) -> Result<(), Error> {
+    #[cfg(target_arch = "x86_64")]
+    {
+        crate::syscalls::arch_prctl(ARCH_SET_FS, thread_pointer as usize)
+    }
+    // The thread pointer is the base of the segment in gs, which reuses the calling thread's
+    // entry in the GDT if it has one, since there are only three
+    #[cfg(target_arch = "x86")]
+    {
+        let selector: u32;
+        core::arch::asm!("mov {:e}, gs", out(reg) selector, options(nostack, nomem));
+        let mut desc = crate::uapi::user_desc {
+            entry_number: if selector == 0 {
+                u32::MAX
+            } else {
+                selector >> 3
+            },
+            base_addr: thread_pointer as u32,
+            limit: 0xfffff,
+            flags: crate::uapi::USER_DESC_SEG_32BIT
+                | crate::uapi::USER_DESC_LIMIT_IN_PAGES
+                | crate::uapi::USER_DESC_USEABLE,
+        };
+        crate::syscalls::set_thread_area(&mut desc)?;
+        let selector = (desc.entry_number << 3) | 3;
+        core::arch::asm!("mov gs, {:e}", in(reg) selector, options(nostack, nomem));
+        Ok(())
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        core::arch::asm!("msr tpidr_el0, {}", in(reg) thread_pointer, options(nostack));
+        Ok(())
+    }
+}
+
+/// The calling thread's thread pointer
+#[inline]
+pub fn thread_pointer() -> *mut u8 {
+    let thread_pointer: *mut u8;
+    #[cfg(target_arch = "x86_64")]
+    unsafe {
+        core::arch::asm!("mov {}, fs:0", out(reg) thread_pointer, options(nostack, readonly));
+    }
+    #[cfg(target_arch = "x86")]
+    unsafe {
+        core::arch::asm!("mov {}, gs:0", out(reg) thread_pointer, options(nostack, readonly));
+    }
+    #[cfg(target_arch = "aarch64")]
+    unsafe {
+        core::arch::asm!("mrs {}, tpidr_el0", out(reg) thread_pointer, options(nostack, nomem));
+    }
+    thread_pointer
+}
+
+/// Allocate and install the main thread's block, before anything uses a thread-local variable
+#[cfg(all(feature = "rt-start", not(test)))]
+pub(crate) fn init_main_thread() {
+    let layout = TlsLayout::current();
+    // mmap only promises page alignment, so make room to align the block further
+    let len = layout.block_size() + layout.block_align();
+    let installed = crate::syscalls::mmap(
+        core::ptr::null_mut(),
+        len,
+        crate::uapi::PROT_READ | crate::uapi::PROT_WRITE,
+        crate::uapi::MAP_PRIVATE | crate::uapi::MAP_ANONYMOUS,
+        None,
+        0,
+    )
+    .and_then(|mapping| unsafe {
+        let block = mapping.add(mapping.align_offset(layout.block_align()));
+        set_thread_pointer(layout.initialize(block))
+    });
+    if installed.is_err() {
+        let _ = crate::syscalls::write(
+            crate::fd::STDERR,
+            b"fatal runtime error: failed to set up thread-local storage\n",
+        );
+        crate::process::abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn initialize() {
+        crate::env::capture_for_test();
+        let layout = TlsLayout::current();
+        // The standard library has thread-local variables
+        assert!(layout.size > 0);
+        assert!(layout.image_size <= layout.size);
+
+        let mut memory = vec![0xffu8; layout.block_size() + layout.block_align()];
+        let offset = memory.as_ptr().align_offset(layout.block_align());
+        let block = unsafe { memory.as_mut_ptr().add(offset) };
+        let thread_pointer = unsafe { layout.initialize(block) };
+        assert_eq!(thread_pointer as usize % layout.block_align(), 0);
+
+        #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
+        let variables = unsafe {
+            assert_eq!(*thread_pointer.cast::<usize>(), thread_pointer as usize);
+            thread_pointer.sub(round_up(layout.size, layout.align))
+        };
+        #[cfg(target_arch = "aarch64")]
+        let variables = unsafe { thread_pointer.add(round_up(TCB_SIZE, layout.align)) };
+
+        let image = unsafe { core::slice::from_raw_parts(layout.image, layout.image_size) };
+        let copy = unsafe { core::slice::from_raw_parts(variables, layout.size) };
+        assert_eq!(&copy[..layout.image_size], image);
+        assert!(copy[layout.image_size..].iter().all(|b| *b == 0));
+    }
+
+    #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
+    #[test]
+    fn matches_glibc() {
+        // glibc's thread descriptor starts at the thread pointer
+        assert_eq!(thread_pointer() as usize, unsafe { libc::pthread_self() }
+            as usize);
+    }
+}
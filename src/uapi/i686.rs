@@ -0,0 +1,50 @@
+use super::{c_int, c_long, c_uint, c_ulong};
+
+pub const O_DIRECT: c_int = 0o40000;
+pub const O_LARGEFILE: c_int = 0o100000;
+pub const O_DIRECTORY: c_int = 0o200000;
+pub const O_NOFOLLOW: c_int = 0o400000;
+
+/// The result of `fstat64` and `fstatat64`, which is glibc's `struct stat64` on i386
+///
+/// The kernel's older `struct stat` has 32-bit sizes and inode numbers, so it is not used. The
+/// 64-bit fields are only 4-byte aligned on i386, which `repr(C)` matches.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct stat {
+    pub st_dev: u64,
+    pub __pad0: c_uint,
+    /// The low 32 bits of `st_ino`
+    pub __st_ino: c_ulong,
+    pub st_mode: c_uint,
+    pub st_nlink: c_uint,
+    pub st_uid: c_ulong,
+    pub st_gid: c_ulong,
+    pub st_rdev: u64,
+    pub __pad3: c_uint,
+    pub st_size: i64,
+    pub st_blksize: c_ulong,
+    pub st_blocks: u64,
+    pub st_atime: c_long,
+    pub st_atime_nsec: c_ulong,
+    pub st_mtime: c_long,
+    pub st_mtime_nsec: c_ulong,
+    pub st_ctime: c_long,
+    pub st_ctime_nsec: c_ulong,
+    pub st_ino: u64,
+}
+
+/// A segment for `set_thread_area`, whose bitfields are in `flags`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct user_desc {
+    pub entry_number: c_uint,
+    pub base_addr: c_uint,
+    pub limit: c_uint,
+    pub flags: c_uint,
+}
+
+pub const USER_DESC_SEG_32BIT: c_uint = 0x01;
+pub const USER_DESC_LIMIT_IN_PAGES: c_uint = 0x10;
+pub const USER_DESC_SEG_NOT_PRESENT: c_uint = 0x20;
+pub const USER_DESC_USEABLE: c_uint = 0x40;
@@ -0,0 +1,770 @@
+//! Types and constants from the Linux kernel's userspace API headers
+//!
+//! These mirror the kernel's own definitions rather than a C library's, which sometimes differ.
+//! glibc's `sigset_t` has room for 1024 signals while the kernel's has 64, and glibc defines
+//! `O_LARGEFILE` as 0 on 64-bit targets where the kernel's is a real flag. The few definitions
+//! which are different on each architecture, like `struct stat`, are in the per-architecture
+//! modules and re-exported here.
+//!
+//! On i386 `time_t` and `off_t` are 32 bits, as in the system calls which take a `timespec`
+//! there. Offsets and sizes which can be larger go through the calls which split them into two
+//! words, like `_llseek`.
+
+#![allow(non_camel_case_types)]
+// The wait status macros keep their C names
+#![allow(non_snake_case)]
+
+#[cfg_attr(target_arch = "x86_64", path = "x86_64.rs")]
+#[cfg_attr(target_arch = "aarch64", path = "aarch64.rs")]
+#[cfg_attr(target_arch = "x86", path = "i686.rs")]
+mod arch;
+pub use arch::*;
+
+pub use core::ffi::{c_char, c_int, c_long, c_uint, c_ulong, c_ushort};
+
+pub type pid_t = c_int;
+pub type uid_t = c_uint;
+pub type gid_t = c_uint;
+pub type mode_t = c_uint;
+pub type off_t = c_long;
+pub type time_t = c_long;
+pub type suseconds_t = c_long;
+pub type clockid_t = c_int;
+pub type key_t = c_int;
+/// One bit for each of the 64 signals, where bit `n - 1` corresponds to signal `n`
+pub type sigset_t = u64;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct timespec {
+    pub tv_sec: time_t,
+    pub tv_nsec: c_long,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct timeval {
+    pub tv_sec: time_t,
+    pub tv_usec: suseconds_t,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct rlimit64 {
+    pub rlim_cur: u64,
+    pub rlim_max: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct winsize {
+    pub ws_row: c_ushort,
+    pub ws_col: c_ushort,
+    pub ws_xpixel: c_ushort,
+    pub ws_ypixel: c_ushort,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct pollfd {
+    pub fd: c_int,
+    pub events: i16,
+    pub revents: i16,
+}
+
+/// The fixed part of each record `getdents64` writes, which is followed by the null-terminated
+/// name and padded to a multiple of 8 bytes
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct linux_dirent64 {
+    pub d_ino: u64,
+    pub d_off: i64,
+    pub d_reclen: c_ushort,
+    pub d_type: u8,
+    pub d_name: [c_char; 0],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct statx_timestamp {
+    pub tv_sec: i64,
+    pub tv_nsec: u32,
+    pub __reserved: i32,
+}
+
+/// The result of `statx`, whose fields are only meaningful if their bit is set in `stx_mask`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct statx {
+    pub stx_mask: u32,
+    pub stx_blksize: u32,
+    pub stx_attributes: u64,
+    pub stx_nlink: u32,
+    pub stx_uid: u32,
+    pub stx_gid: u32,
+    pub stx_mode: u16,
+    pub __spare0: [u16; 1],
+    pub stx_ino: u64,
+    pub stx_size: u64,
+    pub stx_blocks: u64,
+    pub stx_attributes_mask: u64,
+    pub stx_atime: statx_timestamp,
+    pub stx_btime: statx_timestamp,
+    pub stx_ctime: statx_timestamp,
+    pub stx_mtime: statx_timestamp,
+    pub stx_rdev_major: u32,
+    pub stx_rdev_minor: u32,
+    pub stx_dev_major: u32,
+    pub stx_dev_minor: u32,
+    pub stx_mnt_id: u64,
+    pub stx_dio_mem_align: u32,
+    pub stx_dio_offset_align: u32,
+    pub __spare3: [u64; 12],
+}
+
+/// How `openat2` opens a file, which unlike `openat` rejects flags it does not know
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct open_how {
+    pub flags: u64,
+    pub mode: u64,
+    pub resolve: u64,
+}
+
+/// The arguments to `clone3`, which are all 64 bits wide even where pointers are not
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct clone_args {
+    pub flags: u64,
+    pub pidfd: u64,
+    pub child_tid: u64,
+    pub parent_tid: u64,
+    pub exit_signal: u64,
+    pub stack: u64,
+    pub stack_size: u64,
+    pub tls: u64,
+    pub set_tid: u64,
+    pub set_tid_size: u64,
+    pub cgroup: u64,
+}
+
+pub const STDIN_FILENO: c_int = 0;
+pub const STDOUT_FILENO: c_int = 1;
+pub const STDERR_FILENO: c_int = 2;
+
+pub const AT_FDCWD: c_int = -100;
+pub const AT_SYMLINK_NOFOLLOW: c_int = 0x100;
+pub const AT_REMOVEDIR: c_int = 0x200;
+pub const AT_SYMLINK_FOLLOW: c_int = 0x400;
+pub const AT_EMPTY_PATH: c_int = 0x1000;
+
+pub const AT_STATX_SYNC_AS_STAT: c_int = 0;
+pub const AT_STATX_FORCE_SYNC: c_int = 0x2000;
+pub const AT_STATX_DONT_SYNC: c_int = 0x4000;
+
+pub const STATX_TYPE: c_uint = 0x1;
+pub const STATX_MODE: c_uint = 0x2;
+pub const STATX_NLINK: c_uint = 0x4;
+pub const STATX_UID: c_uint = 0x8;
+pub const STATX_GID: c_uint = 0x10;
+pub const STATX_ATIME: c_uint = 0x20;
+pub const STATX_MTIME: c_uint = 0x40;
+pub const STATX_CTIME: c_uint = 0x80;
+pub const STATX_INO: c_uint = 0x100;
+pub const STATX_SIZE: c_uint = 0x200;
+pub const STATX_BLOCKS: c_uint = 0x400;
+/// Everything that is also in `struct stat`
+pub const STATX_BASIC_STATS: c_uint = 0x7ff;
+pub const STATX_BTIME: c_uint = 0x800;
+
+pub const RESOLVE_NO_XDEV: u64 = 0x01;
+pub const RESOLVE_NO_MAGICLINKS: u64 = 0x02;
+pub const RESOLVE_NO_SYMLINKS: u64 = 0x04;
+pub const RESOLVE_BENEATH: u64 = 0x08;
+pub const RESOLVE_IN_ROOT: u64 = 0x10;
+pub const RESOLVE_CACHED: u64 = 0x20;
+
+pub const F_DUPFD: c_int = 0;
+pub const F_GETFD: c_int = 1;
+pub const F_SETFD: c_int = 2;
+pub const F_GETFL: c_int = 3;
+pub const F_SETFL: c_int = 4;
+pub const F_DUPFD_CLOEXEC: c_int = 1030;
+pub const F_SETPIPE_SZ: c_int = 1031;
+pub const F_GETPIPE_SZ: c_int = 1032;
+
+/// The only flag `F_GETFD` and `F_SETFD` have, which is separate from `O_CLOEXEC`
+pub const FD_CLOEXEC: c_int = 1;
+
+pub const F_OK: c_int = 0;
+pub const X_OK: c_int = 1;
+pub const W_OK: c_int = 2;
+pub const R_OK: c_int = 4;
+
+pub const DT_UNKNOWN: u8 = 0;
+pub const DT_FIFO: u8 = 1;
+pub const DT_CHR: u8 = 2;
+pub const DT_DIR: u8 = 4;
+pub const DT_BLK: u8 = 6;
+pub const DT_REG: u8 = 8;
+pub const DT_LNK: u8 = 10;
+pub const DT_SOCK: u8 = 12;
+
+pub const O_ACCMODE: c_int = 0o3;
+pub const O_RDONLY: c_int = 0o0;
+pub const O_WRONLY: c_int = 0o1;
+pub const O_RDWR: c_int = 0o2;
+pub const O_CREAT: c_int = 0o100;
+pub const O_EXCL: c_int = 0o200;
+pub const O_NOCTTY: c_int = 0o400;
+pub const O_TRUNC: c_int = 0o1000;
+pub const O_APPEND: c_int = 0o2000;
+pub const O_NONBLOCK: c_int = 0o4000;
+pub const O_DSYNC: c_int = 0o10000;
+pub const O_ASYNC: c_int = 0o20000;
+pub const O_NOATIME: c_int = 0o1000000;
+pub const O_CLOEXEC: c_int = 0o2000000;
+pub const O_SYNC: c_int = 0o4000000 | O_DSYNC;
+pub const O_PATH: c_int = 0o10000000;
+pub const O_TMPFILE: c_int = 0o20000000 | O_DIRECTORY;
+
+pub const S_IFMT: mode_t = 0o170000;
+pub const S_IFSOCK: mode_t = 0o140000;
+pub const S_IFLNK: mode_t = 0o120000;
+pub const S_IFREG: mode_t = 0o100000;
+pub const S_IFBLK: mode_t = 0o060000;
+pub const S_IFDIR: mode_t = 0o040000;
+pub const S_IFCHR: mode_t = 0o020000;
+pub const S_IFIFO: mode_t = 0o010000;
+pub const S_ISUID: mode_t = 0o4000;
+pub const S_ISGID: mode_t = 0o2000;
+pub const S_ISVTX: mode_t = 0o1000;
+pub const S_IRWXU: mode_t = 0o700;
+pub const S_IRUSR: mode_t = 0o400;
+pub const S_IWUSR: mode_t = 0o200;
+pub const S_IXUSR: mode_t = 0o100;
+pub const S_IRWXG: mode_t = 0o070;
+pub const S_IRGRP: mode_t = 0o040;
+pub const S_IWGRP: mode_t = 0o020;
+pub const S_IXGRP: mode_t = 0o010;
+pub const S_IRWXO: mode_t = 0o007;
+pub const S_IROTH: mode_t = 0o004;
+pub const S_IWOTH: mode_t = 0o002;
+pub const S_IXOTH: mode_t = 0o001;
+
+pub const SEEK_SET: c_int = 0;
+pub const SEEK_CUR: c_int = 1;
+pub const SEEK_END: c_int = 2;
+
+pub const PROT_NONE: c_int = 0x0;
+pub const PROT_READ: c_int = 0x1;
+pub const PROT_WRITE: c_int = 0x2;
+pub const PROT_EXEC: c_int = 0x4;
+
+pub const MAP_SHARED: c_int = 0x01;
+pub const MAP_PRIVATE: c_int = 0x02;
+pub const MAP_FIXED: c_int = 0x10;
+pub const MAP_ANONYMOUS: c_int = 0x20;
+pub const MAP_ANON: c_int = MAP_ANONYMOUS;
+pub const MAP_GROWSDOWN: c_int = 0x0100;
+pub const MAP_NORESERVE: c_int = 0x4000;
+pub const MAP_POPULATE: c_int = 0x8000;
+pub const MAP_STACK: c_int = 0x020000;
+pub const MAP_HUGETLB: c_int = 0x040000;
+pub const MAP_FIXED_NOREPLACE: c_int = 0x100000;
+pub const MAP_HUGE_SHIFT: c_int = 26;
+pub const MAP_HUGE_2MB: c_int = 21 << MAP_HUGE_SHIFT;
+pub const MAP_HUGE_1GB: c_int = 30 << MAP_HUGE_SHIFT;
+
+pub const MREMAP_MAYMOVE: c_int = 1;
+pub const MREMAP_FIXED: c_int = 2;
+
+pub const MS_ASYNC: c_int = 1;
+pub const MS_INVALIDATE: c_int = 2;
+pub const MS_SYNC: c_int = 4;
+
+pub const MADV_NORMAL: c_int = 0;
+pub const MADV_RANDOM: c_int = 1;
+pub const MADV_SEQUENTIAL: c_int = 2;
+pub const MADV_WILLNEED: c_int = 3;
+pub const MADV_DONTNEED: c_int = 4;
+pub const MADV_FREE: c_int = 8;
+pub const MADV_REMOVE: c_int = 9;
+pub const MADV_DONTFORK: c_int = 10;
+pub const MADV_DOFORK: c_int = 11;
+pub const MADV_MERGEABLE: c_int = 12;
+pub const MADV_UNMERGEABLE: c_int = 13;
+pub const MADV_HUGEPAGE: c_int = 14;
+pub const MADV_NOHUGEPAGE: c_int = 15;
+pub const MADV_DONTDUMP: c_int = 16;
+pub const MADV_DODUMP: c_int = 17;
+pub const MADV_WIPEONFORK: c_int = 18;
+pub const MADV_KEEPONFORK: c_int = 19;
+pub const MADV_HWPOISON: c_int = 100;
+pub const MADV_SOFT_OFFLINE: c_int = 101;
+
+pub const IPC_PRIVATE: key_t = 0;
+pub const IPC_CREAT: c_int = 0o1000;
+pub const IPC_EXCL: c_int = 0o2000;
+pub const SHM_HUGETLB: c_int = 0o4000;
+pub const SHM_NORESERVE: c_int = 0o10000;
+
+pub const FUTEX_WAIT: c_int = 0;
+pub const FUTEX_WAKE: c_int = 1;
+pub const FUTEX_REQUEUE: c_int = 3;
+pub const FUTEX_PRIVATE_FLAG: c_int = 128;
+
+pub const CLONE_VM: c_int = 0x100;
+pub const CLONE_PIDFD: c_int = 0x1000;
+pub const CLONE_FS: c_int = 0x200;
+pub const CLONE_FILES: c_int = 0x400;
+pub const CLONE_SIGHAND: c_int = 0x800;
+pub const CLONE_VFORK: c_int = 0x4000;
+pub const CLONE_PARENT: c_int = 0x8000;
+pub const CLONE_THREAD: c_int = 0x10000;
+pub const CLONE_SYSVSEM: c_int = 0x40000;
+pub const CLONE_SETTLS: c_int = 0x80000;
+pub const CLONE_PARENT_SETTID: c_int = 0x100000;
+pub const CLONE_CHILD_CLEARTID: c_int = 0x200000;
+pub const CLONE_CHILD_SETTID: c_int = 0x1000000;
+
+pub const WNOHANG: c_int = 1;
+pub const WUNTRACED: c_int = 2;
+
+// The status wait4 reports has the exit code or stop signal in the second byte, whether a core was
+// dumped in the top bit of the first, and the signal which killed the child in the rest of it
+
+/// Whether the child exited rather than being killed by a signal
+#[inline]
+pub const fn WIFEXITED(status: c_int) -> bool {
+    status & 0x7f == 0
+}
+
+/// The code the child exited with, if [`WIFEXITED`]
+#[inline]
+pub const fn WEXITSTATUS(status: c_int) -> c_int {
+    (status >> 8) & 0xff
+}
+
+/// Whether a signal killed the child
+#[inline]
+pub const fn WIFSIGNALED(status: c_int) -> bool {
+    matches!(status & 0x7f, 1..=0x7e)
+}
+
+/// The signal which killed the child, if [`WIFSIGNALED`]
+#[inline]
+pub const fn WTERMSIG(status: c_int) -> c_int {
+    status & 0x7f
+}
+
+/// Whether the child dumped core when it was killed
+#[inline]
+pub const fn WCOREDUMP(status: c_int) -> bool {
+    status & 0x80 != 0
+}
+
+/// Whether the child is stopped, which is only reported with `WUNTRACED`
+#[inline]
+pub const fn WIFSTOPPED(status: c_int) -> bool {
+    status & 0xff == 0x7f
+}
+
+/// The signal which stopped the child, if [`WIFSTOPPED`]
+#[inline]
+pub const fn WSTOPSIG(status: c_int) -> c_int {
+    WEXITSTATUS(status)
+}
+
+pub const POLLIN: i16 = 0x1;
+pub const POLLPRI: i16 = 0x2;
+pub const POLLOUT: i16 = 0x4;
+pub const POLLERR: i16 = 0x8;
+pub const POLLHUP: i16 = 0x10;
+pub const POLLNVAL: i16 = 0x20;
+
+pub const SIGHUP: c_int = 1;
+pub const SIGINT: c_int = 2;
+pub const SIGQUIT: c_int = 3;
+pub const SIGILL: c_int = 4;
+pub const SIGTRAP: c_int = 5;
+pub const SIGABRT: c_int = 6;
+pub const SIGBUS: c_int = 7;
+pub const SIGFPE: c_int = 8;
+pub const SIGKILL: c_int = 9;
+pub const SIGUSR1: c_int = 10;
+pub const SIGSEGV: c_int = 11;
+pub const SIGUSR2: c_int = 12;
+pub const SIGPIPE: c_int = 13;
+pub const SIGALRM: c_int = 14;
+pub const SIGTERM: c_int = 15;
+pub const SIGSTKFLT: c_int = 16;
+pub const SIGCHLD: c_int = 17;
+pub const SIGCONT: c_int = 18;
+pub const SIGSTOP: c_int = 19;
+pub const SIGTSTP: c_int = 20;
+pub const SIGTTIN: c_int = 21;
+pub const SIGTTOU: c_int = 22;
+pub const SIGURG: c_int = 23;
+pub const SIGXCPU: c_int = 24;
+pub const SIGXFSZ: c_int = 25;
+pub const SIGVTALRM: c_int = 26;
+pub const SIGPROF: c_int = 27;
+pub const SIGWINCH: c_int = 28;
+pub const SIGIO: c_int = 29;
+pub const SIGPWR: c_int = 30;
+pub const SIGSYS: c_int = 31;
+
+pub const SA_NOCLDSTOP: c_ulong = 0x0000_0001;
+pub const SA_NOCLDWAIT: c_ulong = 0x0000_0002;
+pub const SA_SIGINFO: c_ulong = 0x0000_0004;
+pub const SA_RESTORER: c_ulong = 0x0400_0000;
+pub const SA_ONSTACK: c_ulong = 0x0800_0000;
+pub const SA_RESTART: c_ulong = 0x1000_0000;
+pub const SA_NODEFER: c_ulong = 0x4000_0000;
+pub const SA_RESETHAND: c_ulong = 0x8000_0000;
+
+pub const RLIMIT_CPU: c_int = 0;
+pub const RLIMIT_FSIZE: c_int = 1;
+pub const RLIMIT_DATA: c_int = 2;
+pub const RLIMIT_STACK: c_int = 3;
+pub const RLIMIT_CORE: c_int = 4;
+pub const RLIMIT_NOFILE: c_int = 7;
+pub const RLIMIT_AS: c_int = 9;
+
+pub const TIOCGWINSZ: c_ulong = 0x5413;
+
+macro_rules! errnos {
+    ($($name:ident = $value:expr => $description:expr,)*) => {
+        $(pub const $name: c_int = $value;)*
+
+        /// The `errno` values, for matching on by name
+        ///
+        /// [`Error`](crate::Error) holds any number the kernel returns, which is one of these
+        /// unless the kernel is newer than this table.
+        #[repr(i32)]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #[non_exhaustive]
+        pub enum Errno {
+            $($name = $value,)*
+        }
+
+        impl Errno {
+            /// The variant for `errno`, if it is one Linux defines
+            #[inline]
+            pub const fn from_raw(errno: c_int) -> Option<Self> {
+                match errno {
+                    $($name => Some(Self::$name),)*
+                    _ => None,
+                }
+            }
+
+            /// The name of the constant, like `"ENOENT"`
+            #[inline]
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $(Self::$name => stringify!($name),)*
+                }
+            }
+
+            /// The message `strerror` gives, like `"No such file or directory"`
+            #[inline]
+            pub const fn description(self) -> &'static str {
+                match self {
+                    $(Self::$name => $description,)*
+                }
+            }
+        }
+    };
+}
+
+// The errno values, which are the same on every architecture but alpha, mips, parisc, and sparc
+errnos! {
+    EPERM = 1 => "Operation not permitted",
+    ENOENT = 2 => "No such file or directory",
+    ESRCH = 3 => "No such process",
+    EINTR = 4 => "Interrupted system call",
+    EIO = 5 => "Input/output error",
+    ENXIO = 6 => "No such device or address",
+    E2BIG = 7 => "Argument list too long",
+    ENOEXEC = 8 => "Exec format error",
+    EBADF = 9 => "Bad file descriptor",
+    ECHILD = 10 => "No child processes",
+    EAGAIN = 11 => "Resource temporarily unavailable",
+    ENOMEM = 12 => "Cannot allocate memory",
+    EACCES = 13 => "Permission denied",
+    EFAULT = 14 => "Bad address",
+    ENOTBLK = 15 => "Block device required",
+    EBUSY = 16 => "Device or resource busy",
+    EEXIST = 17 => "File exists",
+    EXDEV = 18 => "Invalid cross-device link",
+    ENODEV = 19 => "No such device",
+    ENOTDIR = 20 => "Not a directory",
+    EISDIR = 21 => "Is a directory",
+    EINVAL = 22 => "Invalid argument",
+    ENFILE = 23 => "Too many open files in system",
+    EMFILE = 24 => "Too many open files",
+    ENOTTY = 25 => "Inappropriate ioctl for device",
+    ETXTBSY = 26 => "Text file busy",
+    EFBIG = 27 => "File too large",
+    ENOSPC = 28 => "No space left on device",
+    ESPIPE = 29 => "Illegal seek",
+    EROFS = 30 => "Read-only file system",
+    EMLINK = 31 => "Too many links",
+    EPIPE = 32 => "Broken pipe",
+    EDOM = 33 => "Numerical argument out of domain",
+    ERANGE = 34 => "Numerical result out of range",
+    EDEADLK = 35 => "Resource deadlock avoided",
+    ENAMETOOLONG = 36 => "File name too long",
+    ENOLCK = 37 => "No locks available",
+    ENOSYS = 38 => "Function not implemented",
+    ENOTEMPTY = 39 => "Directory not empty",
+    ELOOP = 40 => "Too many levels of symbolic links",
+    ENOMSG = 42 => "No message of desired type",
+    EIDRM = 43 => "Identifier removed",
+    ECHRNG = 44 => "Channel number out of range",
+    EL2NSYNC = 45 => "Level 2 not synchronized",
+    EL3HLT = 46 => "Level 3 halted",
+    EL3RST = 47 => "Level 3 reset",
+    ELNRNG = 48 => "Link number out of range",
+    EUNATCH = 49 => "Protocol driver not attached",
+    ENOCSI = 50 => "No CSI structure available",
+    EL2HLT = 51 => "Level 2 halted",
+    EBADE = 52 => "Invalid exchange",
+    EBADR = 53 => "Invalid request descriptor",
+    EXFULL = 54 => "Exchange full",
+    ENOANO = 55 => "No anode",
+    EBADRQC = 56 => "Invalid request code",
+    EBADSLT = 57 => "Invalid slot",
+    EBFONT = 59 => "Bad font file format",
+    ENOSTR = 60 => "Device not a stream",
+    ENODATA = 61 => "No data available",
+    ETIME = 62 => "Timer expired",
+    ENOSR = 63 => "Out of streams resources",
+    ENONET = 64 => "Machine is not on the network",
+    ENOPKG = 65 => "Package not installed",
+    EREMOTE = 66 => "Object is remote",
+    ENOLINK = 67 => "Link has been severed",
+    EADV = 68 => "Advertise error",
+    ESRMNT = 69 => "Srmount error",
+    ECOMM = 70 => "Communication error on send",
+    EPROTO = 71 => "Protocol error",
+    EMULTIHOP = 72 => "Multihop attempted",
+    EDOTDOT = 73 => "RFS specific error",
+    EBADMSG = 74 => "Bad message",
+    EOVERFLOW = 75 => "Value too large for defined data type",
+    ENOTUNIQ = 76 => "Name not unique on network",
+    EBADFD = 77 => "File descriptor in bad state",
+    EREMCHG = 78 => "Remote address changed",
+    ELIBACC = 79 => "Can not access a needed shared library",
+    ELIBBAD = 80 => "Accessing a corrupted shared library",
+    ELIBSCN = 81 => ".lib section in a.out corrupted",
+    ELIBMAX = 82 => "Attempting to link in too many shared libraries",
+    ELIBEXEC = 83 => "Cannot exec a shared library directly",
+    EILSEQ = 84 => "Invalid or incomplete multibyte or wide character",
+    ERESTART = 85 => "Interrupted system call should be restarted",
+    ESTRPIPE = 86 => "Streams pipe error",
+    EUSERS = 87 => "Too many users",
+    ENOTSOCK = 88 => "Socket operation on non-socket",
+    EDESTADDRREQ = 89 => "Destination address required",
+    EMSGSIZE = 90 => "Message too long",
+    EPROTOTYPE = 91 => "Protocol wrong type for socket",
+    ENOPROTOOPT = 92 => "Protocol not available",
+    EPROTONOSUPPORT = 93 => "Protocol not supported",
+    ESOCKTNOSUPPORT = 94 => "Socket type not supported",
+    EOPNOTSUPP = 95 => "Operation not supported",
+    EPFNOSUPPORT = 96 => "Protocol family not supported",
+    EAFNOSUPPORT = 97 => "Address family not supported by protocol",
+    EADDRINUSE = 98 => "Address already in use",
+    EADDRNOTAVAIL = 99 => "Cannot assign requested address",
+    ENETDOWN = 100 => "Network is down",
+    ENETUNREACH = 101 => "Network is unreachable",
+    ENETRESET = 102 => "Network dropped connection on reset",
+    ECONNABORTED = 103 => "Software caused connection abort",
+    ECONNRESET = 104 => "Connection reset by peer",
+    ENOBUFS = 105 => "No buffer space available",
+    EISCONN = 106 => "Transport endpoint is already connected",
+    ENOTCONN = 107 => "Transport endpoint is not connected",
+    ESHUTDOWN = 108 => "Cannot send after transport endpoint shutdown",
+    ETOOMANYREFS = 109 => "Too many references: cannot splice",
+    ETIMEDOUT = 110 => "Connection timed out",
+    ECONNREFUSED = 111 => "Connection refused",
+    EHOSTDOWN = 112 => "Host is down",
+    EHOSTUNREACH = 113 => "No route to host",
+    EALREADY = 114 => "Operation already in progress",
+    EINPROGRESS = 115 => "Operation now in progress",
+    ESTALE = 116 => "Stale file handle",
+    EUCLEAN = 117 => "Structure needs cleaning",
+    ENOTNAM = 118 => "Not a XENIX named type file",
+    ENAVAIL = 119 => "No XENIX semaphores available",
+    EISNAM = 120 => "Is a named type file",
+    EREMOTEIO = 121 => "Remote I/O error",
+    EDQUOT = 122 => "Disk quota exceeded",
+    ENOMEDIUM = 123 => "No medium found",
+    EMEDIUMTYPE = 124 => "Wrong medium type",
+    ECANCELED = 125 => "Operation canceled",
+    ENOKEY = 126 => "Required key not available",
+    EKEYEXPIRED = 127 => "Key has expired",
+    EKEYREVOKED = 128 => "Key has been revoked",
+    EKEYREJECTED = 129 => "Key was rejected by service",
+    EOWNERDEAD = 130 => "Owner died",
+    ENOTRECOVERABLE = 131 => "State not recoverable",
+    ERFKILL = 132 => "Operation not possible due to RF-kill",
+    EHWPOISON = 133 => "Memory page has hardware error",
+}
+
+pub const EWOULDBLOCK: c_int = EAGAIN;
+pub const EDEADLOCK: c_int = EDEADLK;
+pub const ENOTSUP: c_int = EOPNOTSUPP;
+
+impl Errno {
+    pub const EWOULDBLOCK: Self = Self::EAGAIN;
+    pub const EDEADLOCK: Self = Self::EDEADLK;
+    pub const ENOTSUP: Self = Self::EOPNOTSUPP;
+
+    /// The number itself
+    #[inline]
+    pub const fn raw(self) -> c_int {
+        self as c_int
+    }
+}
+
+/// The name of the constant for `errno`, like `"ENOENT"`
+#[inline]
+pub fn errno_name(errno: c_int) -> Option<&'static str> {
+    Errno::from_raw(errno).map(Errno::name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::mem::{align_of, offset_of, size_of};
+
+    #[test]
+    fn layouts_match_the_kernel() {
+        assert_eq!(size_of::<timespec>(), 2 * size_of::<usize>());
+        assert_eq!(size_of::<timeval>(), 2 * size_of::<usize>());
+        assert_eq!(size_of::<rlimit64>(), 16);
+        assert_eq!(size_of::<winsize>(), 8);
+        assert_eq!(size_of::<pollfd>(), 8);
+        assert_eq!(size_of::<sigset_t>(), 8);
+        assert_eq!(offset_of!(linux_dirent64, d_reclen), 16);
+        assert_eq!(offset_of!(linux_dirent64, d_type), 18);
+        assert_eq!(offset_of!(linux_dirent64, d_name), 19);
+        assert_eq!(size_of::<statx>(), 256);
+        assert_eq!(offset_of!(statx, stx_ino), 32);
+        assert_eq!(offset_of!(statx, stx_btime), 80);
+        assert_eq!(offset_of!(statx, stx_mnt_id), 144);
+        assert_eq!(size_of::<open_how>(), 24);
+        assert_eq!(size_of::<clone_args>(), 88);
+        #[cfg(target_arch = "x86_64")]
+        {
+            assert_eq!(size_of::<stat>(), 144);
+            assert_eq!(offset_of!(stat, st_mode), 24);
+            assert_eq!(offset_of!(stat, st_size), 48);
+            assert_eq!(offset_of!(stat, st_mtime), 88);
+        }
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(align_of::<stat>(), 8);
+        #[cfg(target_arch = "x86")]
+        {
+            assert_eq!(size_of::<stat>(), 96);
+            assert_eq!(align_of::<stat>(), 4);
+            assert_eq!(offset_of!(stat, st_mode), 16);
+            assert_eq!(offset_of!(stat, st_size), 44);
+            assert_eq!(offset_of!(stat, st_mtime), 72);
+            assert_eq!(offset_of!(stat, st_ino), 88);
+        }
+        #[cfg(target_arch = "aarch64")]
+        {
+            assert_eq!(size_of::<stat>(), 128);
+            assert_eq!(offset_of!(stat, st_mode), 16);
+            assert_eq!(offset_of!(stat, st_size), 48);
+            assert_eq!(offset_of!(stat, st_mtime), 88);
+        }
+    }
+
+    #[test]
+    fn layouts_match_libc_where_it_uses_the_kernels() {
+        assert_eq!(size_of::<stat>(), size_of::<libc::stat64>());
+        assert_eq!(offset_of!(stat, st_size), offset_of!(libc::stat64, st_size));
+        assert_eq!(
+            offset_of!(stat, st_mtime),
+            offset_of!(libc::stat64, st_mtime)
+        );
+        assert_eq!(size_of::<timespec>(), size_of::<libc::timespec>());
+        assert_eq!(size_of::<pollfd>(), size_of::<libc::pollfd>());
+        assert_eq!(size_of::<statx>(), size_of::<libc::statx>());
+        assert_eq!(
+            offset_of!(statx, stx_mtime),
+            offset_of!(libc::statx, stx_mtime)
+        );
+        assert_eq!(size_of::<winsize>(), size_of::<libc::winsize>());
+        // glibc's is large enough for 1024 signals
+        assert!(size_of::<sigset_t>() < size_of::<libc::sigset_t>());
+    }
+
+    #[test]
+    fn constants_match_libc() {
+        assert_eq!(O_DIRECTORY, libc::O_DIRECTORY);
+        assert_eq!(O_NOFOLLOW, libc::O_NOFOLLOW);
+        assert_eq!(O_DIRECT, libc::O_DIRECT);
+        assert_eq!(O_CLOEXEC, libc::O_CLOEXEC);
+        assert_eq!(O_SYNC, libc::O_SYNC);
+        assert_eq!(O_TMPFILE, libc::O_TMPFILE);
+        assert_eq!(S_IFMT, libc::S_IFMT);
+        assert_eq!(R_OK, libc::R_OK);
+        assert_eq!(F_DUPFD_CLOEXEC, libc::F_DUPFD_CLOEXEC);
+        assert_eq!(F_GETPIPE_SZ, libc::F_GETPIPE_SZ);
+        assert_eq!(FD_CLOEXEC, libc::FD_CLOEXEC);
+        assert_eq!(STATX_BTIME, libc::STATX_BTIME);
+        assert_eq!(CLONE_PIDFD, libc::CLONE_PIDFD);
+        assert_eq!(POLLNVAL, libc::POLLNVAL);
+        assert_eq!(WNOHANG, libc::WNOHANG);
+        assert_eq!(DT_LNK, libc::DT_LNK);
+        assert_eq!(MAP_HUGE_1GB, libc::MAP_HUGE_1GB);
+        assert_eq!(MAP_FIXED_NOREPLACE, libc::MAP_FIXED_NOREPLACE);
+        assert_eq!(CLONE_CHILD_CLEARTID, libc::CLONE_CHILD_CLEARTID);
+        assert_eq!(SA_ONSTACK, libc::SA_ONSTACK as c_ulong);
+        assert_eq!(TIOCGWINSZ, libc::TIOCGWINSZ);
+        assert_eq!(RLIMIT_STACK, libc::RLIMIT_STACK as c_int);
+        assert_eq!(SIGSYS, libc::SIGSYS);
+        assert_eq!(EHWPOISON, libc::EHWPOISON);
+        assert_eq!(errno_name(ENOENT), Some("ENOENT"));
+        assert_eq!(errno_name(EWOULDBLOCK), Some("EAGAIN"));
+        assert_eq!(errno_name(0), None);
+        assert_eq!(ENOTSUP, libc::ENOTSUP);
+        assert_eq!(Errno::from_raw(ENOENT), Some(Errno::ENOENT));
+        assert_eq!(Errno::from_raw(41), None);
+        assert_eq!(Errno::EWOULDBLOCK.raw(), EAGAIN);
+        // glibc makes this 0 on 64-bit targets, where the kernel sets it on every open anyway
+        assert_ne!(O_LARGEFILE, 0);
+    }
+
+    #[test]
+    fn wait_statuses_match_libc() {
+        // Exited with 0 and 3, killed by SIGKILL, dumped core after SIGSEGV, stopped by SIGSTOP,
+        // and continued
+        for status in [0, 3 << 8, 9, 0x80 | 11, (19 << 8) | 0x7f, 0xffff] {
+            assert_eq!(WIFEXITED(status), libc::WIFEXITED(status));
+            assert_eq!(WEXITSTATUS(status), libc::WEXITSTATUS(status));
+            assert_eq!(WIFSIGNALED(status), libc::WIFSIGNALED(status));
+            assert_eq!(WTERMSIG(status), libc::WTERMSIG(status));
+            assert_eq!(WCOREDUMP(status), libc::WCOREDUMP(status));
+            assert_eq!(WIFSTOPPED(status), libc::WIFSTOPPED(status));
+            assert_eq!(WSTOPSIG(status), libc::WSTOPSIG(status));
+        }
+    }
+
+    #[test]
+    fn errnos_match_strerror() {
+        for errno in 1..4096 {
+            let message = unsafe { core::ffi::CStr::from_ptr(libc::strerror(errno)) };
+            let message = message.to_str().unwrap();
+            match Errno::from_raw(errno) {
+                Some(known) => assert_eq!(known.description(), message),
+                None => assert!(message.starts_with("Unknown error"), "{}", message),
+            }
+        }
+    }
+}
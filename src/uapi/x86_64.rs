@@ -0,0 +1,30 @@
+use super::{c_int, c_long, c_uint, c_ulong};
+
+pub const O_DIRECT: c_int = 0o40000;
+pub const O_LARGEFILE: c_int = 0o100000;
+pub const O_DIRECTORY: c_int = 0o200000;
+pub const O_NOFOLLOW: c_int = 0o400000;
+
+/// The result of `fstat` and `newfstatat`, which on x86_64 is also glibc's `struct stat64`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct stat {
+    pub st_dev: c_ulong,
+    pub st_ino: c_ulong,
+    pub st_nlink: c_ulong,
+    pub st_mode: c_uint,
+    pub st_uid: c_uint,
+    pub st_gid: c_uint,
+    pub __pad0: c_uint,
+    pub st_rdev: c_ulong,
+    pub st_size: c_long,
+    pub st_blksize: c_long,
+    pub st_blocks: c_long,
+    pub st_atime: c_ulong,
+    pub st_atime_nsec: c_ulong,
+    pub st_mtime: c_ulong,
+    pub st_mtime_nsec: c_ulong,
+    pub st_ctime: c_ulong,
+    pub st_ctime_nsec: c_ulong,
+    pub __unused: [c_long; 3],
+}
@@ -0,0 +1,207 @@
+//! Locates the functions the kernel maps into every process so that reading the clock does not
+//! require entering the kernel.
+
+use crate::{
+    elf::{self, Dyn, Ehdr, Phdr, Sym},
+    uapi::{self, c_int},
+    CStr,
+};
+use core::{
+    mem,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+// i386 has the same names, for the versions which take a 32-bit time_t
+#[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
+const NAMES: [&[u8]; 4] = [
+    b"__vdso_clock_gettime",
+    b"__vdso_gettimeofday",
+    b"__vdso_getcpu",
+    b"__vdso_time",
+];
+
+#[cfg(target_arch = "aarch64")]
+const NAMES: [&[u8]; 4] = [
+    b"__kernel_clock_gettime",
+    b"__kernel_gettimeofday",
+    b"__kernel_getcpu",
+    b"__kernel_time",
+];
+
+const CLOCK_GETTIME: usize = 0;
+const GETTIMEOFDAY: usize = 1;
+const GETCPU: usize = 2;
+const TIME: usize = 3;
+
+static RESOLVED: AtomicBool = AtomicBool::new(false);
+static SYMBOLS: [AtomicUsize; 4] = [
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+];
+
+pub(crate) type ClockGettime = unsafe extern "C" fn(c_int, *mut uapi::timespec) -> c_int;
+pub(crate) type Gettimeofday = unsafe extern "C" fn(*mut uapi::timeval, *mut u8) -> c_int;
+pub(crate) type Getcpu = unsafe extern "C" fn(*mut u32, *mut u32, *mut u8) -> c_int;
+pub(crate) type Time = unsafe extern "C" fn(*mut uapi::time_t) -> uapi::time_t;
+
+#[inline]
+pub(crate) fn clock_gettime() -> Option<ClockGettime> {
+    symbol(CLOCK_GETTIME).map(|f| unsafe { mem::transmute::<usize, ClockGettime>(f) })
+}
+
+#[inline]
+pub(crate) fn gettimeofday() -> Option<Gettimeofday> {
+    symbol(GETTIMEOFDAY).map(|f| unsafe { mem::transmute::<usize, Gettimeofday>(f) })
+}
+
+#[inline]
+pub(crate) fn getcpu() -> Option<Getcpu> {
+    symbol(GETCPU).map(|f| unsafe { mem::transmute::<usize, Getcpu>(f) })
+}
+
+#[inline]
+pub(crate) fn time() -> Option<Time> {
+    symbol(TIME).map(|f| unsafe { mem::transmute::<usize, Time>(f) })
+}
+
+fn symbol(index: usize) -> Option<usize> {
+    // Resolving is idempotent, so if two threads race here they just both do the work.
+    if !RESOLVED.load(Ordering::Acquire) {
+        if let Some(ehdr) = crate::env::sysinfo_ehdr() {
+            let addresses = unsafe { resolve(ehdr) };
+            for (symbol, address) in SYMBOLS.iter().zip(addresses.iter()) {
+                symbol.store(*address, Ordering::Relaxed);
+            }
+        }
+        RESOLVED.store(true, Ordering::Release);
+    }
+    match SYMBOLS[index].load(Ordering::Relaxed) {
+        0 => None,
+        address => Some(address),
+    }
+}
+
+/// Look up the address of each of `NAMES` in the vDSO, or 0 if the symbol is missing
+///
+/// # Safety
+///
+/// `ehdr` must point to the start of the vDSO image, as found in `AT_SYSINFO_EHDR`
+unsafe fn resolve(ehdr: *const u8) -> [usize; 4] {
+    let mut addresses = [0; 4];
+
+    let header = &*ehdr.cast::<Ehdr>();
+    if header.e_ident[..4] != *b"\x7fELF" {
+        return addresses;
+    }
+    let phdrs = core::slice::from_raw_parts(
+        ehdr.add(header.e_phoff as usize).cast::<Phdr>(),
+        header.e_phnum as usize,
+    );
+
+    // The vDSO is a prelinked shared object; its addresses are relative to where it is loaded,
+    // offset by the virtual address of its (only) loadable segment.
+    let mut load_offset = None;
+    let mut dynamic = None;
+    for phdr in phdrs {
+        match phdr.p_type {
+            elf::PT_LOAD if load_offset.is_none() => {
+                load_offset = Some(
+                    (ehdr as usize)
+                        .wrapping_add(phdr.p_offset as usize)
+                        .wrapping_sub(phdr.p_vaddr as usize),
+                );
+            }
+            elf::PT_DYNAMIC => dynamic = Some(ehdr.add(phdr.p_offset as usize).cast::<Dyn>()),
+            _ => {}
+        }
+    }
+    let (load_offset, mut dynamic) = match (load_offset, dynamic) {
+        (Some(l), Some(d)) => (l, d),
+        _ => return addresses,
+    };
+
+    let mut strtab = None;
+    let mut symtab = None;
+    let mut hash = None;
+    let mut gnu_hash = None;
+    while (*dynamic).d_tag != elf::DT_NULL {
+        let address = load_offset.wrapping_add((*dynamic).d_val as usize);
+        match (*dynamic).d_tag {
+            elf::DT_STRTAB => strtab = Some(address as *const u8),
+            elf::DT_SYMTAB => symtab = Some(address as *const Sym),
+            elf::DT_HASH => hash = Some(address as *const u32),
+            elf::DT_GNU_HASH => gnu_hash = Some(address as *const u32),
+            _ => {}
+        }
+        dynamic = dynamic.add(1);
+    }
+    let (strtab, symtab) = match (strtab, symtab) {
+        (Some(s), Some(t)) => (s, t),
+        _ => return addresses,
+    };
+    let count = match (hash, gnu_hash) {
+        // The second word of a SysV hash table is the number of entries in the symbol table
+        (Some(hash), _) => *hash.add(1) as usize,
+        (None, Some(gnu_hash)) => gnu_hash_symbol_count(gnu_hash),
+        (None, None) => return addresses,
+    };
+
+    for sym in core::slice::from_raw_parts(symtab, count) {
+        if sym.kind() != elf::STT_FUNC
+            || (sym.binding() != elf::STB_GLOBAL && sym.binding() != elf::STB_WEAK)
+            || sym.st_shndx == elf::SHN_UNDEF
+        {
+            continue;
+        }
+        let name = CStr::from_ptr(strtab.add(sym.st_name as usize));
+        if let Some(i) = NAMES.iter().position(|n| name == *n) {
+            addresses[i] = load_offset.wrapping_add(sym.st_value as usize);
+        }
+    }
+
+    addresses
+}
+
+/// A GNU hash table does not record the number of symbols, but the last symbol is the end of the
+/// chain that starts at the highest bucket.
+unsafe fn gnu_hash_symbol_count(table: *const u32) -> usize {
+    let nbuckets = *table as usize;
+    let symoffset = *table.add(1) as usize;
+    let bloom_size = *table.add(2) as usize;
+    let buckets = table.add(4 + bloom_size * mem::size_of::<usize>() / 4);
+    let chains = buckets.add(nbuckets);
+
+    let mut last = (0..nbuckets)
+        .map(|i| *buckets.add(i) as usize)
+        .max()
+        .unwrap_or(0);
+    if last < symoffset {
+        return symoffset;
+    }
+    while *chains.add(last - symoffset) & 1 == 0 {
+        last += 1;
+    }
+    last + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_clock_gettime() {
+        let addresses = unsafe { resolve(libc::getauxval(libc::AT_SYSINFO_EHDR) as *const u8) };
+        assert_ne!(addresses[CLOCK_GETTIME], 0);
+        assert_ne!(addresses[GETTIMEOFDAY], 0);
+
+        let vdso = unsafe { mem::transmute::<usize, ClockGettime>(addresses[CLOCK_GETTIME]) };
+        let mut ts = uapi::timespec::default();
+        assert_eq!(unsafe { vdso(libc::CLOCK_REALTIME, &mut ts) }, 0);
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap();
+        assert!((now.as_secs() as uapi::time_t - ts.tv_sec).abs() <= 1);
+    }
+}
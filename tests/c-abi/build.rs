@@ -0,0 +1,9 @@
+fn main() {
+    println!("cargo:rerun-if-changed=src/checks.c");
+    // No libc headers, so the C code can only use what veneer exports
+    cc::Build::new()
+        .file("src/checks.c")
+        .flag("-ffreestanding")
+        .flag("-nostdinc")
+        .compile("checks");
+}
@@ -0,0 +1,111 @@
+// Each check returns 0, or the line of the first thing that was wrong
+
+typedef __SIZE_TYPE__ size_t;
+typedef __PTRDIFF_TYPE__ ssize_t;
+
+void *malloc(size_t size);
+void *calloc(size_t count, size_t size);
+void *realloc(void *ptr, size_t size);
+int posix_memalign(void **memptr, size_t alignment, size_t size);
+void free(void *ptr);
+char *getenv(const char *name);
+
+int *__errno_location(void);
+#define errno (*__errno_location())
+
+ssize_t read(int fd, void *buf, size_t count);
+ssize_t write(int fd, const void *buf, size_t count);
+int open(const char *path, int flags, ...);
+int close(int fd);
+
+int strcmp(const char *s1, const char *s2);
+int strncmp(const char *s1, const char *s2, size_t n);
+char *strchr(const char *s, int c);
+void *memchr(const void *s, int c, size_t n);
+
+int snprintf(char *buf, size_t size, const char *format, ...);
+
+#define O_RDONLY 0
+#define O_WRONLY 1
+#define O_CREAT 0100
+#define O_TRUNC 01000
+#define ENOENT 2
+
+#define CHECK(condition) \
+    if (!(condition)) \
+    return __LINE__
+
+int check_heap(void) {
+    char *bytes = malloc(32);
+    CHECK(bytes != 0);
+    CHECK((size_t)bytes % 16 == 0);
+    for (int i = 0; i < 32; i++)
+        bytes[i] = (char)i;
+    bytes = realloc(bytes, 1 << 20);
+    CHECK(bytes != 0);
+    for (int i = 0; i < 32; i++)
+        CHECK(bytes[i] == (char)i);
+    free(bytes);
+
+    int *zeroed = calloc(256, sizeof(int));
+    CHECK(zeroed != 0);
+    for (int i = 0; i < 256; i++)
+        CHECK(zeroed[i] == 0);
+    free(zeroed);
+
+    void *aligned = 0;
+    CHECK(posix_memalign(&aligned, 4096, 10) == 0);
+    CHECK((size_t)aligned % 4096 == 0);
+    free(aligned);
+    return 0;
+}
+
+int check_strings(void) {
+    const char *text = "name=value";
+    CHECK(strcmp(text, "name=value") == 0);
+    CHECK(strcmp(text, "name=valuf") < 0);
+    CHECK(strncmp(text, "name=other", 5) == 0);
+    CHECK(strchr(text, '=') == text + 4);
+    CHECK(strchr(text, '?') == 0);
+    CHECK(memchr(text, 'v', 10) == text + 5);
+    CHECK(memchr(text, 'v', 5) == 0);
+    return 0;
+}
+
+int check_files(const char *path) {
+    int fd = open(path, O_WRONLY | O_CREAT | O_TRUNC, 0644);
+    CHECK(fd >= 0);
+    CHECK(write(fd, "from C", 6) == 6);
+    CHECK(close(fd) == 0);
+
+    char buf[16];
+    fd = open(path, O_RDONLY);
+    CHECK(fd >= 0);
+    CHECK(read(fd, buf, sizeof(buf)) == 6);
+    CHECK(strncmp(buf, "from C", 6) == 0);
+    CHECK(close(fd) == 0);
+
+    errno = 0;
+    CHECK(open("/nonexistent/file", O_RDONLY) == -1);
+    CHECK(errno == ENOENT);
+    CHECK(close(-1) == -1);
+    return 0;
+}
+
+int check_environment(void) {
+    const char *value = getenv("VENEER_C_ABI");
+    CHECK(value != 0);
+    CHECK(strcmp(value, "set") == 0);
+    CHECK(getenv("VENEER_C_ABI_UNSET") == 0);
+    return 0;
+}
+
+int check_format(void) {
+    char buf[64];
+    int len = snprintf(buf, sizeof(buf), "%s=%d, %05u, 0x%lx, %-3c|", "n", -7, 42u, 0xbeefUL, 'c');
+    CHECK(len == 25);
+    CHECK(strcmp(buf, "n=-7, 00042, 0xbeef, c  |") == 0);
+    CHECK(snprintf(buf, 4, "%zu", (size_t)123456) == 6);
+    CHECK(strcmp(buf, "123") == 0);
+    return 0;
+}
@@ -0,0 +1,40 @@
+#![no_std]
+#![no_main]
+
+extern "C" {
+    fn check_heap() -> i32;
+    fn check_strings() -> i32;
+    fn check_files(path: *const u8) -> i32;
+    fn check_environment() -> i32;
+    fn check_format() -> i32;
+}
+
+#[veneer::test]
+fn heap() {
+    assert_eq!(unsafe { check_heap() }, 0);
+}
+
+#[veneer::test]
+fn strings() {
+    assert_eq!(unsafe { check_strings() }, 0);
+}
+
+#[veneer::test]
+fn files_and_errno() {
+    assert_eq!(unsafe { check_files(b"/tmp/veneer-c-abi\0".as_ptr()) }, 0);
+}
+
+#[veneer::test]
+fn environment() {
+    assert_eq!(unsafe { check_environment() }, 0);
+}
+
+#[veneer::test]
+fn format() {
+    assert_eq!(unsafe { check_format() }, 0);
+}
+
+#[veneer::main]
+fn main() -> veneer::process::ExitCode {
+    veneer::test_main()
+}
@@ -0,0 +1,18 @@
+//! Runs a program which links C code against the functions the `c-abi` feature exports
+
+use std::process::Command;
+
+mod common;
+
+#[test]
+fn c_code_runs_on_veneer() {
+    let binary = common::build("c-abi", "debug", &[], common::STATIC_PIE);
+
+    let output = Command::new(&binary)
+        .env("VENEER_C_ABI", "set")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("test result: ok. 5 passed; 0 failed"));
+}
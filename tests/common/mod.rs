@@ -0,0 +1,54 @@
+//! Building the fixture crates in `tests/`, which are programs on top of the runtime
+
+// Each test crate uses only some of this
+#![allow(dead_code)]
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Flags for a static position-independent executable, which must relocate itself
+///
+/// rustc only links a static PIE on targets which say they support one, which i686 does not, so
+/// the linker is also asked for one directly.
+pub const STATIC_PIE: &str = "-C relocation-model=pie -C target-feature=+crt-static \
+    -C link-arg=-nostartfiles -C link-arg=-static-pie";
+
+/// Flags for a static executable which runs at the addresses it was linked at
+pub const STATIC: &str =
+    "-C relocation-model=static -C target-feature=+crt-static -C link-arg=-nostartfiles";
+
+/// Build the binary of the fixture crate in `tests/{name}` with `features` enabled, and return its
+/// path
+pub fn build(name: &str, profile: &str, features: &[&str], rustflags: &str) -> PathBuf {
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join(name);
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    // Naming the target keeps these flags away from the proc macro, which can't be static. The
+    // target for 32-bit x86 is named after the i686 rather than the architecture.
+    let arch = match env::consts::ARCH {
+        "x86" => "i686",
+        arch => arch,
+    };
+    let target = format!("{}-unknown-linux-gnu", arch);
+    let mut cargo = Command::new(env!("CARGO"));
+    cargo
+        .arg("build")
+        .arg("--manifest-path")
+        .arg(fixture.join("Cargo.toml"))
+        .arg("--target")
+        .arg(&target)
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .arg("--features")
+        .arg(features.join(","))
+        .env("RUSTFLAGS", rustflags);
+    if profile == "release" {
+        cargo.arg("--release");
+    }
+    assert!(cargo.status().unwrap().success());
+    target_dir.join(target).join(profile).join(name)
+}
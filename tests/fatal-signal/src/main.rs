@@ -0,0 +1,22 @@
+#![no_std]
+#![no_main]
+
+/// Use a page of stack in every call, so the stack runs out quickly
+#[allow(unconditional_recursion)]
+#[inline(never)]
+fn recurse(depth: usize) -> usize {
+    let frame = core::hint::black_box([depth as u8; 4096]);
+    recurse(depth + 1) + usize::from(frame[depth % frame.len()])
+}
+
+#[veneer::main]
+fn main(args: veneer::env::Args) -> u8 {
+    let local = 0u8;
+    // Where the stack is, to compare with the stack pointer the handler reports
+    veneer::println!("stack at {:#x}", core::hint::black_box(&local) as *const u8 as usize);
+    match args.skip(1).next().map(|arg| arg.as_bytes()) {
+        Some(b"overflow") => recurse(0) as u8,
+        Some(b"null") => unsafe { core::ptr::read_volatile(core::ptr::null::<u8>()) },
+        _ => 2,
+    }
+}
@@ -0,0 +1,73 @@
+//! Runs a program built with the `rt-signals` feature which faults, and checks what the handler
+//! reports before the signal kills it
+
+use std::{
+    os::unix::process::ExitStatusExt,
+    process::{Command, Output},
+};
+
+mod common;
+
+const SIGSEGV: i32 = 11;
+
+// The name the report gives the stack pointer
+#[cfg(target_arch = "x86_64")]
+const SP: &str = "rsp";
+#[cfg(target_arch = "x86")]
+const SP: &str = "esp";
+#[cfg(target_arch = "aarch64")]
+const SP: &str = "sp";
+
+fn fault(how: &str) -> (Output, String) {
+    let binary = common::build("fatal-signal", "debug", &[], common::STATIC_PIE);
+    let output = Command::new(&binary).arg(how).output().unwrap();
+    // The handler lets the original signal kill the process
+    assert_eq!(output.status.signal(), Some(SIGSEGV), "{:?}", output);
+    let report = String::from_utf8(output.stderr.clone()).unwrap();
+    (output, report)
+}
+
+/// The number after `label` in `text`, which is printed in hex
+fn hex_after(text: &str, label: &str) -> usize {
+    let mut words = text.split_whitespace();
+    words.find(|word| *word == label).unwrap();
+    let number = words.next().unwrap();
+    usize::from_str_radix(number.trim_start_matches("0x"), 16).unwrap()
+}
+
+#[test]
+fn reports_null_dereference() {
+    let (output, report) = fault("null");
+    assert!(
+        report.contains("\nfatal signal SIGSEGV at address "),
+        "{}",
+        report
+    );
+    assert_eq!(hex_after(&report, "address"), 0, "{}", report);
+    assert!(!report.contains("overflowed"), "{}", report);
+
+    // The registers are read from the right places in the context the kernel saved
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stack = hex_after(&stdout, "at");
+    let sp = hex_after(&report, SP);
+    assert!(
+        sp <= stack && stack - sp < 64 * 1024,
+        "{} is {:#x} but main's stack is at {:#x}\n{}",
+        SP,
+        sp,
+        stack,
+        report
+    );
+}
+
+#[test]
+fn reports_stack_overflow() {
+    // Only a handler on the alternate signal stack can run once the stack is exhausted
+    let (_, report) = fault("overflow");
+    assert!(
+        report.contains("\nthread main has overflowed its stack\nfatal signal SIGSEGV at address "),
+        "{}",
+        report
+    );
+    assert_ne!(hex_after(&report, "address"), 0, "{}", report);
+}
@@ -0,0 +1,27 @@
+//! Runs tests which inject failures into system calls with the `fault-injection` feature
+
+use std::process::Command;
+
+mod common;
+
+#[test]
+fn injected_faults() {
+    let binary = common::build("faults", "debug", &[], common::STATIC_PIE);
+
+    let output = Command::new(&binary).output().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("test result: ok. 6 passed; 0 failed; 1 ignored"));
+
+    let output = Command::new(&binary)
+        .arg("--ignored")
+        .env("VENEER_FAULTS", "faccessat=EACCES,read@0=EIO")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        output.stderr, b"veneer: ignoring fault rule 'read@0=EIO'\n",
+        "{:?}",
+        output
+    );
+}
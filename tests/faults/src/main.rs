@@ -0,0 +1,123 @@
+#![no_std]
+#![no_main]
+
+use core::time::Duration;
+use veneer::{
+    fd,
+    fs::{self, Directory, File},
+    io::Write,
+    process,
+    syscalls::{
+        self,
+        fault::{self, Fault, Schedule},
+        probe::{self, Capability, Support},
+    },
+    uapi, CStr,
+};
+
+const CONTENTS: &[u8] = b"contents which take several short reads\n";
+
+fn create(path: &[u8]) {
+    File::create(path).unwrap().write_all(CONTENTS).unwrap();
+}
+
+#[veneer::test]
+fn reads_are_retried_when_interrupted() {
+    create(b"/tmp/veneer-faults-interrupted\0");
+    fault::inject("read", Fault::Fail(uapi::EINTR), Schedule::Every(2)).unwrap();
+    assert_eq!(fs::read(b"/tmp/veneer-faults-interrupted\0").unwrap(), CONTENTS);
+}
+
+#[veneer::test]
+fn short_reads() {
+    create(b"/tmp/veneer-faults-short-reads\0");
+    fault::inject("read", Fault::Limit(3), Schedule::Every(1)).unwrap();
+    assert_eq!(fs::read(b"/tmp/veneer-faults-short-reads\0").unwrap(), CONTENTS);
+}
+
+#[veneer::test]
+fn write_all_finishes_short_and_interrupted_writes() {
+    fault::inject("write", Fault::Limit(7), Schedule::Every(1)).unwrap();
+    fault::inject("write", Fault::Fail(uapi::EINTR), Schedule::Every(3)).unwrap();
+    create(b"/tmp/veneer-faults-short-writes\0");
+    fault::clear();
+    assert_eq!(fs::read(b"/tmp/veneer-faults-short-writes\0").unwrap(), CONTENTS);
+}
+
+#[veneer::test]
+fn directory_errors_are_returned() {
+    fault::inject("getdents64", Fault::Fail(uapi::EIO), Schedule::Nth(2)).unwrap();
+    let dir = Directory::open(CStr::from_bytes(b"/\0")).unwrap();
+    assert_eq!(dir.read().err().map(|e| e.0), Some(uapi::EIO));
+}
+
+#[veneer::test]
+fn only_the_scheduled_call_fails() {
+    fault::inject("close", Fault::Fail(uapi::EBADF), Schedule::Nth(2)).unwrap();
+    let fd = || {
+        syscalls::openat(
+            fd::CWD,
+            CStr::from_bytes(b"/\0"),
+            syscalls::OpenFlags::RDONLY,
+            syscalls::OpenMode::empty(),
+        )
+        .unwrap()
+    };
+    assert!(syscalls::close(fd()).is_ok());
+    assert_eq!(syscalls::close(fd()).unwrap_err(), uapi::EBADF);
+    assert!(syscalls::close(fd()).is_ok());
+}
+
+#[veneer::test]
+fn newer_syscalls_fall_back() {
+    for syscall in ["statx", "clone3", "openat2", "copy_file_range", "pidfd_open"] {
+        fault::inject(syscall, Fault::Fail(uapi::ENOSYS), Schedule::Every(1)).unwrap();
+    }
+
+    create(b"/tmp/veneer-faults-fallback\0");
+    let metadata = fs::metadata(b"/tmp/veneer-faults-fallback\0").unwrap();
+    assert_eq!(metadata.size(), CONTENTS.len() as u64);
+    assert_eq!(metadata.created(), None);
+
+    let copied = fs::copy(
+        b"/tmp/veneer-faults-fallback\0",
+        b"/tmp/veneer-faults-fallback-copy\0",
+    );
+    assert_eq!(copied.unwrap(), CONTENTS.len() as u64);
+    assert_eq!(fs::read(b"/tmp/veneer-faults-fallback-copy\0").unwrap(), CONTENTS);
+
+    let dir = Directory::open(CStr::from_bytes(b"/tmp\0")).unwrap();
+    assert!(dir
+        .open_file(CStr::from_bytes(b"veneer-faults-fallback\0"))
+        .is_ok());
+    let escape = dir.open_file(CStr::from_bytes(b"../etc/hostname\0"));
+    assert_eq!(escape.err().map(|e| e.0), Some(uapi::EXDEV));
+
+    let pid = syscalls::fork().unwrap();
+    if pid == 0 {
+        syscalls::exit_group(7);
+    }
+    let status = process::wait_timeout(pid, Duration::from_secs(10)).unwrap();
+    assert_eq!(status.map(|status| (status >> 8) & 0xff), Some(7));
+
+    for (capability, support) in probe::report() {
+        // The harness forked this test with clone3, which is then trusted and only fails this time
+        if capability != Capability::Clone3 {
+            assert_eq!(support, Support::Unsupported, "{:?}", capability);
+        }
+    }
+    assert!(!probe::detect(Capability::Statx));
+}
+
+// Run with VENEER_FAULTS=faccessat=EACCES
+#[veneer::test]
+#[ignore]
+fn rules_from_the_environment() {
+    let access = syscalls::faccessat(fd::CWD, CStr::from_bytes(b"/\0"), 0);
+    assert_eq!(access.unwrap_err(), uapi::EACCES);
+}
+
+#[veneer::main]
+fn main() -> veneer::process::ExitCode {
+    veneer::test_main()
+}
@@ -0,0 +1,64 @@
+//! Runs the tests of a program that uses `#[veneer::test]`, and checks what it reports
+
+use std::process::Command;
+
+mod common;
+
+#[test]
+fn runs_tests() {
+    let binary = common::build("harness", "debug", &[], common::STATIC_PIE);
+
+    let output = Command::new(&binary).output().unwrap();
+    assert_eq!(output.status.code(), Some(101), "{:?}", output);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    let results = stdout
+        .lines()
+        .filter(|line| line.starts_with("test "))
+        .collect::<Vec<_>>();
+    // Tests run in order of their names
+    assert_eq!(
+        results[..results.len() - 1],
+        [
+            "test does_not_panic - should panic ... FAILED",
+            "test fails ... FAILED",
+            "test hook_takes_itself - should panic ... ok",
+            "test ignored ... ignored",
+            "test nested::returns_error ... FAILED",
+            "test panics - should panic ... ok",
+            "test passes ... ok",
+        ]
+    );
+    assert!(results[results.len() - 1].starts_with(
+        "test result: FAILED. 3 passed; 3 failed; 1 ignored; 0 measured; 0 filtered out; finished in "
+    ));
+
+    // Output is only shown for the tests that failed
+    assert!(stdout.contains("---- fails stdout ----\noutput from a failing test\n"));
+    assert!(!stdout.contains("only shown if the test fails"));
+    assert!(stdout.contains("note: test did not panic as expected"));
+    assert!(stdout.contains("Error: something went wrong"));
+    assert!(
+        stdout.contains("failures:\n    does_not_panic\n    fails\n    nested::returns_error\n")
+    );
+}
+
+#[test]
+fn filters_tests() {
+    let binary = common::build("harness", "debug", &[], common::STATIC_PIE);
+
+    let output = Command::new(&binary).arg("pass").output().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("\nrunning 1 test\ntest passes ... ok\n"));
+    assert!(stdout
+        .contains("test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 6 filtered out"));
+
+    let output = Command::new(&binary)
+        .args(["--ignored", "--exact", "ignored"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("test ignored ... FAILED"));
+    assert!(stdout.contains("ignored tests do not run"));
+}
@@ -0,0 +1,57 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use alloc::{boxed::Box, vec::Vec};
+
+#[veneer::test]
+fn passes() {
+    veneer::println!("this is only shown if the test fails");
+    let squares: Vec<u32> = (1..4).map(|n| n * n).collect();
+    assert_eq!(squares, [1, 4, 9]);
+}
+
+#[veneer::test]
+fn fails() {
+    veneer::println!("output from a failing test");
+    assert_eq!(1 + 1, 3);
+}
+
+#[veneer::test]
+#[should_panic(expected = "out of range")]
+fn panics() {
+    panic!("index out of range");
+}
+
+#[veneer::test]
+#[should_panic]
+fn does_not_panic() {}
+
+#[veneer::test]
+#[should_panic(expected = "hook ran")]
+fn hook_takes_itself() {
+    veneer::panic::set_hook(Box::new(|_| {
+        drop(veneer::panic::take_hook());
+        veneer::println!("hook ran");
+    }));
+    panic!("reported by the hook");
+}
+
+#[veneer::test]
+#[ignore]
+fn ignored() {
+    panic!("ignored tests do not run");
+}
+
+mod nested {
+    #[veneer::test]
+    fn returns_error() -> Result<(), &'static str> {
+        Err("something went wrong")
+    }
+}
+
+#[veneer::main]
+fn main() -> veneer::process::ExitCode {
+    veneer::test_main()
+}
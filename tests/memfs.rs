@@ -0,0 +1,15 @@
+//! Runs tests which select an in-memory filesystem as the backend for `fs`
+
+use std::process::Command;
+
+mod common;
+
+#[test]
+fn in_memory_filesystem() {
+    let binary = common::build("memfs", "debug", &[], common::STATIC_PIE);
+
+    let output = Command::new(&binary).output().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("test result: ok. 4 passed; 0 failed"));
+}
@@ -0,0 +1,75 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use alloc::{boxed::Box, vec::Vec};
+use veneer::{
+    fd,
+    fs::{self, backend::Kernel, Directory, File, MemFs},
+    io::{Read, Write},
+    syscalls, uapi, CStr,
+};
+
+fn select(memfs: MemFs) -> &'static MemFs {
+    let memfs = Box::leak(Box::new(memfs));
+    fs::set_backend(memfs);
+    memfs
+}
+
+#[veneer::test]
+fn files_never_reach_the_disk() {
+    let memfs = MemFs::new();
+    memfs.create_dir(b"/tmp", 0o777).unwrap();
+    select(memfs);
+
+    let path = b"/tmp/veneer-memfs-only\0";
+    File::create(path).unwrap().write_all(b"in memory").unwrap();
+    assert_eq!(fs::read(path).unwrap(), b"in memory");
+    let on_disk = syscalls::faccessat(fd::CWD, CStr::from_bytes(path), uapi::F_OK);
+    assert_eq!(on_disk.unwrap_err(), uapi::ENOENT);
+}
+
+#[veneer::test]
+fn directories_list_entries_in_creation_order() {
+    let memfs = MemFs::new();
+    for name in [&b"/zebra"[..], b"/apple", b"/mango"] {
+        memfs.create_file(name, b"", 0o644).unwrap();
+    }
+    memfs.symlink(b"zebra", b"/link").unwrap();
+    select(memfs);
+
+    let dir = Directory::open(CStr::from_bytes(b"/\0")).unwrap();
+    let contents = dir.read().unwrap();
+    let names = contents
+        .iter()
+        .map(|entry| entry.name().as_bytes())
+        .collect::<Vec<_>>();
+    assert_eq!(names, [&b"."[..], b"..", b"zebra", b"apple", b"mango", b"link"]);
+}
+
+#[veneer::test]
+fn open_files_keep_their_backend() {
+    let memfs = select(MemFs::new());
+    memfs.create_file(b"/file", b"contents", 0o644).unwrap();
+    let mut file = File::open(b"/file\0").unwrap();
+    fs::set_backend(&Kernel);
+
+    let mut buf = [0; 16];
+    assert_eq!(file.read(&mut buf).unwrap(), 8);
+    assert_eq!(&buf[..8], b"contents");
+}
+
+#[veneer::test]
+fn permissions_are_checked() {
+    let memfs = select(MemFs::new());
+    memfs.create_file(b"/secret", b"", 0o200).unwrap();
+    assert_eq!(fs::read(b"/secret\0").unwrap_err(), uapi::EACCES);
+    memfs.set_permissions(b"/secret", 0o600).unwrap();
+    assert_eq!(fs::read(b"/secret\0").unwrap(), b"");
+}
+
+#[veneer::main]
+fn main() -> veneer::process::ExitCode {
+    veneer::test_main()
+}
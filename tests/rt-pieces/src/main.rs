@@ -0,0 +1,100 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+
+fn run() -> u8 {
+    let message: Box<str> = "ok".into();
+    veneer::println!("{}", message);
+    0
+}
+
+#[cfg(feature = "start")]
+#[veneer::main]
+fn main() -> u8 {
+    run()
+}
+
+#[cfg(all(
+    not(feature = "start"),
+    any(target_arch = "x86_64", target_arch = "x86")
+))]
+core::arch::global_asm!(
+    ".globl _start",
+    "_start:",
+    "xor ebp, ebp",
+    "call {main}",
+    main = sym own_main,
+);
+
+#[cfg(all(not(feature = "start"), target_arch = "aarch64"))]
+core::arch::global_asm!(
+    ".globl _start",
+    "_start:",
+    "mov x29, xzr",
+    "mov x30, xzr",
+    "bl {main}",
+    main = sym own_main,
+);
+
+#[cfg(not(feature = "start"))]
+extern "C" fn own_main() -> ! {
+    veneer::process::exit(run().into())
+}
+
+#[cfg(not(feature = "alloc"))]
+mod bump {
+    use core::{
+        alloc::{GlobalAlloc, Layout},
+        cell::UnsafeCell,
+        sync::atomic::{AtomicUsize, Ordering::SeqCst},
+    };
+
+    struct Bump {
+        arena: UnsafeCell<[u8; 4096]>,
+        used: AtomicUsize,
+    }
+
+    unsafe impl Sync for Bump {}
+
+    unsafe impl GlobalAlloc for Bump {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let base = self.arena.get() as usize;
+            let mut start = 0;
+            let claimed = self.used.fetch_update(SeqCst, SeqCst, |used| {
+                start = (base + used + layout.align() - 1) & !(layout.align() - 1);
+                let end = start - base + layout.size();
+                if end <= 4096 {
+                    Some(end)
+                } else {
+                    None
+                }
+            });
+            match claimed {
+                Ok(_) => start as *mut u8,
+                Err(_) => core::ptr::null_mut(),
+            }
+        }
+
+        unsafe fn dealloc(&self, _ptr: *mut u8, _layout: Layout) {}
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: Bump = Bump {
+        arena: UnsafeCell::new([0; 4096]),
+        used: AtomicUsize::new(0),
+    };
+}
+
+#[cfg(not(feature = "panic"))]
+#[panic_handler]
+fn panic(_info: &core::panic::PanicInfo) -> ! {
+    veneer::process::abort()
+}
+
+// The precompiled alloc crate refers to the personality function even though nothing unwinds
+#[cfg(not(feature = "personality"))]
+#[no_mangle]
+extern "C" fn rust_eh_personality() {}
@@ -0,0 +1,26 @@
+//! Builds a program with every combination of the runtime's pieces, providing the rest itself
+//!
+//! Without `rt-start` nothing would relocate a position-independent executable, so these are
+//! linked at fixed addresses.
+
+use std::process::Command;
+
+mod common;
+
+const PIECES: [&str; 4] = ["start", "alloc", "panic", "personality"];
+
+#[test]
+fn every_combination() {
+    for combination in 0..1 << PIECES.len() {
+        let features = PIECES
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| combination & (1 << i) != 0)
+            .map(|(_, piece)| *piece)
+            .collect::<Vec<_>>();
+        let binary = common::build("rt-pieces", "debug", &features, common::STATIC);
+        let output = Command::new(&binary).output().unwrap();
+        assert!(output.status.success(), "{:?}: {:?}", features, output);
+        assert_eq!(output.stdout, b"ok\n", "{:?}", features);
+    }
+}
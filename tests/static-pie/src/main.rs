@@ -0,0 +1,43 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use core::sync::atomic::{AtomicI32, Ordering::SeqCst};
+
+// Pointers stored in statics are only correct once the runtime has relocated itself
+static WORDS: [&str; 2] = ["hello", "goodbye"];
+
+static ARGC: AtomicI32 = AtomicI32::new(0);
+
+// Registered the way the `ctor` crate does it
+#[used]
+#[link_section = ".init_array"]
+static CONSTRUCTOR: extern "C" fn(i32, *const *const u8, *const *const u8) = constructor;
+
+extern "C" fn constructor(argc: i32, _argv: *const *const u8, _envp: *const *const u8) {
+    ARGC.store(argc, SeqCst);
+}
+
+#[used]
+#[link_section = ".fini_array"]
+static DESTRUCTOR: extern "C" fn() = destructor;
+
+extern "C" fn destructor() {
+    veneer::println!("destroyed");
+}
+
+#[veneer::main]
+fn main(args: veneer::env::Args) -> u8 {
+    veneer::println!("constructed with {} arguments", ARGC.load(SeqCst));
+    // Calls through a vtable need relocations too
+    let greet: Box<dyn Fn(&[u8])> = Box::new(|name| {
+        veneer::println!("{}, {}", WORDS[0], core::str::from_utf8(name).unwrap());
+    });
+    for arg in args.skip(1) {
+        greet(arg.as_bytes());
+    }
+    veneer::println!("{}", WORDS[1]);
+    0
+}
@@ -0,0 +1,48 @@
+//! Builds a static position-independent executable on top of the runtime, and runs it
+
+use std::{convert::TryInto, fs, process::Command};
+
+mod common;
+
+const ELFCLASS32: u8 = 1;
+const ET_DYN: u16 = 3;
+const PT_INTERP: u32 = 3;
+
+fn check(profile: &str) {
+    let binary = common::build("static-pie", profile, &[], common::STATIC_PIE);
+
+    let elf = fs::read(&binary).unwrap();
+    let half = |at: usize| u16::from_ne_bytes([elf[at], elf[at + 1]]);
+    // Only a position-independent executable can be loaded at a random address
+    assert_eq!(half(16), ET_DYN);
+    // And a static one has no interpreter to relocate it
+    let word = |at: usize| u32::from_ne_bytes(elf[at..at + 4].try_into().unwrap());
+    // The header fields after e_entry move up when addresses are 4 bytes rather than 8
+    let (phoff, phentsize, phnum) = if elf[4] == ELFCLASS32 {
+        (word(28) as usize, half(42), half(44))
+    } else {
+        let phoff = u64::from_ne_bytes(elf[32..40].try_into().unwrap());
+        (phoff as usize, half(54), half(56))
+    };
+    for i in 0..usize::from(phnum) {
+        let phdr = phoff + i * usize::from(phentsize);
+        assert_ne!(word(phdr), PT_INTERP);
+    }
+
+    let output = Command::new(&binary).arg("world").output().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        output.stdout,
+        b"constructed with 2 arguments\nhello, world\ngoodbye\ndestroyed\n"
+    );
+}
+
+#[test]
+fn static_pie_debug() {
+    check("debug");
+}
+
+#[test]
+fn static_pie_release() {
+    check("release");
+}
@@ -0,0 +1,74 @@
+//! Runs a program built with the `trace` feature, and checks the system calls it logs
+
+use std::process::Command;
+
+mod common;
+
+#[test]
+fn logs_syscalls() {
+    let binary = common::build("trace", "debug", &[], common::STATIC_PIE);
+
+    // Nothing is logged unless it is asked for
+    let output = Command::new(&binary).output().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(output.stdout, b"hello\n");
+    assert!(output.stderr.is_empty(), "{:?}", output);
+
+    let output = Command::new(&binary)
+        .env("VENEER_TRACE", "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(output.stdout, b"hello\n");
+    let log = String::from_utf8(output.stderr).unwrap();
+    assert!(log.contains("write(1, 6) = 6\n"), "{}", log);
+    // Opens on 32-bit targets always add LARGEFILE
+    let flags = if cfg!(target_pointer_width = "32") {
+        "CLOEXEC | LARGEFILE"
+    } else {
+        "CLOEXEC"
+    };
+    let open = format!(
+        "openat(-100, \"/nonexistent\", OpenFlags({}), OpenMode(0x0)) = -1 ENOENT\n",
+        flags
+    );
+    assert!(log.contains(&open), "{}", log);
+
+    // The summary comes just before the process exits
+    let summary = &log[log.find("     calls     errors syscall\n").unwrap()..];
+    assert!(
+        summary.contains("\n         1          0 write\n"),
+        "{}",
+        summary
+    );
+    assert!(
+        summary.contains("\n         1          1 openat\n"),
+        "{}",
+        summary
+    );
+    assert!(
+        summary.ends_with(" total\nexit_group(0) = ?\n"),
+        "{}",
+        summary
+    );
+}
+
+#[test]
+fn logs_to_a_chosen_fd() {
+    let binary = common::build("trace", "debug", &[], common::STATIC_PIE);
+
+    let output = Command::new(&binary)
+        .env("VENEER_TRACE", "1")
+        .env("VENEER_TRACE_FD", "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    assert!(output.stderr.is_empty(), "{:?}", output);
+    let log = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        log.starts_with("hello\n") || log.contains("\nhello\n"),
+        "{}",
+        log
+    );
+    assert!(log.contains("write(1, 6) = 6\n"), "{}", log);
+}
@@ -0,0 +1,9 @@
+#![no_std]
+#![no_main]
+
+#[veneer::main]
+fn main() -> u8 {
+    veneer::println!("hello");
+    let _ = veneer::fs::File::open(b"/nonexistent\0");
+    0
+}
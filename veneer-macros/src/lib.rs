@@ -1,7 +1,10 @@
 #![feature(proc_macro_diagnostic, proc_macro_span, proc_macro_quote)]
 extern crate proc_macro;
 
-use proc_macro::{quote, Delimiter, Diagnostic, Level, TokenStream, TokenTree};
+use proc_macro::{
+    quote, Delimiter, Diagnostic, Group, Ident, Level, Punct, Spacing, Span, TokenStream,
+    TokenTree,
+};
 
 #[proc_macro_attribute]
 pub fn main(args: TokenStream, item: TokenStream) -> TokenStream {
@@ -35,17 +38,17 @@ pub fn main(args: TokenStream, item: TokenStream) -> TokenStream {
     let not_a_fn = Diagnostic::spanned(
         vec![span],
         Level::Error,
-        "Attribute macro veneer_macros::main may only be applied to functions which take no arguments",
+        "Attribute macro veneer_macros::main may only be applied to functions",
     );
 
-    let name = if let (
+    let (name, params) = if let (
         Some(TokenTree::Ident(f)),
         Some(TokenTree::Ident(name)),
-        Some(TokenTree::Group(args)),
+        Some(TokenTree::Group(params)),
     ) = (signature.get(0), signature.get(1), signature.get(2))
     {
-        if f.to_string() == "fn" && args.delimiter() == Delimiter::Parenthesis {
-            name
+        if f.to_string() == "fn" && params.delimiter() == Delimiter::Parenthesis {
+            (name, params)
         } else {
             not_a_fn.emit();
             return item;
@@ -55,27 +58,258 @@ pub fn main(args: TokenStream, item: TokenStream) -> TokenStream {
         return item;
     };
 
+    let call_args = match main_arguments(params.stream()) {
+        Some(args) => TokenTree::from(Group::new(Delimiter::Parenthesis, args)),
+        None => return item,
+    };
+
     let name = TokenTree::from(name.clone());
 
-    let header = if signature.len() == 3 {
-        quote! {
-            #[no_mangle]
-            unsafe extern "C" fn __veneer_main() {
-               $name();
-               veneer::syscalls::exit(0);
+    let header = quote! {
+        #[no_mangle]
+        #[allow(unreachable_code)]
+        unsafe extern "C" fn __veneer_main() {
+            let exit_code = veneer::process::Termination::report($name $call_args);
+            veneer::process::exit(exit_code.to_i32());
+        }
+    };
+    header.into_iter().chain(item.into_iter()).collect()
+}
+
+/// Registers a function as a test for `veneer::test_main`
+///
+/// Like with `#[test]`, the function may be followed by `#[ignore]` or `#[should_panic]`, which
+/// may give the text the panic message must contain as `#[should_panic(expected = "...")]`.
+#[proc_macro_attribute]
+pub fn test(args: TokenStream, item: TokenStream) -> TokenStream {
+    if !args.is_empty() {
+        Diagnostic::spanned(
+            vec![span_of(&args.into_iter().collect::<Vec<_>>())],
+            Level::Error,
+            "Attribute macro veneer_macros::test does not accept any arguments",
+        )
+        .emit();
+    }
+
+    let tokens = item.into_iter().collect::<Vec<_>>();
+    let mut kept = Vec::new();
+    let mut should_panic = quote!(veneer::testing::ShouldPanic::No);
+    let mut ignore = false;
+    let mut i = 0;
+    while let (Some(TokenTree::Punct(pound)), Some(TokenTree::Group(attr))) =
+        (tokens.get(i), tokens.get(i + 1))
+    {
+        if pound.as_char() != '#' || attr.delimiter() != Delimiter::Bracket {
+            break;
+        }
+        let attr_tokens = attr.stream().into_iter().collect::<Vec<_>>();
+        match attr_tokens.first() {
+            Some(TokenTree::Ident(ident)) if ident.to_string() == "should_panic" => {
+                match should_panic_kind(&attr_tokens) {
+                    Some(kind) => should_panic = kind,
+                    None => {
+                        Diagnostic::spanned(
+                            vec![attr.span()],
+                            Level::Error,
+                            "expected `#[should_panic]` or `#[should_panic(expected = \"...\")]`",
+                        )
+                        .emit();
+                        return TokenStream::new();
+                    }
+                }
             }
+            Some(TokenTree::Ident(ident)) if ident.to_string() == "ignore" => ignore = true,
+            _ => kept.extend_from_slice(&tokens[i..i + 2]),
         }
-    } else {
-        quote! {
-            #[no_mangle]
-            unsafe extern "C" fn __veneer_main() {
-               let exit_code = match $name() {
-                    Ok(()) => 0,
-                    Err(_) => 1,
-                };
-                veneer::syscalls::exit(exit_code);
+        i += 2;
+    }
+    kept.extend_from_slice(&tokens[i..]);
+
+    // The name is the identifier after `fn`, and the parameter list after that must be empty
+    let f = kept
+        .iter()
+        .position(|t| matches!(t, TokenTree::Ident(ident) if ident.to_string() == "fn"));
+    let name = match f.and_then(|f| Some((kept.get(f + 1)?, kept.get(f + 2)?))) {
+        Some((TokenTree::Ident(name), TokenTree::Group(params)))
+            if params.delimiter() == Delimiter::Parenthesis =>
+        {
+            if !params.stream().is_empty() {
+                Diagnostic::spanned(
+                    vec![params.span()],
+                    Level::Error,
+                    "veneer::test functions may not take any parameters",
+                )
+                .emit();
+                return kept.into_iter().collect();
             }
+            TokenTree::from(name.clone())
+        }
+        _ => {
+            Diagnostic::spanned(
+                vec![span_of(&kept)],
+                Level::Error,
+                "Attribute macro veneer_macros::test may only be applied to functions",
+            )
+            .emit();
+            return kept.into_iter().collect();
         }
     };
-    header.into_iter().chain(item.into_iter()).collect()
+    let ignore = TokenTree::from(Ident::new(
+        if ignore { "true" } else { "false" },
+        Span::call_site(),
+    ));
+
+    let registration = quote! {
+        const _: () = {
+            #[used]
+            #[link_section = "veneer_tests"]
+            static TEST: veneer::testing::Test = veneer::testing::Test {
+                module_path: module_path!(),
+                name: stringify!($name),
+                run: || veneer::process::Termination::report($name()).to_i32(),
+                should_panic: $should_panic,
+                ignore: $ignore,
+            };
+        };
+    };
+    kept.into_iter().chain(registration).collect()
+}
+
+/// Reads the contents of a `#[should_panic]` attribute, which may name the expected message
+/// either as `should_panic = "..."` or `should_panic(expected = "...")`
+fn should_panic_kind(attr: &[TokenTree]) -> Option<TokenStream> {
+    let expected = match attr.get(1..)? {
+        [] => return Some(quote!(veneer::testing::ShouldPanic::Yes)),
+        [TokenTree::Punct(eq), TokenTree::Literal(message)] if eq.as_char() == '=' => {
+            message.clone()
+        }
+        [TokenTree::Group(args)] if args.delimiter() == Delimiter::Parenthesis => {
+            match args.stream().into_iter().collect::<Vec<_>>().as_slice() {
+                [TokenTree::Ident(key), TokenTree::Punct(eq), TokenTree::Literal(message)]
+                    if key.to_string() == "expected" && eq.as_char() == '=' =>
+                {
+                    message.clone()
+                }
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    Some(quote!(veneer::testing::ShouldPanic::YesWithMessage($expected)))
+}
+
+/// Builds the argument list that `__veneer_main` passes to the user's main, one expression for
+/// each parameter. Returns `None` if any parameter is unsupported, after emitting a diagnostic
+/// for it.
+fn main_arguments(params: TokenStream) -> Option<TokenStream> {
+    let mut args = Vec::new();
+    let mut seen_args = false;
+    let mut seen_env = false;
+    let mut ok = true;
+
+    for param in split_params(params) {
+        // The type is everything after the first lone `:`, which separates it from the pattern
+        let colon = param.iter().enumerate().position(|(i, t)| {
+            is_colon(t)
+                && !param.get(i + 1).is_some_and(is_colon)
+                && !(i > 0 && is_colon(&param[i - 1]))
+        });
+        let ty = match colon {
+            Some(colon) if colon + 1 < param.len() => &param[colon + 1..],
+            _ => {
+                Diagnostic::spanned(
+                    vec![span_of(&param)],
+                    Level::Error,
+                    "veneer::main parameters must have the form `name: Type`",
+                )
+                .emit();
+                ok = false;
+                continue;
+            }
+        };
+
+        // Only plain paths like `Args` or `veneer::env::Args` are accepted
+        let is_path = ty
+            .iter()
+            .all(|t| matches!(t, TokenTree::Ident(_)) || is_colon(t));
+        let type_name = match ty.last() {
+            Some(TokenTree::Ident(ident)) if is_path => ident.to_string(),
+            _ => String::new(),
+        };
+
+        let (seen, expr) = match type_name.as_str() {
+            "Args" => (&mut seen_args, quote!(veneer::env::args())),
+            "Env" => (&mut seen_env, quote!(veneer::env::Env::current())),
+            _ => {
+                Diagnostic::spanned(
+                    vec![span_of(ty)],
+                    Level::Error,
+                    "veneer::main parameters must be of type `veneer::env::Args` or `veneer::env::Env`",
+                )
+                .emit();
+                ok = false;
+                continue;
+            }
+        };
+        if *seen {
+            Diagnostic::spanned(
+                vec![span_of(ty)],
+                Level::Error,
+                format!(
+                    "veneer::main may only accept one parameter of type `{}`",
+                    type_name
+                ),
+            )
+            .emit();
+            ok = false;
+            continue;
+        }
+        *seen = true;
+
+        if !args.is_empty() {
+            args.push(TokenTree::from(Punct::new(',', Spacing::Alone)));
+        }
+        args.extend(expr);
+    }
+
+    if ok {
+        Some(args.into_iter().collect())
+    } else {
+        None
+    }
+}
+
+/// Split a parameter list on the commas that are not nested inside generic arguments
+fn split_params(params: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut split = Vec::new();
+    let mut current = Vec::new();
+    let mut depth = 0usize;
+    for token in params {
+        if let TokenTree::Punct(punct) = &token {
+            match punct.as_char() {
+                '<' => depth += 1,
+                '>' => depth = depth.saturating_sub(1),
+                ',' if depth == 0 => {
+                    split.push(std::mem::take(&mut current));
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        current.push(token);
+    }
+    if !current.is_empty() {
+        split.push(current);
+    }
+    split
+}
+
+fn is_colon(token: &TokenTree) -> bool {
+    matches!(token, TokenTree::Punct(punct) if punct.as_char() == ':')
+}
+
+fn span_of(tokens: &[TokenTree]) -> Span {
+    let start = tokens.first().unwrap().span();
+    let end = tokens.last().unwrap().span();
+    start.join(end).unwrap_or(start)
 }